@@ -1,5 +1,17 @@
-use clap::Parser;
+use std::{
+    collections::{BTreeMap, HashSet},
+    ffi::OsString,
+    net::Ipv4Addr,
+    path::Path,
+};
+
+use anyhow::{Context, Result, anyhow};
+use clap::{
+    ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser, error::ErrorKind,
+    parser::ValueSource,
+};
 use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
 use tokio::net::lookup_host;
 use tracing::*;
 
@@ -7,46 +19,112 @@ static MANAGER: OnceCell<Manager> = OnceCell::new();
 
 struct Manager {
     clap_matches: Args,
+    /// Where each option's value came from: `default`, `file`, `env` or `cli`.
+    sources: BTreeMap<String, &'static str>,
 }
 
-#[derive(Debug, Parser)]
+/// A value from the configuration file, handed to clap as if typed on the command line.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ConfigValue {
+    Bool(bool),
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl std::fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigValue::Bool(value) => write!(f, "{value}"),
+            ConfigValue::Integer(value) => write!(f, "{value}"),
+            ConfigValue::Float(value) => write!(f, "{value}"),
+            ConfigValue::Text(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Every option can also be set from a configuration file, using the option's long name
+/// (`web-server` or `web_server`), and from a `BR4KCAM_`-prefixed environment variable.
+/// The command line overrides the environment, which overrides the file.
+#[derive(Debug, Parser, Serialize)]
 #[command(
     version = env!("CARGO_PKG_VERSION"),
     author = env!("CARGO_PKG_AUTHORS"),
     about = env!("CARGO_PKG_DESCRIPTION"),
 )]
 pub struct Args {
+    /// Reads options from a TOML or YAML file, picked by its extension.
+    #[arg(long, env = "BR4KCAM_CONFIG", value_parser = expand::<String>)]
+    config: Option<String>,
+
     /// Turns all log categories up to Debug, for more information check RUST_LOG env variable.
-    #[arg(short, long, value_parser = expand::<bool>)]
+    #[arg(short, long, env = "BR4KCAM_VERBOSE", value_parser = expand::<bool>)]
     verbose: bool,
 
     /// Sets the IP and port that the server will be provided.
-    #[arg(long, default_value = "0.0.0.0:8080", value_parser = expand::<String>)]
+    #[arg(
+        long,
+        env = "BR4KCAM_WEB_SERVER",
+        default_value = "0.0.0.0:8080",
+        value_parser = expand::<String>,
+    )]
     web_server: String,
 
     /// Turns all log categories up to Trace to the log file, for more information check RUST_LOG env variable.
-    #[arg(long)]
+    #[arg(long, env = "BR4KCAM_ENABLE_TRACING_LEVEL_LOG_FILE")]
     enable_tracing_level_log_file: bool,
 
     /// Specifies the path in which the logs will be stored.
-    #[arg(long, default_value = "./logs", value_parser = expand::<String>)]
+    #[arg(
+        long,
+        env = "BR4KCAM_LOG_PATH",
+        default_value = "./logs",
+        value_parser = expand::<String>,
+    )]
     log_path: Option<String>,
 
+    /// Specifies the path in which the images captured over MAVLink will be stored.
+    #[arg(
+        long,
+        env = "BR4KCAM_CAPTURES_PATH",
+        default_value = "./captures",
+        value_parser = expand::<String>,
+    )]
+    captures_path: String,
+
     /// Sets the default version used by the REST API, this will remove the prefix used by its path.
-    #[arg(long, default_value = "1", value_names = ["1"], value_parser = expand::<u8>)]
+    #[arg(
+        long,
+        env = "BR4KCAM_DEFAULT_API_VERSION",
+        default_value = "1",
+        value_names = ["1"],
+        value_parser = expand::<u8>,
+    )]
     default_api_version: u8,
 
     /// Sets the Mavlink Camera Manager address.
-    #[arg(long, default_value = "127.0.0.1:6020", value_parser = expand::<String>)]
+    #[arg(
+        long,
+        env = "BR4KCAM_MCM_ADDRESS",
+        default_value = "127.0.0.1:6020",
+        value_parser = expand::<String>,
+    )]
     mcm_address: String,
 
     /// Sets the file path for the autopilot lua script to control zoom and focus
-    #[arg(long, default_value = "./scripts/br4kcam.lua", value_parser = expand::<String>)]
+    #[arg(
+        long,
+        env = "BR4KCAM_AUTOPILOT_SCRIPTS_FILE",
+        default_value = "./scripts/br4kcam.lua",
+        value_parser = expand::<String>,
+    )]
     autopilot_scripts_file: Option<String>,
 
     /// Sets the settings file path
     #[arg(
         long,
+        env = "BR4KCAM_SETTINGS_FILE",
         value_name = "./settings.json",
         default_value = "~/.config/br4kcam-manager/settings.json",
         value_parser = expand::<String>,
@@ -54,12 +132,13 @@ pub struct Args {
     settings_file: String,
 
     /// Deletes settings file before starting.
-    #[arg(long,value_parser = expand::<bool>)]
+    #[arg(long, env = "BR4KCAM_RESET", value_parser = expand::<bool>)]
     reset: bool,
 
     /// Sets the mavlink connection string
     #[arg(
         long,
+        env = "BR4KCAM_MAVLINK",
         value_name = "<TYPE>:<IP/SERIAL>:<PORT/BAUDRATE>",
         default_value = "udpout:127.0.0.1:11001",
         value_parser = expand::<String>,
@@ -67,20 +146,63 @@ pub struct Args {
     mavlink: String,
 
     /// Sets the MAVLink System ID.
-    #[arg(long, value_name = "SYSTEM_ID", default_value = "1", value_parser = expand::<u8>)]
+    #[arg(
+        long,
+        env = "BR4KCAM_MAVLINK_SYSTEM_ID",
+        value_name = "SYSTEM_ID",
+        default_value = "1",
+        value_parser = expand::<u8>,
+    )]
     mavlink_system_id: u8,
 
     /// Sets the MAVLink Component ID.
-    #[arg(long, value_name = "COMPONENT_ID", default_value = "56", value_parser = expand::<u8>)]
+    #[arg(
+        long,
+        env = "BR4KCAM_MAVLINK_COMPONENT_ID",
+        value_name = "COMPONENT_ID",
+        default_value = "56",
+        value_parser = expand::<u8>,
+    )]
     mavlink_component_id: u8,
 
+    /// Sets how many times per second focus, zoom and tilt are published over MAVLink, 0 disables it.
+    #[arg(
+        long,
+        env = "BR4KCAM_MAVLINK_STATE_RATE",
+        value_name = "HZ",
+        default_value = "1",
+        value_parser = expand::<f32>,
+    )]
+    mavlink_state_rate: f32,
+
+    /// Runs without BlueOS, e.g. next to mavlink-router: no MAVLink endpoint is requested
+    /// and the autopilot is rebooted over MAVLink.
+    #[arg(long, env = "BR4KCAM_STANDALONE", value_parser = expand::<bool>)]
+    standalone: bool,
+
     /// Sets the BlueOS IP address.
-    #[arg(long, default_value = "127.0.0.1", value_parser = expand::<String>)]
+    #[arg(
+        long,
+        env = "BR4KCAM_BLUEOS_ADDRESS",
+        default_value = "127.0.0.1",
+        value_parser = expand::<String>,
+    )]
     blueos_address: String,
 
     /// Accept any ONVIF device named "hd" without checking hardware ID.
-    #[arg(long, value_parser = expand::<bool>)]
+    #[arg(long, env = "BR4KCAM_MCM_SKIP_HARDWARE_CHECK", value_parser = expand::<bool>)]
     mcm_skip_hardware_check: bool,
+
+    /// Registers cameras by IP address, comma separated, for setups without Mavlink Camera
+    /// Manager.
+    #[arg(
+        long,
+        env = "BR4KCAM_CAMERAS",
+        value_name = "IP,IP",
+        value_delimiter = ',',
+        value_parser = expand::<Ipv4Addr>,
+    )]
+    cameras: Vec<Ipv4Addr>,
 }
 
 fn expand<T: std::str::FromStr>(s: &str) -> Result<T, String>
@@ -96,13 +218,122 @@ where
 /// Constructs our manager, Should be done inside main
 #[instrument(level = "debug")]
 pub fn init() {
-    init_with(Args::parse());
+    let (args, sources) =
+        parse_layered(std::env::args_os().collect()).unwrap_or_else(|error| error.exit());
+
+    MANAGER.get_or_init(|| Manager {
+        clap_matches: args,
+        sources,
+    });
 }
 
 /// Constructs our manager, Should be done inside main
 #[instrument(level = "debug")]
 pub fn init_with(args: Args) {
-    MANAGER.get_or_init(|| Manager { clap_matches: args });
+    MANAGER.get_or_init(|| Manager {
+        clap_matches: args,
+        sources: BTreeMap::new(),
+    });
+}
+
+/// Parses `argv` on top of the environment and the configuration file it names.
+///
+/// Options the command line and the environment leave at their defaults are taken from
+/// the file and handed back to clap as arguments, so file values go through the same
+/// parsing and validation as everything else.
+fn parse_layered(
+    argv: Vec<OsString>,
+) -> Result<(Args, BTreeMap<String, &'static str>), clap::Error> {
+    let command = Args::command();
+    let matches = command.clone().try_get_matches_from(&argv)?;
+
+    let Some(config_file) = matches.get_one::<String>("config") else {
+        let args = Args::from_arg_matches(&matches)?;
+        return Ok((args, sources_of(&matches, &HashSet::new())));
+    };
+
+    let file = read_config_file(Path::new(config_file))
+        .map_err(|error| command.clone().error(ErrorKind::Io, format!("{error:#}")))?;
+
+    let mut layered = argv[..1].to_vec();
+    let mut from_file = HashSet::new();
+    for (key, value) in file {
+        let id = key.replace('-', "_");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_id() == id.as_str() && id != "config")
+        else {
+            return Err(command.clone().error(
+                ErrorKind::UnknownArgument,
+                format!("Unknown option {key:?} in {config_file:?}"),
+            ));
+        };
+
+        if !matches!(
+            matches.value_source(&id),
+            None | Some(ValueSource::DefaultValue)
+        ) {
+            continue;
+        }
+
+        let long = arg.get_long().unwrap_or(id.as_str());
+        match (arg.get_action(), value) {
+            (ArgAction::SetTrue, ConfigValue::Bool(true)) => {
+                layered.push(format!("--{long}").into())
+            }
+            (ArgAction::SetTrue, ConfigValue::Bool(false)) => {}
+            (ArgAction::SetTrue, value) => {
+                return Err(command.clone().error(
+                    ErrorKind::InvalidValue,
+                    format!("Option {key:?} in {config_file:?} must be true or false, not {value}"),
+                ));
+            }
+            (_, value) => layered.push(format!("--{long}={value}").into()),
+        }
+        from_file.insert(id);
+    }
+    layered.extend(argv.into_iter().skip(1));
+
+    let matches = command.try_get_matches_from(layered)?;
+    let args = Args::from_arg_matches(&matches)?;
+
+    Ok((args, sources_of(&matches, &from_file)))
+}
+
+fn read_config_file(path: &Path) -> Result<BTreeMap<String, ConfigValue>> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read configuration file {path:?}"))?;
+
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("toml") => {
+            toml::from_str(&contents).with_context(|| format!("Failed to parse TOML from {path:?}"))
+        }
+        Some("yaml" | "yml") => serde_yaml::from_str(&contents)
+            .with_context(|| format!("Failed to parse YAML from {path:?}")),
+        _ => Err(anyhow!(
+            "Configuration file {path:?} must end with .toml, .yaml or .yml"
+        )),
+    }
+}
+
+fn sources_of(matches: &ArgMatches, from_file: &HashSet<String>) -> BTreeMap<String, &'static str> {
+    matches
+        .ids()
+        .map(|id| {
+            let id = id.as_str();
+            let source = if from_file.contains(id) {
+                "file"
+            } else {
+                match matches.value_source(id) {
+                    Some(ValueSource::CommandLine) => "cli",
+                    Some(ValueSource::EnvVariable) => "env",
+                    _ => "default",
+                }
+            };
+
+            (id.to_owned(), source)
+        })
+        .collect()
 }
 
 /// Local acessor to the parsed Args
@@ -129,6 +360,12 @@ pub fn log_path() -> String {
         .expect("Clap arg \"log-path\" should always be \"Some(_)\" because of the default value.")
 }
 
+#[cfg(all(feature = "autopilot", feature = "mcm"))]
+#[instrument(level = "debug")]
+pub fn captures_path() -> String {
+    args().captures_path.clone()
+}
+
 #[instrument(level = "debug")]
 pub fn command_line_string() -> String {
     std::env::args().collect::<Vec<String>>().join(" ")
@@ -140,16 +377,39 @@ pub fn command_line() -> String {
     format!("{:#?}", args())
 }
 
+/// Every option's effective value, and whether it came from the defaults, the
+/// configuration file, the environment or the command line.
+#[instrument(level = "debug")]
+pub fn effective_configuration() -> serde_json::Value {
+    let manager = MANAGER.get().unwrap();
+
+    let serde_json::Value::Object(values) =
+        serde_json::to_value(&manager.clap_matches).expect("Args should serialize to JSON")
+    else {
+        unreachable!("Args is a struct");
+    };
+
+    values
+        .into_iter()
+        .map(|(id, value)| {
+            let source = manager.sources.get(&id).copied().unwrap_or("default");
+            (id, serde_json::json!({ "value": value, "source": source }))
+        })
+        .collect()
+}
+
 #[instrument(level = "debug")]
 pub async fn web_server() -> std::net::SocketAddr {
     resolve_address(&args().web_server).await.unwrap()
 }
 
+#[cfg(feature = "mcm")]
 #[instrument(level = "debug")]
 pub async fn mcm_address() -> std::net::SocketAddr {
     resolve_address(&args().mcm_address).await.unwrap()
 }
 
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug")]
 pub fn autopilot_scripts_file() -> String {
     let autopilot_scripts_file = args()
@@ -174,6 +434,7 @@ pub fn is_reset() -> bool {
     args().reset
 }
 
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug")]
 pub async fn mavlink_connection_string() -> String {
     let mavlink = args().mavlink.clone();
@@ -185,26 +446,42 @@ pub async fn mavlink_connection_string() -> String {
     format!("{kind}:{}", address.to_string())
 }
 
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug")]
 pub fn mavlink_system_id() -> u8 {
     args().mavlink_system_id
 }
 
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug")]
 pub fn mavlink_component_id() -> u8 {
     args().mavlink_component_id
 }
 
+#[cfg(feature = "autopilot")]
+#[instrument(level = "debug")]
+pub fn mavlink_state_rate() -> f32 {
+    args().mavlink_state_rate
+}
+
 #[instrument(level = "debug")]
 pub fn default_api_version() -> u8 {
     args().default_api_version
 }
 
+#[cfg(feature = "mcm")]
 #[instrument(level = "debug")]
 pub fn mcm_skip_hardware_check() -> bool {
     args().mcm_skip_hardware_check
 }
 
+#[cfg(feature = "autopilot")]
+#[instrument(level = "debug")]
+pub fn is_standalone() -> bool {
+    args().standalone
+}
+
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug")]
 pub async fn blueos_address() -> std::net::SocketAddr {
     let address = &args().blueos_address;
@@ -215,6 +492,11 @@ pub async fn blueos_address() -> std::net::SocketAddr {
     resolve_address(&address).await.unwrap()
 }
 
+#[instrument(level = "debug")]
+pub fn cameras() -> Vec<Ipv4Addr> {
+    args().cameras.clone()
+}
+
 #[instrument(level = "debug")]
 async fn resolve_address(address: &str) -> std::io::Result<std::net::SocketAddr> {
     let mut addrs = lookup_host(address).await?;
@@ -225,3 +507,53 @@ async fn resolve_address(address: &str) -> std::io::Result<std::net::SocketAddr>
         )
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_overrides_the_configuration_file() {
+        let path = std::env::temp_dir().join(format!("br4kcam-cli-{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "web-server = \"0.0.0.0:9090\"\nmavlink_system_id = 2\nverbose = true\n",
+        )
+        .unwrap();
+
+        let argv = [
+            "br4kcam-manager",
+            "--config",
+            path.to_str().unwrap(),
+            "--mavlink-system-id",
+            "3",
+        ]
+        .map(OsString::from)
+        .to_vec();
+        let (args, sources) = parse_layered(argv).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(args.web_server, "0.0.0.0:9090");
+        assert_eq!(sources["web_server"], "file");
+        assert!(args.verbose);
+        assert_eq!(args.mavlink_system_id, 3);
+        assert_eq!(sources["mavlink_system_id"], "cli");
+        assert_eq!(sources["mavlink_component_id"], "default");
+    }
+
+    #[test]
+    fn cameras_are_comma_separated() {
+        let argv = ["br4kcam-manager", "--cameras", "192.168.2.10,192.168.2.11"]
+            .map(OsString::from)
+            .to_vec();
+        let (args, _) = parse_layered(argv).unwrap();
+
+        assert_eq!(
+            args.cameras,
+            [
+                Ipv4Addr::new(192, 168, 2, 10),
+                Ipv4Addr::new(192, 168, 2, 11)
+            ]
+        );
+    }
+}
@@ -1,6 +1,10 @@
+use std::collections::{HashMap, hash_map::Entry};
+
 use anyhow::Result;
 use tracing::*;
 
+#[cfg(all(feature = "autopilot", feature = "mcm"))]
+use br4kcam_manager::{image_capture, mavlink_camera};
 use br4kcam_manager::{
     logger,
     web::{self, ShutdownReason},
@@ -13,11 +17,15 @@ async fn start_application(first_start: bool) -> Result<bool> {
 
     logger::init(cli::log_path(), cli::is_verbose(), cli::is_tracing());
 
-    autopilot::set_backend_version(format!(
-        "{}-{}",
-        env!("CARGO_PKG_VERSION"),
-        option_env!("VERGEN_GIT_SHA").unwrap_or("?"),
-    ));
+    #[cfg(feature = "autopilot")]
+    {
+        autopilot::set_backend_version(format!(
+            "{}-{}",
+            env!("CARGO_PKG_VERSION"),
+            option_env!("VERGEN_GIT_SHA").unwrap_or("?"),
+        ));
+        autopilot::set_actuators_state_rate(cli::mavlink_state_rate());
+    }
 
     info!(
         "{}, version: {}-{}, build date: {}",
@@ -32,76 +40,70 @@ async fn start_application(first_start: bool) -> Result<bool> {
     );
     debug!("Command line call: {}", cli::command_line_string());
     debug!("Command line input struct call: {}", cli::command_line());
+    web::routes::v1::info::set_configuration(cli::effective_configuration());
 
     settings::init(cli::settings_file(), first_start && cli::is_reset())
         .await
         .unwrap();
 
+    for hostname in cli::cameras() {
+        if let Err(error) = cameras::register_camera(hostname).await {
+            error!("Failed registering camera at {hostname}: {error:?}");
+        }
+    }
+
+    #[cfg(feature = "mcm")]
     let mcm_client_startup_task = tokio::spawn(mcm_client::init(
         cli::mcm_address().await,
         cli::mcm_skip_hardware_check(),
     ));
 
-    blueos_client::init(cli::blueos_address().await).await;
+    #[cfg(feature = "autopilot")]
+    let autopilot_startup_task = start_autopilot().await;
 
-    let autopilot_startup_task = tokio::spawn(async move {
-        let mut endpoint_failures = 0u32;
-        loop {
-            match blueos_client::create_mavlink_endpoint(&cli::mavlink_connection_string().await)
-                .await
-            {
-                Ok(()) => {
-                    autopilot::report_endpoint_setup(true, None);
-                    info!("Successfully created MAVLink endpoint!");
-                    break;
-                }
-                Err(error) => {
-                    let detail = format!("{error:?}");
-                    autopilot::report_endpoint_setup(false, Some(detail.clone()));
-                    if endpoint_failures == 0 {
-                        error!("Failed creating MAVLink Endpoint: {detail}");
-                    } else {
-                        warn!("Failed creating MAVLink Endpoint: {detail}");
-                    }
-                    endpoint_failures += 1;
-                    tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-                }
-            }
-        }
+    #[cfg(all(feature = "autopilot", feature = "mcm"))]
+    {
+        mavlink_camera::start();
+        image_capture::start(cli::captures_path(), cli::web_server().await);
+    }
+    let camera_identity_task = start_camera_identity();
 
-        // BlueOS users can delete our endpoint at runtime; re-create it while
-        // MAVLink is down so traffic can resume without restarting this process.
-        tokio::spawn(async move {
-            let mut warned = false;
-            loop {
-                tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
-                if !autopilot::needs_mavlink_endpoint_ensure() {
-                    warned = false;
-                    continue;
-                }
-                match blueos_client::ensure_mavlink_endpoint(
-                    &cli::mavlink_connection_string().await,
-                )
-                .await
-                {
-                    Ok(changed) => {
-                        autopilot::report_endpoint_setup(true, None);
-                        if changed {
-                            info!("Re-created BlueOS MAVLink endpoint");
-                        }
-                        warned = false;
-                    }
-                    Err(error) => {
-                        let detail = format!("{error:?}");
-                        autopilot::report_endpoint_setup(false, Some(detail.clone()));
-                        if !warned {
-                            warn!("Failed re-ensuring MAVLink endpoint: {detail}");
-                            warned = true;
-                        }
-                    }
-                }
-            }
-        });
+    let shutdown_reason = web::run(cli::web_server().await, cli::default_api_version()).await;
+
+    #[cfg(feature = "autopilot")]
+    autopilot_startup_task.abort();
+    camera_identity_task.abort();
+    #[cfg(feature = "mcm")]
+    {
+        mcm_client_startup_task.abort();
+        mcm_client::shutdown().await;
+    }
+    #[cfg(feature = "autopilot")]
+    autopilot::shutdown_actuators_stream().await;
+
+    if shutdown_reason == ShutdownReason::Signal {
+        return Ok(false);
+    }
+
+    Ok(true)
+}
+
+#[cfg(feature = "autopilot")]
+async fn start_autopilot() -> tokio::task::JoinHandle<()> {
+    if cli::is_standalone() {
+        autopilot::set_platform(autopilot::Platform::Standalone);
+        info!("Running standalone, without BlueOS");
+    } else {
+        autopilot::set_platform(autopilot::Platform::BlueOs);
+        blueos_client::init(cli::blueos_address().await).await;
+    }
+
+    tokio::spawn(async move {
+        match autopilot::platform() {
+            autopilot::Platform::BlueOs => setup_blueos_mavlink_endpoint().await,
+            // Something else, e.g. mavlink-router, already routes the autopilot to us.
+            autopilot::Platform::Standalone => autopilot::report_endpoint_setup(true, None),
+        }
 
         loop {
             if let Err(error) = autopilot::init(
@@ -121,20 +123,123 @@ async fn start_application(first_start: bool) -> Result<bool> {
 
             break;
         }
-    });
+    })
+}
 
-    let shutdown_reason = web::run(cli::web_server().await, cli::default_api_version()).await;
+/// Lens calibration and stream preferences belong to the physical unit: follow its serial
+/// across the UUIDs cameras are addressed by. The unit is named by its own system
+/// configuration, or by the serial ONVIF reports through MCM when that names none.
+fn start_camera_identity() -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async {
+        let mut changes = cameras::subscribe_cameras();
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+        // What each camera's system configuration named it, asked once per camera.
+        let mut configured = HashMap::new();
+        loop {
+            tokio::select! {
+                _ = changes.recv() => {}
+                _ = interval.tick() => {}
+            }
 
-    autopilot_startup_task.abort();
-    mcm_client_startup_task.abort();
-    mcm_client::shutdown().await;
-    autopilot::shutdown_actuators_stream().await;
+            let cameras = cameras::cameras().await;
+            configured.retain(|camera_uuid, _| cameras.contains_key(camera_uuid));
+            #[cfg(feature = "mcm")]
+            let onvif_serials = mcm_client::camera_serials().await;
 
-    if shutdown_reason == ShutdownReason::Signal {
-        return Ok(false);
+            for camera_uuid in cameras.keys().copied() {
+                if let Entry::Vacant(entry) = configured.entry(camera_uuid) {
+                    match br4kcam_commands::sys_config(camera_uuid).await {
+                        Ok(config) => {
+                            entry.insert(config.unit_serial().map(str::to_owned));
+                        }
+                        Err(error) => {
+                            debug!(
+                                "Failed reading the system configuration of camera {camera_uuid}: {error:?}"
+                            );
+                        }
+                    }
+                }
+
+                // Until the camera answers, which source names it is not known yet.
+                let Some(serial) = configured.get(&camera_uuid).cloned() else {
+                    continue;
+                };
+                #[cfg(feature = "mcm")]
+                let serial = serial.or_else(|| onvif_serials.get(&camera_uuid).cloned());
+                let Some(serial) = serial else {
+                    continue;
+                };
+
+                #[cfg(feature = "autopilot")]
+                let adopted = autopilot::adopt_camera_serial(camera_uuid, &serial).await;
+                #[cfg(not(feature = "autopilot"))]
+                let adopted = settings::adopt_serial(camera_uuid, &serial).await;
+
+                if let Err(error) = adopted {
+                    debug!("Failed adopting serial {serial:?} for camera {camera_uuid}: {error:?}");
+                }
+            }
+        }
+    })
+}
+
+/// Create our MAVLink endpoint on BlueOS, and keep re-creating it if a user deletes it.
+#[cfg(feature = "autopilot")]
+async fn setup_blueos_mavlink_endpoint() {
+    let mut endpoint_failures = 0u32;
+    loop {
+        match blueos_client::create_mavlink_endpoint(&cli::mavlink_connection_string().await).await
+        {
+            Ok(()) => {
+                autopilot::report_endpoint_setup(true, None);
+                info!("Successfully created MAVLink endpoint!");
+                break;
+            }
+            Err(error) => {
+                let detail = format!("{error:?}");
+                autopilot::report_endpoint_setup(false, Some(detail.clone()));
+                if endpoint_failures == 0 {
+                    error!("Failed creating MAVLink Endpoint: {detail}");
+                } else {
+                    warn!("Failed creating MAVLink Endpoint: {detail}");
+                }
+                endpoint_failures += 1;
+                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+            }
+        }
     }
 
-    Ok(true)
+    // BlueOS users can delete our endpoint at runtime; re-create it while
+    // MAVLink is down so traffic can resume without restarting this process.
+    tokio::spawn(async move {
+        let mut warned = false;
+        loop {
+            tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+            if !autopilot::needs_mavlink_endpoint_ensure() {
+                warned = false;
+                continue;
+            }
+            match blueos_client::ensure_mavlink_endpoint(&cli::mavlink_connection_string().await)
+                .await
+            {
+                Ok(changed) => {
+                    autopilot::report_endpoint_setup(true, None);
+                    if changed {
+                        info!("Re-created BlueOS MAVLink endpoint");
+                    }
+                    warned = false;
+                }
+                Err(error) => {
+                    let detail = format!("{error:?}");
+                    autopilot::report_endpoint_setup(false, Some(detail.clone()));
+                    if !warned {
+                        warn!("Failed re-ensuring MAVLink endpoint: {detail}");
+                        warned = true;
+                    }
+                }
+            }
+        }
+    });
 }
 
 #[tokio::main(flavor = "multi_thread")]
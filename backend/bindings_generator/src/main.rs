@@ -14,7 +14,8 @@ use br4kcam_commands::{
         advanced_display::AdvancedParameterSetting, base_display::BaseParameterSetting,
     },
 };
-use mcm_client::{Camera, Stream, mcm_types};
+use cameras::{Camera, Stream};
+use mcm_client::{StreamChannel, StreamPreferences, StreamSize, mcm_types};
 
 fn main() -> Result<()> {
     if let Err(error) = generate_typescript_bindings_for_mcm_client() {
@@ -63,6 +64,9 @@ fn generate_typescript_bindings_for_mcm_client() -> Result<()> {
     let ts_rs_bindings = [
         Camera::export_to_string()?,
         Stream::export_to_string()?,
+        StreamPreferences::export_to_string()?,
+        StreamChannel::export_to_string()?,
+        StreamSize::export_to_string()?,
         mcm_types::VideoEncodeType::export_to_string()?,
         mcm_types::CaptureConfiguration::export_to_string()?,
         mcm_types::VideoSourceType::export_to_string()?,
@@ -86,6 +90,7 @@ fn generate_typescript_bindings_for_mcm_client() -> Result<()> {
         mcm_types::OnvifDeviceInformation::export_to_string()?,
         mcm_types::PostStream::export_to_string()?,
         mcm_types::RemoveStream::export_to_string()?,
+        mcm_types::ThumbnailRequest::export_to_string()?,
         mcm_types::OnvifDevice::export_to_string()?,
         mcm_types::AuthenticateOnvifDeviceRequest::export_to_string()?,
         mcm_types::UnauthenticateOnvifDeviceRequest::export_to_string()?,
@@ -117,14 +122,44 @@ fn generate_typescript_bindings_for_autopilot() -> Result<()> {
         api::ActuatorsControl::export_to_string()?,
         api::Action::export_to_string()?,
         api::ActuatorsState::export_to_string()?,
+        api::JogVelocity::export_to_string()?,
+        api::ActuatorsStep::export_to_string()?,
         api::ActuatorsConfig::export_to_string()?,
         api::ActuatorsParametersConfig::export_to_string()?,
         api::ServoChannel::export_to_string()?,
         api::MountType::export_to_string()?,
+        api::TiltBackend::export_to_string()?,
         api::CameraID::export_to_string()?,
         api::ScriptFunction::export_to_string()?,
         api::FocusZoomPoints::export_to_string()?,
         api::FocusZoomPoint::export_to_string()?,
+        api::CalibrationCurve::export_to_string()?,
+        api::CalibrationSessionStart::export_to_string()?,
+        api::CalibrationStep::export_to_string()?,
+        api::CalibrationFocusNudge::export_to_string()?,
+        api::CalibrationSession::export_to_string()?,
+        api::CalibrationUnit::export_to_string()?,
+        api::CalibrationImportTarget::export_to_string()?,
+        api::CalibrationCsvImport::export_to_string()?,
+        api::CalibrationSamples::export_to_string()?,
+        api::CalibrationCsvImportReport::export_to_string()?,
+        api::CalibrationSet::export_to_string()?,
+        api::CalibrationAutoSwitch::export_to_string()?,
+        api::CalibrationSets::export_to_string()?,
+        api::CalibrationSetName::export_to_string()?,
+        api::CalibrationSetSave::export_to_string()?,
+        api::MotionProfiles::export_to_string()?,
+        api::ActuatorsPreset::export_to_string()?,
+        api::PresetSave::export_to_string()?,
+        api::PresetName::export_to_string()?,
+        api::OpticsMapping::export_to_string()?,
+        api::ZoomMappingPoint::export_to_string()?,
+        api::FocusDistancePoint::export_to_string()?,
+        api::VehicleTelemetry::export_to_string()?,
+        api::ImageCapture::export_to_string()?,
+        api::SettingsImportSummary::export_to_string()?,
+        api::VehicleProfiles::export_to_string()?,
+        api::AdoptVehicle::export_to_string()?,
     ]
     .join("\n\n");
 
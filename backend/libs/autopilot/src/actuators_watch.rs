@@ -2,7 +2,7 @@ use std::{
     collections::HashMap,
     sync::{
         Mutex,
-        atomic::{AtomicBool, AtomicUsize, Ordering},
+        atomic::{AtomicUsize, Ordering},
     },
     time::{Duration, Instant},
 };
@@ -20,6 +20,7 @@ use crate::{
     api,
     manager::{self, MANAGER},
     mavlink::Message,
+    message_loop,
 };
 
 const MIN_EMIT_INTERVAL: Duration = Duration::from_millis(100);
@@ -34,7 +35,6 @@ const RECEIVER_REOPEN_BACKOFF: Duration = Duration::from_secs(1);
 const SERVO_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
 
 static STATE_TX: OnceCell<broadcast::Sender<ActuatorsStateUpdate>> = OnceCell::new();
-static WATCHER_STARTED: AtomicBool = AtomicBool::new(false);
 static INTEREST: AtomicUsize = AtomicUsize::new(0);
 static INTEREST_CHANGED: Notify = Notify::const_new();
 /// Instant of the last SERVO-backed sample per camera (missing = never / invalidated).
@@ -222,14 +222,7 @@ pub fn subscribe() -> broadcast::Receiver<ActuatorsStateUpdate> {
 /// The watcher stays idle until [`add_interest`] is called: no high-rate stream
 /// is requested from the autopilot while there are no interested consumers.
 pub fn start() {
-    if WATCHER_STARTED
-        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
-        .is_err()
-    {
-        return;
-    }
-
-    tokio::spawn(actuators_watcher());
+    message_loop::spawn_once("actuators watcher", actuators_watcher());
 }
 
 /// Register interest in the SERVO stream, requesting it when the first one arrives.
@@ -350,13 +343,13 @@ async fn actuators_watcher() {
 
                             let mut manager = manager.write().await;
                             let mut updated = Vec::new();
-                            for (camera_uuid, actuators) in &mut manager.settings.actuators {
+                            for (camera_uuid, actuators) in manager.settings.actuators.iter_mut() {
                                 let state =
                                     manager::actuators_state_from_servo(actuators, &servo_output_raw);
                                 actuators.state = state;
                                 if interested {
-                                    updated.push(*camera_uuid);
-                                    gate.try_emit(*camera_uuid, state, &sender);
+                                    updated.push(camera_uuid);
+                                    gate.try_emit(camera_uuid, state, &sender);
                                 }
                             }
                             drop(manager);
@@ -473,6 +466,7 @@ mod tests {
             focus: Some(10.0),
             zoom: Some(20.0),
             tilt: Some(30.0),
+            ..Default::default()
         };
 
         gate.try_emit(camera_uuid, state, &sender);
@@ -507,11 +501,13 @@ mod tests {
             focus: Some(1.0),
             zoom: None,
             tilt: None,
+            ..Default::default()
         };
         let second = api::ActuatorsState {
             focus: Some(2.0),
             zoom: None,
             tilt: None,
+            ..Default::default()
         };
 
         gate.try_emit(camera_uuid, first, &sender);
@@ -542,6 +538,7 @@ mod tests {
             focus: Some(10.0),
             zoom: None,
             tilt: None,
+            ..Default::default()
         };
 
         gate.try_emit(camera_uuid, state, &sender);
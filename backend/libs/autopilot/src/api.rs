@@ -1,10 +1,12 @@
+use indexmap::IndexMap;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use uuid::Uuid;
 
 use crate::{CameraActuators, parameters::ActuatorsParameters};
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 pub struct ActuatorsControl {
     #[ts(as = "String")]
     pub camera_uuid: Uuid,
@@ -12,7 +14,7 @@ pub struct ActuatorsControl {
     pub action: Action,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 #[serde(tag = "action", content = "json")]
 pub enum Action {
     #[serde(rename = "exportLuaScript")]
@@ -31,16 +33,137 @@ pub enum Action {
     ResetActuatorsConfig,
     #[serde(rename = "forgetActuatorsConfig")]
     ForgetActuatorsConfig,
+    #[serde(rename = "startCalibrationSession")]
+    StartCalibrationSession(CalibrationSessionStart),
+    #[serde(rename = "getCalibrationSession")]
+    GetCalibrationSession,
+    #[serde(rename = "goToCalibrationStep")]
+    GoToCalibrationStep(CalibrationStep),
+    #[serde(rename = "nudgeCalibrationFocus")]
+    NudgeCalibrationFocus(CalibrationFocusNudge),
+    #[serde(rename = "captureCalibrationPoint")]
+    CaptureCalibrationPoint,
+    #[serde(rename = "previewCalibrationSession")]
+    PreviewCalibrationSession,
+    #[serde(rename = "commitCalibrationSession")]
+    CommitCalibrationSession,
+    #[serde(rename = "discardCalibrationSession")]
+    DiscardCalibrationSession,
+    #[serde(rename = "importCalibrationCsv")]
+    ImportCalibrationCsv(CalibrationCsvImport),
+    #[serde(rename = "getCalibrationSamples")]
+    GetCalibrationSamples,
+    #[serde(rename = "getCalibrationSets")]
+    GetCalibrationSets,
+    #[serde(rename = "saveCalibrationSet")]
+    SaveCalibrationSet(CalibrationSetSave),
+    #[serde(rename = "deleteCalibrationSet")]
+    DeleteCalibrationSet(CalibrationSetName),
+    #[serde(rename = "switchCalibrationSet")]
+    SwitchCalibrationSet(CalibrationSetName),
+    #[serde(rename = "setCalibrationAutoSwitch")]
+    SetCalibrationAutoSwitch(Option<CalibrationAutoSwitch>),
+    #[serde(rename = "getOpticsMapping")]
+    GetOpticsMapping,
+    #[serde(rename = "setOpticsMapping")]
+    SetOpticsMapping(Option<OpticsMapping>),
+    #[serde(rename = "jog")]
+    Jog(JogVelocity),
+    #[serde(rename = "stopJog")]
+    StopJog,
+    #[serde(rename = "stepActuators")]
+    StepActuators(ActuatorsStep),
+    #[serde(rename = "getMotionProfiles")]
+    GetMotionProfiles,
+    #[serde(rename = "setMotionProfiles")]
+    SetMotionProfiles(MotionProfiles),
+    #[serde(rename = "getPresets")]
+    GetPresets,
+    #[serde(rename = "savePreset")]
+    SavePreset(PresetSave),
+    #[serde(rename = "deletePreset")]
+    DeletePreset(PresetName),
+    #[serde(rename = "recallPreset")]
+    RecallPreset(PresetName),
 }
 
-#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Clone, Copy, TS)]
+/// Result of an [`ActuatorsControl`], shaped after its action.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ActuatorsControlResult {
+    /// `getActuatorsState`, `setActuatorsState`, `stepActuators` and `recallPreset`.
+    State(ActuatorsState),
+    /// `getActuatorsConfig`, `getActuatorsDefaultConfig`, `setActuatorsConfig`,
+    /// `resetActuatorsConfig` and `commitCalibrationSession`.
+    Config(Box<ActuatorsConfig>),
+    /// `startCalibrationSession`, `getCalibrationSession` while one runs,
+    /// `goToCalibrationStep`, `nudgeCalibrationFocus` and `captureCalibrationPoint`.
+    CalibrationSession(Box<CalibrationSession>),
+    /// `previewCalibrationSession`.
+    CalibrationPoints(FocusZoomPoints),
+    /// `importCalibrationCsv`.
+    CalibrationCsvImport(CalibrationCsvImportReport),
+    /// `getCalibrationSamples`.
+    CalibrationSamples(CalibrationSamples),
+    /// `getCalibrationSets` and every action changing them.
+    CalibrationSets(Box<CalibrationSets>),
+    /// `getOpticsMapping` and `setOpticsMapping`, once the camera has one.
+    OpticsMapping(OpticsMapping),
+    /// `jog`, with the velocity actually applied.
+    Jog(JogVelocity),
+    /// `getMotionProfiles` and `setMotionProfiles`.
+    MotionProfiles(MotionProfiles),
+    /// `getPresets`, `savePreset` and `deletePreset`.
+    Presets(IndexMap<String, ActuatorsPreset>),
+    /// Every other action, and the ones above when there is nothing to report.
+    None,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Clone, Copy, TS, JsonSchema)]
 pub struct ActuatorsState {
+    pub focus: Option<f32>,
+    pub zoom: Option<f32>,
+    /// Tilt output in percent of the channel range.
+    pub tilt: Option<f32>,
+    /// Camera pitch over the horizon in degrees, within `tilt_mnt_pitch_min` and
+    /// `tilt_mnt_pitch_max`. Takes precedence over `tilt` in setpoints.
+    #[serde(default)]
+    pub tilt_degrees: Option<f32>,
+    /// Optical magnification, e.g. `2.0` for 2x. Needs the camera's [`OpticsMapping`] and
+    /// takes precedence over `zoom` in setpoints.
+    #[serde(default)]
+    pub zoom_magnification: Option<f32>,
+    /// Horizontal field of view in degrees. Needs the camera's [`OpticsMapping`] and takes
+    /// precedence over `zoom` in setpoints, after `zoom_magnification`.
+    #[serde(default)]
+    pub horizontal_fov: Option<f32>,
+    /// Subject distance in focus, in meters. Needs the camera's [`OpticsMapping`] and takes
+    /// precedence over `focus` in setpoints.
+    #[serde(default)]
+    pub focus_distance: Option<f32>,
+}
+
+/// Velocity per axis, from -1 (full speed down) to 1 (full speed up). Must be refreshed at
+/// least every 500 ms, or the jog stops.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Clone, Copy, TS, JsonSchema)]
+pub struct JogVelocity {
     pub focus: Option<f32>,
     pub zoom: Option<f32>,
     pub tilt: Option<f32>,
 }
 
-#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Clone, TS)]
+/// Relative move per axis, from the current position. Axes left out do not move.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Clone, Copy, TS, JsonSchema)]
+pub struct ActuatorsStep {
+    /// Percent; negative is nearer.
+    pub focus: Option<f32>,
+    /// Percent; negative zooms out.
+    pub zoom: Option<f32>,
+    /// Degrees; negative tilts down.
+    pub tilt_degrees: Option<f32>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Clone, TS, JsonSchema)]
 pub struct ActuatorsConfig {
     pub parameters: Option<ActuatorsParametersConfig>,
     pub closest_points: Option<FocusZoomPoints>,
@@ -57,7 +180,7 @@ impl From<&CameraActuators> for ActuatorsConfig {
     }
 }
 
-#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Clone, TS)]
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize, Clone, TS, JsonSchema)]
 pub struct ActuatorsParametersConfig {
     // Camera parameters
     pub camera_id: Option<CameraID>,
@@ -94,6 +217,7 @@ pub struct ActuatorsParametersConfig {
     pub tilt_mnt_type: Option<MountType>,
     pub tilt_mnt_pitch_min: Option<i32>,
     pub tilt_mnt_pitch_max: Option<i32>,
+    pub tilt_backend: Option<TiltBackend>,
 }
 
 impl From<&ActuatorsParameters> for ActuatorsParametersConfig {
@@ -123,18 +247,19 @@ impl From<&ActuatorsParameters> for ActuatorsParametersConfig {
             tilt_mnt_type: Some(value.tilt_mnt_type),
             tilt_mnt_pitch_min: Some(value.tilt_mnt_pitch_min),
             tilt_mnt_pitch_max: Some(value.tilt_mnt_pitch_max),
+            tilt_backend: Some(value.tilt_backend),
         }
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 pub enum CameraID {
     #[default]
     CAM1 = 1,
     CAM2 = 2,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 #[repr(u8)]
 pub enum TiltChannelFunction {
     #[default]
@@ -144,7 +269,7 @@ pub enum TiltChannelFunction {
     MNT2 = 13,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 #[repr(u8)]
 pub enum MountType {
     #[default]
@@ -152,7 +277,17 @@ pub enum MountType {
     BrushlessPWM = 7,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+/// How tilt setpoints reach the mount.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+pub enum TiltBackend {
+    /// The `MNTx` servo output of the camera's mount, with the setpoint as feedback.
+    #[default]
+    Servo,
+    /// A gimbal that reports its attitude back with `GIMBAL_DEVICE_ATTITUDE_STATUS`.
+    GimbalManager,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 #[repr(u8)]
 pub enum ServoChannel {
     SERVO1 = 1,
@@ -189,7 +324,7 @@ pub enum ServoChannel {
     SERVO32 = 32,
 }
 
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 #[repr(u8)]
 pub enum ScriptFunction {
     #[default]
@@ -211,7 +346,7 @@ pub enum ScriptFunction {
     SCRIPT16 = 109,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, PartialOrd, TS)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, PartialOrd, TS, JsonSchema)]
 pub struct FocusZoomPoints(pub Vec<FocusZoomPoint>);
 impl FocusZoomPoints {
     pub fn to_lua(&self) -> String {
@@ -225,8 +360,278 @@ impl FocusZoomPoints {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, TS)]
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, TS, JsonSchema,
+)]
 pub struct FocusZoomPoint {
     pub zoom: u32,
     pub focus: u32,
 }
+
+/// Which focus/zoom curve a calibration session is building.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CalibrationCurve {
+    Closest,
+    Furthest,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationSessionStart {
+    pub curve: CalibrationCurve,
+    /// Zoom positions (0-100 %) to visit. Defaults to five evenly spaced steps.
+    pub zoom_steps: Option<Vec<f32>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationStep {
+    pub step: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationFocusNudge {
+    /// Focus change in percent, relative to the last focus commanded by the session.
+    pub delta: f32,
+}
+
+/// Guided calibration progress for one camera, shared by every client following along.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationSession {
+    pub curve: CalibrationCurve,
+    /// Zoom positions (0-100 %) visited by the session, in ascending order.
+    pub zoom_steps: Vec<f32>,
+    /// Index into `zoom_steps` the zoom was last moved to.
+    pub current_step: usize,
+    /// Last focus setpoint (0-100 %) commanded by the session.
+    pub focus: f32,
+    /// Captured point per step, in PWM, `None` until captured.
+    pub captured: Vec<Option<FocusZoomPoint>>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CalibrationUnit {
+    /// Raw PWM, in microseconds.
+    #[default]
+    Pwm,
+    /// 0-100 % of the zoom channel (zoom) and script channel (focus) ranges.
+    Percent,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CalibrationImportTarget {
+    /// Keep the measurements as samples, e.g. for offline curve optimization.
+    #[default]
+    Samples,
+    /// Use the measurements directly as the camera's calibration curves.
+    Calibration,
+}
+
+/// Calibration measurements in CSV form, as in `tools/calibration/data/*.csv`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationCsvImport {
+    pub csv: String,
+    /// Header of the zoom column. Defaults to `Zoom`.
+    pub zoom_column: Option<String>,
+    /// Header of the closest focus column. Defaults to `Close`, skipped when absent.
+    pub closest_column: Option<String>,
+    /// Header of the furthest focus column. Defaults to `Far`, skipped when absent.
+    pub furthest_column: Option<String>,
+    pub unit: Option<CalibrationUnit>,
+    pub target: Option<CalibrationImportTarget>,
+}
+
+/// Measured focus/zoom samples per curve, in PWM.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationSamples {
+    pub closest: FocusZoomPoints,
+    pub furthest: FocusZoomPoints,
+}
+
+/// Curves parsed from an imported CSV, in PWM. A curve is `None` when its column was absent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationCsvImportReport {
+    pub target: CalibrationImportTarget,
+    pub closest: Option<FocusZoomPoints>,
+    pub furthest: Option<FocusZoomPoints>,
+}
+
+/// A named pair of focus curves, e.g. one for bench testing in air and one for water.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationSet {
+    pub closest_points: FocusZoomPoints,
+    pub furthest_points: FocusZoomPoints,
+}
+
+/// Switch between two calibration sets based on the vehicle depth.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationAutoSwitch {
+    pub air_set: String,
+    pub water_set: String,
+    /// Depth, in meters, above which `water_set` is used.
+    pub depth_threshold: f32,
+}
+
+/// Every calibration set of a camera. The active one mirrors the camera's current curves.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationSets {
+    pub active: String,
+    pub sets: IndexMap<String, CalibrationSet>,
+    pub auto_switch: Option<CalibrationAutoSwitch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationSetName {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct CalibrationSetSave {
+    pub name: String,
+    /// Defaults to the active set's closest curve.
+    pub closest_points: Option<FocusZoomPoints>,
+    /// Defaults to the active set's furthest curve.
+    pub furthest_points: Option<FocusZoomPoints>,
+}
+
+/// Limits on how fast setpoints move each axis. Axes without a limit go straight to their
+/// target.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct MotionProfiles {
+    /// Percent per second.
+    pub focus_max_rate: Option<f32>,
+    /// Percent per second.
+    pub zoom_max_rate: Option<f32>,
+    /// Degrees per second.
+    pub tilt_max_rate: Option<f32>,
+    /// Degrees per second squared.
+    pub tilt_max_acceleration: Option<f32>,
+}
+
+/// A named position of a camera. Axes left out are not moved on recall.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct ActuatorsPreset {
+    /// Focus, 0-100 %.
+    pub focus: Option<f32>,
+    /// Zoom, 0-100 %.
+    pub zoom: Option<f32>,
+    /// Camera pitch over the horizon, in degrees.
+    pub tilt_degrees: Option<f32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct PresetSave {
+    pub name: String,
+    /// Defaults to the camera's current focus, zoom and tilt.
+    pub preset: Option<ActuatorsPreset>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct PresetName {
+    pub name: String,
+}
+
+/// Tables from the zoom and focus percentages to the optical units of a camera.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct OpticsMapping {
+    /// Sorted by zoom; empty when the zoom is not mapped.
+    pub zoom: Vec<ZoomMappingPoint>,
+    /// Sorted by focus; empty when the focus is not mapped.
+    pub focus: Vec<FocusDistancePoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct ZoomMappingPoint {
+    /// Zoom, 0-100 %.
+    pub zoom: f32,
+    pub magnification: f32,
+    /// Horizontal field of view in degrees.
+    pub horizontal_fov: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct FocusDistancePoint {
+    /// Lens position between the closest (0 %) and furthest (100 %) focus curves.
+    pub focus: f32,
+    /// Subject distance in meters.
+    pub distance: f32,
+}
+
+/// Latest vehicle position and attitude reported by the autopilot. Fields are `None` when
+/// the autopilot has not reported them recently.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct VehicleTelemetry {
+    /// Degrees.
+    pub latitude: Option<f64>,
+    /// Degrees.
+    pub longitude: Option<f64>,
+    /// Meters above mean sea level.
+    pub altitude: Option<f32>,
+    /// Meters above the home position.
+    pub relative_altitude: Option<f32>,
+    /// Meters below the surface.
+    pub depth: Option<f32>,
+    /// Degrees from north, 0 to 360.
+    pub heading: Option<f32>,
+    /// Degrees.
+    pub roll: Option<f32>,
+    /// Degrees.
+    pub pitch: Option<f32>,
+    /// Degrees.
+    pub yaw: Option<f32>,
+}
+
+/// A still image requested over MAVLink, with the vehicle state at the time of the request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct ImageCapture {
+    #[ts(as = "String")]
+    pub camera_uuid: Uuid,
+    /// Index of the image for this camera since startup, as reported to the GCS.
+    pub image_index: i32,
+    /// Microseconds since the UNIX epoch.
+    pub time_utc_us: u64,
+    pub telemetry: VehicleTelemetry,
+    pub actuators_state: Option<ActuatorsState>,
+}
+
+/// Cameras a settings import touches, compared with the current settings.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct SettingsImportSummary {
+    /// Cameras only the imported document configures.
+    #[ts(as = "Vec<String>")]
+    pub added: Vec<Uuid>,
+    /// Cameras whose configuration differs from the imported one.
+    #[ts(as = "Vec<String>")]
+    pub changed: Vec<Uuid>,
+    /// Cameras the imported document does not configure, forgotten by the import.
+    #[ts(as = "Vec<String>")]
+    pub removed: Vec<Uuid>,
+    /// Cameras configured identically on both sides.
+    #[ts(as = "Vec<String>")]
+    pub unchanged: Vec<Uuid>,
+    /// Whether the imported stream preferences differ from the current ones.
+    pub streams_changed: bool,
+    /// False for a dry run.
+    pub applied: bool,
+}
+
+/// Vehicles with stored actuator profiles, keyed by autopilot identity.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct VehicleProfiles {
+    /// The vehicle whose profile is the active configuration.
+    pub active: Option<String>,
+    /// Every other vehicle with a stored profile.
+    pub stored: Vec<String>,
+    /// A connected autopilot without a profile. Parameter pushes are refused until it is
+    /// adopted.
+    pub unknown: Option<String>,
+}
+
+/// Start a profile for the connected, unknown autopilot.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, TS, JsonSchema)]
+pub struct AdoptVehicle {
+    /// Start without cameras instead of copying the active vehicle's configuration.
+    #[serde(default)]
+    pub start_empty: bool,
+}
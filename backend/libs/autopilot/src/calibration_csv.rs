@@ -0,0 +1,321 @@
+//! Parses calibration measurements exported as CSV (see `tools/calibration/data`) into
+//! focus/zoom curves, in PWM.
+
+use anyhow::{Context, Result, anyhow};
+use tracing::*;
+
+use crate::{
+    api::{self, CalibrationUnit},
+    parameters::ActuatorsParameters,
+};
+
+const DEFAULT_ZOOM_COLUMN: &str = "Zoom";
+const DEFAULT_CLOSEST_COLUMN: &str = "Close";
+const DEFAULT_FURTHEST_COLUMN: &str = "Far";
+
+/// Parse `import.csv` and validate every curve against `parameters`' channel ranges.
+///
+/// Zoom is checked against the zoom channel and focus against the script channel, which
+/// is the output driving the lens. Each curve must be strictly increasing in zoom. Every
+/// row that cannot be imported is reported, not only the first one.
+#[instrument(level = "debug", skip_all)]
+pub(crate) fn parse(
+    import: &api::CalibrationCsvImport,
+    parameters: &ActuatorsParameters,
+) -> Result<api::CalibrationCsvImportReport> {
+    let unit = import.unit.unwrap_or_default();
+    // Spreadsheet exports may start with a byte order mark.
+    let mut lines = import
+        .csv
+        .trim_start_matches('\u{feff}')
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let (_, header) = lines.next().context("CSV is empty")?;
+    let header = split_row(header).context("Invalid header")?;
+
+    let zoom_index = column_index(&header, import.zoom_column.as_deref(), DEFAULT_ZOOM_COLUMN)?
+        .context("CSV has no zoom column")?;
+    let closest_index = column_index(
+        &header,
+        import.closest_column.as_deref(),
+        DEFAULT_CLOSEST_COLUMN,
+    )?;
+    let furthest_index = column_index(
+        &header,
+        import.furthest_column.as_deref(),
+        DEFAULT_FURTHEST_COLUMN,
+    )?;
+    if closest_index.is_none() && furthest_index.is_none() {
+        return Err(anyhow!("CSV has neither a closest nor a furthest column"));
+    }
+
+    let zoom_range = (parameters.zoom_channel_min, parameters.zoom_channel_max);
+    let focus_range = (parameters.script_channel_min, parameters.script_channel_max);
+
+    let mut closest = closest_index.map(|_| Vec::new());
+    let mut furthest = furthest_index.map(|_| Vec::new());
+    let mut row_errors = Vec::new();
+
+    for (line_index, line) in lines {
+        let row_number = line_index + 1;
+        let row = match split_row(line) {
+            Ok(row) => row,
+            Err(error) => {
+                row_errors.push(format!("Row {row_number}: {error}"));
+                continue;
+            }
+        };
+        let cell = |index: usize| row.get(index).map(|cell| cell.as_str()).unwrap_or("");
+
+        let zoom_cell = cell(zoom_index);
+        if zoom_cell.is_empty() {
+            row_errors.push(format!("Row {row_number}: missing zoom value"));
+            continue;
+        }
+        let zoom = match to_pwm(zoom_cell, unit, zoom_range) {
+            Ok(zoom) => zoom,
+            Err(error) => {
+                row_errors.push(format!("Row {row_number}, zoom {zoom_cell:?}: {error}"));
+                continue;
+            }
+        };
+
+        for (index, points) in [
+            (closest_index, &mut closest),
+            (furthest_index, &mut furthest),
+        ] {
+            let (Some(index), Some(points)) = (index, points.as_mut()) else {
+                continue;
+            };
+            let focus_cell = cell(index);
+            if focus_cell.is_empty() {
+                continue;
+            }
+            match to_pwm(focus_cell, unit, focus_range) {
+                Ok(focus) => points.push(api::FocusZoomPoint { zoom, focus }),
+                Err(error) => {
+                    row_errors.push(format!("Row {row_number}, focus {focus_cell:?}: {error}"))
+                }
+            }
+        }
+    }
+
+    if !row_errors.is_empty() {
+        return Err(anyhow!(
+            "{} rows cannot be imported:\n{}",
+            row_errors.len(),
+            row_errors.join("\n")
+        ));
+    }
+
+    for (name, points) in [("closest", &closest), ("furthest", &furthest)] {
+        let Some(points) = points else {
+            continue;
+        };
+        if let Some(pair) = points.windows(2).find(|pair| pair[0].zoom >= pair[1].zoom) {
+            return Err(anyhow!(
+                "The {name} curve is not strictly increasing in zoom ({} then {})",
+                pair[0].zoom,
+                pair[1].zoom
+            ));
+        }
+    }
+
+    Ok(api::CalibrationCsvImportReport {
+        target: import.target.unwrap_or_default(),
+        closest: closest.map(api::FocusZoomPoints),
+        furthest: furthest.map(api::FocusZoomPoints),
+    })
+}
+
+/// Split a CSV row into trimmed cells. Quoted cells may hold commas and `""` for a quote.
+fn split_row(line: &str) -> Result<Vec<String>> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+
+    let mut characters = line.chars().peekable();
+    while let Some(character) = characters.next() {
+        match (quoted, character) {
+            (true, '"') if characters.peek() == Some(&'"') => {
+                characters.next();
+                cell.push('"');
+            }
+            (true, '"') => quoted = false,
+            (false, '"') if cell.trim().is_empty() => {
+                cell.clear();
+                quoted = true;
+            }
+            (false, ',') => cells.push(std::mem::take(&mut cell).trim().to_string()),
+            _ => cell.push(character),
+        }
+    }
+    if quoted {
+        return Err(anyhow!("Unterminated quoted cell"));
+    }
+    cells.push(cell.trim().to_string());
+
+    Ok(cells)
+}
+
+/// Index of `requested` (or `default`) in `header`. A requested column must exist; a
+/// defaulted one is optional.
+fn column_index(
+    header: &[String],
+    requested: Option<&str>,
+    default: &str,
+) -> Result<Option<usize>> {
+    let name = requested.unwrap_or(default);
+    let index = header
+        .iter()
+        .position(|column| column.eq_ignore_ascii_case(name));
+
+    if index.is_none() && requested.is_some() {
+        return Err(anyhow!("CSV has no column named {name:?}"));
+    }
+
+    Ok(index)
+}
+
+/// Convert a cell to PWM, rejecting values outside the `(min, max)` channel range.
+fn to_pwm(cell: &str, unit: CalibrationUnit, (min, max): (u16, u16)) -> Result<u32> {
+    let value: f32 = cell.parse().context("Not a number")?;
+
+    let pwm = match unit {
+        CalibrationUnit::Pwm => value,
+        CalibrationUnit::Percent => {
+            if !(0.0..=100.0).contains(&value) {
+                return Err(anyhow!("{value} % is outside 0-100 %"));
+            }
+            min as f32 + (max as f32 - min as f32) * value / 100.0
+        }
+    }
+    .round();
+
+    if pwm < min as f32 || pwm > max as f32 {
+        return Err(anyhow!("{pwm} is outside the channel range {min}-{max}"));
+    }
+
+    Ok(pwm as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PV1: &str = "Zoom,Close,Far
+935,957,871
+1100,1032,953
+1250,1141,1036
+1400,,1147
+1600,1479,1332
+1831,2026,1677
+";
+
+    fn import(csv: &str) -> api::CalibrationCsvImport {
+        api::CalibrationCsvImport {
+            csv: csv.to_string(),
+            zoom_column: None,
+            closest_column: None,
+            furthest_column: None,
+            unit: None,
+            target: None,
+        }
+    }
+
+    #[test]
+    fn parses_measurement_file_skipping_empty_cells() {
+        let report = parse(&import(PV1), &ActuatorsParameters::default()).unwrap();
+
+        let closest = report.closest.unwrap().0;
+        let furthest = report.furthest.unwrap().0;
+        assert_eq!(closest.len(), 5);
+        assert_eq!(furthest.len(), 6);
+        assert_eq!(
+            closest[3],
+            api::FocusZoomPoint {
+                zoom: 1600,
+                focus: 1479
+            }
+        );
+        assert_eq!(report.target, api::CalibrationImportTarget::Samples);
+    }
+
+    #[test]
+    fn percent_maps_onto_channel_ranges() {
+        let mut request = import("z,f\n0,0\n100,100\n");
+        request.zoom_column = Some("z".into());
+        request.closest_column = Some("f".into());
+        request.unit = Some(CalibrationUnit::Percent);
+        let parameters = ActuatorsParameters::default();
+
+        let closest = parse(&request, &parameters).unwrap().closest.unwrap().0;
+        assert_eq!(closest[0].zoom, parameters.zoom_channel_min as u32);
+        assert_eq!(closest[1].focus, parameters.script_channel_max as u32);
+    }
+
+    #[test]
+    fn quoted_cells_crlf_and_a_byte_order_mark_are_accepted() {
+        let csv =
+            "\u{feff}\"Zoom\",\"Close, mm\",Far\r\n\"935\", 957 ,871\r\n1100,\"1032\",953\r\n";
+        let mut request = import(csv);
+        request.closest_column = Some("Close, mm".into());
+
+        let report = parse(&request, &ActuatorsParameters::default()).unwrap();
+        assert_eq!(
+            report.closest.unwrap().0[1],
+            api::FocusZoomPoint {
+                zoom: 1100,
+                focus: 1032
+            }
+        );
+        assert_eq!(report.furthest.unwrap().0.len(), 2);
+
+        assert_eq!(
+            split_row(r#""say ""hi""",x"#).unwrap(),
+            vec![r#"say "hi""#.to_string(), "x".to_string()]
+        );
+        assert!(split_row(r#""open,x"#).is_err());
+    }
+
+    #[test]
+    fn shipped_measurements_import_once_the_zoom_range_covers_them() {
+        let pv2 = include_str!("../../../../tools/calibration/data/pv2.csv");
+
+        let error = parse(&import(pv2), &ActuatorsParameters::default())
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("2 rows cannot be imported"), "{error}");
+        assert!(
+            error.contains(r#"Row 2, zoom "917": 917 is outside the channel range 935-1850"#),
+            "{error}"
+        );
+        assert!(error.contains(r#"Row 13, zoom "1948""#), "{error}");
+
+        let parameters = ActuatorsParameters {
+            zoom_channel_min: 870,
+            zoom_channel_max: 1950,
+            ..Default::default()
+        };
+        let report = parse(&import(pv2), &parameters).unwrap();
+        assert_eq!(report.closest.unwrap().0.len(), 6);
+        assert_eq!(report.furthest.unwrap().0.len(), 6);
+
+        let pv1 = include_str!("../../../../tools/calibration/data/pv1.csv");
+        assert!(parse(&import(pv1), &ActuatorsParameters::default()).is_ok());
+    }
+
+    #[test]
+    fn rejects_out_of_range_and_non_monotonic_rows() {
+        let parameters = ActuatorsParameters::default();
+
+        assert!(parse(&import("Zoom,Close\n935,957\n1900,1000\n"), &parameters).is_err());
+        assert!(parse(&import("Zoom,Close\n1100,957\n1000,1000\n"), &parameters).is_err());
+
+        let mut request = import(PV1);
+        request.furthest_column = Some("Missing".into());
+        assert!(parse(&request, &parameters).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! Guided focus calibration: step the zoom through a set of positions, let the user
+//! nudge focus until the image is sharp, and capture one focus/zoom point per step.
+//!
+//! Sessions live in memory only; nothing is persisted until a session is committed.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::{Context, Result, anyhow};
+use once_cell::sync::Lazy;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{
+    api::{self, CalibrationCurve, CalibrationSession},
+    manager::{self, MANAGER},
+};
+
+const DEFAULT_ZOOM_STEPS: &[f32] = &[0.0, 25.0, 50.0, 75.0, 100.0];
+/// The generated Lua script asserts at least two points per curve.
+const MIN_CURVE_POINTS: usize = 2;
+
+static SESSIONS: Lazy<Mutex<HashMap<Uuid, CalibrationSession>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+impl CalibrationSession {
+    fn new(curve: CalibrationCurve, zoom_steps: Option<Vec<f32>>, focus: f32) -> Result<Self> {
+        let mut zoom_steps = zoom_steps.unwrap_or_else(|| DEFAULT_ZOOM_STEPS.to_vec());
+        if let Some(step) = zoom_steps
+            .iter()
+            .find(|step| !(0.0..=100.0).contains(*step))
+        {
+            return Err(anyhow!("Zoom step {step} is outside 0-100 %"));
+        }
+        zoom_steps.sort_by(f32::total_cmp);
+        zoom_steps.dedup();
+        if zoom_steps.len() < MIN_CURVE_POINTS {
+            return Err(anyhow!(
+                "Calibration requires at least {MIN_CURVE_POINTS} distinct zoom steps"
+            ));
+        }
+
+        Ok(Self {
+            curve,
+            captured: vec![None; zoom_steps.len()],
+            zoom_steps,
+            current_step: 0,
+            focus,
+        })
+    }
+
+    /// Captured points as a curve, sorted by zoom.
+    pub fn points(&self) -> Result<api::FocusZoomPoints> {
+        let mut points: Vec<api::FocusZoomPoint> =
+            self.captured.iter().flatten().copied().collect();
+        points.sort();
+
+        if points.len() < MIN_CURVE_POINTS {
+            return Err(anyhow!(
+                "Calibration requires at least {MIN_CURVE_POINTS} captured points, got {}",
+                points.len()
+            ));
+        }
+        if let Some(pair) = points.windows(2).find(|pair| pair[0].zoom == pair[1].zoom) {
+            return Err(anyhow!(
+                "Two captured points share zoom {}; recapture one of them",
+                pair[0].zoom
+            ));
+        }
+
+        Ok(api::FocusZoomPoints(points))
+    }
+}
+
+/// Current session for `camera_uuid`, if any.
+pub fn get(camera_uuid: Uuid) -> Option<CalibrationSession> {
+    SESSIONS.lock().unwrap().get(&camera_uuid).cloned()
+}
+
+/// Drop the session for `camera_uuid`. Returns whether one existed.
+pub fn discard(camera_uuid: Uuid) -> bool {
+    SESSIONS.lock().unwrap().remove(&camera_uuid).is_some()
+}
+
+/// Start (or restart) a session and move the zoom to its first step.
+#[instrument(level = "debug")]
+pub async fn start(
+    camera_uuid: Uuid,
+    start: &api::CalibrationSessionStart,
+) -> Result<CalibrationSession> {
+    let focus = {
+        let manager = MANAGER.get().context("Not available")?.read().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+        actuators.state.focus.unwrap_or(50.0)
+    };
+
+    let session = CalibrationSession::new(start.curve, start.zoom_steps.clone(), focus)?;
+    manager::Manager::apply_state_setpoints(&api::ActuatorsState {
+        zoom: Some(session.zoom_steps[0]),
+        focus: Some(session.focus),
+        tilt: None,
+        ..Default::default()
+    })
+    .await?;
+
+    SESSIONS
+        .lock()
+        .unwrap()
+        .insert(camera_uuid, session.clone());
+    info!(%camera_uuid, curve = ?start.curve, "Started calibration session");
+
+    Ok(session)
+}
+
+/// Move the zoom to `step` of the running session.
+#[instrument(level = "debug")]
+pub async fn go_to_step(camera_uuid: Uuid, step: usize) -> Result<CalibrationSession> {
+    let zoom = {
+        let session = get(camera_uuid).context("No calibration session running")?;
+        *session
+            .zoom_steps
+            .get(step)
+            .with_context(|| format!("Calibration step {step} out of range"))?
+    };
+
+    manager::Manager::apply_state_setpoints(&api::ActuatorsState {
+        zoom: Some(zoom),
+        focus: None,
+        tilt: None,
+        ..Default::default()
+    })
+    .await?;
+
+    update(camera_uuid, |session| session.current_step = step)
+}
+
+/// Shift focus by `delta` percent from the last focus the session commanded.
+#[instrument(level = "debug")]
+pub async fn nudge_focus(camera_uuid: Uuid, delta: f32) -> Result<CalibrationSession> {
+    let focus = {
+        let session = get(camera_uuid).context("No calibration session running")?;
+        (session.focus + delta).clamp(0.0, 100.0)
+    };
+
+    manager::Manager::apply_state_setpoints(&api::ActuatorsState {
+        zoom: None,
+        focus: Some(focus),
+        tilt: None,
+        ..Default::default()
+    })
+    .await?;
+
+    update(camera_uuid, |session| session.focus = focus)
+}
+
+/// Record the current zoom and focus outputs as the point for the current step.
+///
+/// Focus is read from the script channel, which is the output driving the lens.
+#[instrument(level = "debug")]
+pub async fn capture(camera_uuid: Uuid) -> Result<CalibrationSession> {
+    if get(camera_uuid).is_none() {
+        return Err(anyhow!("No calibration session running"));
+    }
+
+    let servo_output_raw = crate::mavlink::component()?
+        .request_servo_output_raw()
+        .await
+        .context("Failed waiting for SERVO_OUTPUT_RAW_DATA message")?;
+
+    let point = {
+        let manager = MANAGER.get().context("Not available")?.read().await;
+        let parameters = &manager
+            .settings
+            .actuators
+            .get(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?
+            .parameters;
+
+        let zoom = manager::get_output_raw_from_channel(&servo_output_raw, parameters.zoom_channel)
+            .context("Zoom channel is not reported by SERVO_OUTPUT_RAW")?;
+        let focus =
+            manager::get_output_raw_from_channel(&servo_output_raw, parameters.script_channel)
+                .context("Script channel is not reported by SERVO_OUTPUT_RAW")?;
+
+        api::FocusZoomPoint {
+            zoom: zoom as u32,
+            focus: focus as u32,
+        }
+    };
+
+    debug!(%camera_uuid, ?point, "Captured calibration point");
+    update(camera_uuid, |session| {
+        let step = session.current_step;
+        session.captured[step] = Some(point);
+    })
+}
+
+/// Persist the captured curve and regenerate the Lua script. Ends the session.
+#[instrument(level = "debug")]
+pub async fn commit(camera_uuid: Uuid) -> Result<()> {
+    let session = get(camera_uuid).context("No calibration session running")?;
+    let points = session.points()?;
+
+    let new_config = match session.curve {
+        CalibrationCurve::Closest => api::ActuatorsConfig {
+            closest_points: Some(points),
+            ..Default::default()
+        },
+        CalibrationCurve::Furthest => api::ActuatorsConfig {
+            furthest_points: Some(points),
+            ..Default::default()
+        },
+    };
+
+    manager::reboot_outside_apply(
+        Box::pin(async { manager::Manager::update_config(&camera_uuid, &new_config, false).await }),
+        Box::pin(async {
+            manager::Manager::finalize_config_after_reboot(&camera_uuid, None).await
+        }),
+    )
+    .await?;
+
+    discard(camera_uuid);
+    info!(%camera_uuid, curve = ?session.curve, "Committed calibration session");
+
+    Ok(())
+}
+
+/// Apply `change` to the running session, failing when it was discarded meanwhile.
+fn update(
+    camera_uuid: Uuid,
+    change: impl FnOnce(&mut CalibrationSession),
+) -> Result<CalibrationSession> {
+    let mut sessions = SESSIONS.lock().unwrap();
+    let session = sessions
+        .get_mut(&camera_uuid)
+        .context("Calibration session was discarded")?;
+    change(session);
+    Ok(session.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(zoom: u32, focus: u32) -> Option<api::FocusZoomPoint> {
+        Some(api::FocusZoomPoint { zoom, focus })
+    }
+
+    #[test]
+    fn zoom_steps_are_sorted_deduplicated_and_bounded() {
+        let session = CalibrationSession::new(
+            CalibrationCurve::Closest,
+            Some(vec![100.0, 0.0, 50.0, 50.0]),
+            50.0,
+        )
+        .unwrap();
+        assert_eq!(session.zoom_steps, vec![0.0, 50.0, 100.0]);
+        assert_eq!(session.captured.len(), 3);
+
+        assert!(
+            CalibrationSession::new(CalibrationCurve::Closest, Some(vec![0.0, 120.0]), 50.0)
+                .is_err()
+        );
+        assert!(
+            CalibrationSession::new(CalibrationCurve::Closest, Some(vec![10.0, 10.0]), 50.0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn points_require_two_distinct_zooms() {
+        let mut session = CalibrationSession::new(CalibrationCurve::Furthest, None, 50.0).unwrap();
+        session.captured[3] = point(1600, 1332);
+        assert!(session.points().is_err());
+
+        session.captured[0] = point(935, 871);
+        assert_eq!(
+            session.points().unwrap(),
+            api::FocusZoomPoints(vec![
+                api::FocusZoomPoint {
+                    zoom: 935,
+                    focus: 871
+                },
+                api::FocusZoomPoint {
+                    zoom: 1600,
+                    focus: 1332
+                },
+            ])
+        );
+
+        session.captured[1] = point(935, 900);
+        assert!(session.points().is_err());
+    }
+}
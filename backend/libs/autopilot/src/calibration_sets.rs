@@ -0,0 +1,383 @@
+//! Named calibration sets per camera, and the optional depth-driven switch between
+//! an "air" and a "water" set (refraction shifts the focus curves underwater).
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result, anyhow};
+use once_cell::sync::Lazy;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{
+    CameraActuators, api,
+    manager::{self, MANAGER},
+    message_loop,
+};
+
+/// Depth band around the threshold where no switch happens, so waves do not flap the set.
+const DEPTH_HYSTERESIS: f32 = 0.2;
+/// Every switch regenerates and reloads the Lua script; do not do that more often.
+const MIN_SWITCH_INTERVAL: Duration = Duration::from_secs(10);
+/// How often the vehicle depth is compared with the thresholds.
+const DEPTH_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+static LAST_AUTO_SWITCH_AT: Lazy<Mutex<HashMap<Uuid, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[instrument(level = "debug")]
+pub async fn get(camera_uuid: Uuid) -> Result<api::CalibrationSets> {
+    let manager = MANAGER.get().context("Not available")?.read().await;
+    let actuators = manager
+        .settings
+        .actuators
+        .get(&camera_uuid)
+        .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+    Ok(actuators.calibration_sets.clone())
+}
+
+/// Create or replace the set `save.name`. Saving the active set applies it right away.
+#[instrument(level = "debug", skip(save))]
+pub async fn save(camera_uuid: Uuid, save: &api::CalibrationSetSave) -> Result<()> {
+    if save.name.trim().is_empty() {
+        return Err(anyhow!("Calibration set name cannot be empty"));
+    }
+
+    let is_active = {
+        let _apply = manager::CONFIG_APPLY.lock().await;
+        let mut manager = MANAGER.get().context("Not available")?.write().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get_mut(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+        let set = api::CalibrationSet {
+            closest_points: save
+                .closest_points
+                .clone()
+                .unwrap_or_else(|| actuators.closest_points.clone()),
+            furthest_points: save
+                .furthest_points
+                .clone()
+                .unwrap_or_else(|| actuators.furthest_points.clone()),
+        };
+        for points in [&set.closest_points, &set.furthest_points] {
+            if points.0.len() < 2 {
+                return Err(anyhow!("A calibration curve requires at least 2 points"));
+            }
+        }
+
+        let is_active = actuators.calibration_sets.active == save.name;
+        if !is_active {
+            actuators
+                .calibration_sets
+                .sets
+                .insert(save.name.clone(), set);
+        }
+        is_active
+    };
+
+    if is_active {
+        // The active set mirrors the live curves; route through the regular apply path.
+        return apply_active(camera_uuid, save.name.clone(), save).await;
+    }
+
+    manager::Manager::save_actuators_settings().await
+}
+
+#[instrument(level = "debug")]
+pub async fn delete(camera_uuid: Uuid, name: &str) -> Result<()> {
+    {
+        let _apply = manager::CONFIG_APPLY.lock().await;
+        let mut manager = MANAGER.get().context("Not available")?.write().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get_mut(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+        let sets = &mut actuators.calibration_sets;
+
+        if sets.active == name {
+            return Err(anyhow!("Cannot delete the active calibration set {name:?}"));
+        }
+        if let Some(auto_switch) = &sets.auto_switch
+            && (auto_switch.air_set == name || auto_switch.water_set == name)
+        {
+            return Err(anyhow!(
+                "Calibration set {name:?} is used by the automatic depth switch"
+            ));
+        }
+        sets.sets
+            .shift_remove(name)
+            .with_context(|| format!("Unknown calibration set {name:?}"))?;
+    }
+
+    manager::Manager::save_actuators_settings().await
+}
+
+/// Make `name` the active set, regenerating and reloading the Lua script.
+#[instrument(level = "debug")]
+pub async fn switch(camera_uuid: Uuid, name: &str) -> Result<()> {
+    let set = {
+        let manager = MANAGER.get().context("Not available")?.read().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+        actuators
+            .calibration_sets
+            .sets
+            .get(name)
+            .cloned()
+            .with_context(|| format!("Unknown calibration set {name:?}"))?
+    };
+
+    let save = api::CalibrationSetSave {
+        name: name.to_string(),
+        closest_points: Some(set.closest_points),
+        furthest_points: Some(set.furthest_points),
+    };
+    apply_active(camera_uuid, name.to_string(), &save).await?;
+    info!(%camera_uuid, name, "Switched calibration set");
+
+    Ok(())
+}
+
+#[instrument(level = "debug")]
+pub async fn set_auto_switch(
+    camera_uuid: Uuid,
+    auto_switch: Option<api::CalibrationAutoSwitch>,
+) -> Result<()> {
+    {
+        let _apply = manager::CONFIG_APPLY.lock().await;
+        let mut manager = MANAGER.get().context("Not available")?.write().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get_mut(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+        let sets = &mut actuators.calibration_sets;
+
+        if let Some(auto_switch) = &auto_switch {
+            for name in [&auto_switch.air_set, &auto_switch.water_set] {
+                if !sets.sets.contains_key(name) {
+                    return Err(anyhow!("Unknown calibration set {name:?}"));
+                }
+            }
+            if !auto_switch.depth_threshold.is_finite() || auto_switch.depth_threshold <= 0.0 {
+                return Err(anyhow!(
+                    "Depth threshold must be a positive number of meters"
+                ));
+            }
+        }
+        sets.auto_switch = auto_switch;
+    }
+
+    manager::Manager::save_actuators_settings().await
+}
+
+/// Mark `name` active and push its curves through [`manager::Manager::update_config`].
+///
+/// The live curves are copied into the active set, so `name` is made active first and the
+/// previous set restored when the curves cannot be applied.
+async fn apply_active(
+    camera_uuid: Uuid,
+    name: String,
+    save: &api::CalibrationSetSave,
+) -> Result<()> {
+    let new_config = api::ActuatorsConfig {
+        closest_points: save.closest_points.clone(),
+        furthest_points: save.furthest_points.clone(),
+        ..Default::default()
+    };
+
+    manager::reboot_outside_apply(
+        Box::pin(async {
+            let previous = set_active(&camera_uuid, name).await?;
+            let needs_reboot =
+                match manager::Manager::update_config(&camera_uuid, &new_config, false).await {
+                    Ok(needs_reboot) => needs_reboot,
+                    Err(error) => {
+                        set_active(&camera_uuid, previous).await?;
+                        return Err(error);
+                    }
+                };
+            if !needs_reboot {
+                manager::Manager::save_actuators_settings().await?;
+            }
+
+            Ok(needs_reboot)
+        }),
+        Box::pin(async {
+            manager::Manager::finalize_config_after_reboot(&camera_uuid, None).await
+        }),
+    )
+    .await
+}
+
+/// Make `name` the active set of `camera_uuid`, returning the previous one.
+async fn set_active(camera_uuid: &Uuid, name: String) -> Result<String> {
+    let mut manager = MANAGER.get().context("Not available")?.write().await;
+    let actuators = manager
+        .settings
+        .actuators
+        .get_mut(camera_uuid)
+        .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+    Ok(activate(actuators, name))
+}
+
+fn activate(actuators: &mut CameraActuators, name: String) -> String {
+    std::mem::replace(&mut actuators.calibration_sets.active, name)
+}
+
+/// The set `auto_switch` asks for at `depth`, or `None` inside the hysteresis band.
+fn desired_set(auto_switch: &api::CalibrationAutoSwitch, depth: f32) -> Option<&str> {
+    if depth > auto_switch.depth_threshold + DEPTH_HYSTERESIS {
+        Some(&auto_switch.water_set)
+    } else if depth < auto_switch.depth_threshold - DEPTH_HYSTERESIS {
+        Some(&auto_switch.air_set)
+    } else {
+        None
+    }
+}
+
+/// Spawn the depth watcher once. Cheap when no camera has an automatic switch configured.
+pub fn start() {
+    message_loop::spawn_once("depth watcher", depth_watcher());
+}
+
+#[instrument(level = "debug", skip_all)]
+async fn depth_watcher() {
+    let mut interval = tokio::time::interval(DEPTH_POLL_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        let Some(depth) = crate::telemetry::depth() else {
+            continue;
+        };
+
+        for (camera_uuid, name) in pending_switches(depth).await {
+            // Spawned so a reload or reboot does not stall depth sampling.
+            tokio::spawn(
+                async move {
+                    if let Err(error) = switch(camera_uuid, &name).await {
+                        warn!(%camera_uuid, name, "Automatic calibration switch failed: {error:?}");
+                    }
+                }
+                .instrument(Span::current()),
+            );
+        }
+    }
+}
+
+/// Cameras whose automatic switch asks for another set at `depth`, rate limited per camera.
+async fn pending_switches(depth: f32) -> Vec<(Uuid, String)> {
+    let Some(manager) = MANAGER.get() else {
+        return Vec::new();
+    };
+
+    let candidates: Vec<(Uuid, String)> = {
+        let manager = manager.read().await;
+        manager
+            .settings
+            .actuators
+            .iter()
+            .filter_map(|(camera_uuid, actuators)| {
+                let sets = &actuators.calibration_sets;
+                let desired = desired_set(sets.auto_switch.as_ref()?, depth)?;
+                (desired != sets.active).then(|| (camera_uuid, desired.to_string()))
+            })
+            .collect()
+    };
+
+    let mut last_switch_at = LAST_AUTO_SWITCH_AT.lock().unwrap();
+    candidates
+        .into_iter()
+        .filter(|(camera_uuid, _)| {
+            let due = last_switch_at
+                .get(camera_uuid)
+                .is_none_or(|at| at.elapsed() >= MIN_SWITCH_INTERVAL);
+            if due {
+                info!(%camera_uuid, depth, "Depth crossed the calibration switch threshold");
+                last_switch_at.insert(*camera_uuid, Instant::now());
+            }
+            due
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::*;
+
+    #[test]
+    fn desired_set_holds_inside_hysteresis_band() {
+        let auto_switch = api::CalibrationAutoSwitch {
+            air_set: "air".into(),
+            water_set: "water".into(),
+            depth_threshold: 0.5,
+        };
+
+        assert_eq!(desired_set(&auto_switch, 0.0), Some("air"));
+        assert_eq!(desired_set(&auto_switch, 0.5), None);
+        assert_eq!(desired_set(&auto_switch, 1.0), Some("water"));
+    }
+
+    fn curve(focus: u32) -> api::FocusZoomPoints {
+        api::FocusZoomPoints(vec![
+            api::FocusZoomPoint { zoom: 0, focus },
+            api::FocusZoomPoint {
+                zoom: 100,
+                focus: focus + 100,
+            },
+        ])
+    }
+
+    #[test]
+    fn switching_back_restores_the_original_curves() {
+        let air = api::CalibrationSet {
+            closest_points: curve(1000),
+            furthest_points: curve(1500),
+        };
+        let water = api::CalibrationSet {
+            closest_points: curve(1100),
+            furthest_points: curve(1600),
+        };
+
+        let mut actuators = CameraActuators {
+            closest_points: air.closest_points.clone(),
+            furthest_points: air.furthest_points.clone(),
+            calibration_sets: api::CalibrationSets {
+                active: "air".into(),
+                sets: IndexMap::from([
+                    ("air".into(), air.clone()),
+                    ("water".into(), water.clone()),
+                ]),
+                auto_switch: None,
+            },
+            ..Default::default()
+        };
+
+        // What `apply_active` does for each switch: mark the set active, then load its curves.
+        for name in ["water", "air"] {
+            let set = actuators.calibration_sets.sets[name].clone();
+            activate(&mut actuators, name.into());
+            actuators.set_closest_points(&set.closest_points, false);
+            actuators.set_furthest_points(&set.furthest_points, false);
+        }
+
+        assert_eq!(actuators.calibration_sets.sets["air"], air);
+        assert_eq!(actuators.calibration_sets.sets["water"], water);
+        assert_eq!(actuators.closest_points, air.closest_points);
+        assert_eq!(actuators.furthest_points, air.furthest_points);
+    }
+}
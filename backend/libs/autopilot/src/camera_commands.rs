@@ -0,0 +1,327 @@
+//! `MAV_CMD_SET_CAMERA_ZOOM` and `MAV_CMD_SET_CAMERA_FOCUS` sent by ground stations and
+//! joysticks, routed to the addressed cameras' actuators through their autopilot camera
+//! instance.
+//!
+//! Commands may target a camera component announced by [`crate::camera_protocol`], or the
+//! manager's own component with the camera picked by a camera id parameter (0 for all
+//! cameras, 1-6 for the autopilot camera instance, or a camera component id).
+
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use mavlink::{
+    MavHeader,
+    ardupilotmega::{COMMAND_LONG_DATA, CameraZoomType, MavCmd, MavResult, SetFocusType},
+};
+use once_cell::sync::Lazy;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{
+    api, camera_protocol,
+    manager::{self, MANAGER},
+};
+
+/// Percent moved by a single `*_TYPE_STEP` command.
+const STEP_PERCENT: f32 = 5.0;
+/// Percent per second moved while a `*_TYPE_CONTINUOUS` command is active.
+const CONTINUOUS_RATE: f32 = 20.0;
+const CONTINUOUS_TICK: Duration = Duration::from_millis(100);
+
+/// The continuous motion of each camera axis.
+static CONTINUOUS: Lazy<Mutex<HashMap<CameraAxis, tokio::task::JoinHandle<()>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+type CameraAxis = (Uuid, Axis);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Axis {
+    Zoom,
+    Focus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Motion {
+    Range(f32),
+    Step(f32),
+    Continuous(f32),
+}
+
+impl Axis {
+    fn get(self, state: &api::ActuatorsState) -> Option<f32> {
+        match self {
+            Axis::Zoom => state.zoom,
+            Axis::Focus => state.focus,
+        }
+    }
+
+    fn setpoint(self, value: f32) -> api::ActuatorsState {
+        match self {
+            Axis::Zoom => api::ActuatorsState {
+                zoom: Some(value),
+                ..Default::default()
+            },
+            Axis::Focus => api::ActuatorsState {
+                focus: Some(value),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// Decode the axis and motion of a zoom or focus command; `None` for unsupported modes.
+fn decode(command: &COMMAND_LONG_DATA) -> Option<(Axis, Motion)> {
+    let mode = command.param1 as u8;
+    let value = command.param2;
+
+    match command.command {
+        MavCmd::MAV_CMD_SET_CAMERA_ZOOM => {
+            let motion = match mode {
+                mode if mode == CameraZoomType::ZOOM_TYPE_RANGE as u8 => Motion::Range(value),
+                mode if mode == CameraZoomType::ZOOM_TYPE_STEP as u8 => Motion::Step(value),
+                mode if mode == CameraZoomType::ZOOM_TYPE_CONTINUOUS as u8 => {
+                    Motion::Continuous(value)
+                }
+                _ => return None,
+            };
+            Some((Axis::Zoom, motion))
+        }
+        MavCmd::MAV_CMD_SET_CAMERA_FOCUS => {
+            let motion = match mode {
+                mode if mode == SetFocusType::FOCUS_TYPE_RANGE as u8 => Motion::Range(value),
+                mode if mode == SetFocusType::FOCUS_TYPE_STEP as u8 => Motion::Step(value),
+                mode if mode == SetFocusType::FOCUS_TYPE_CONTINUOUS as u8 => {
+                    Motion::Continuous(value)
+                }
+                _ => return None,
+            };
+            Some((Axis::Focus, motion))
+        }
+        _ => None,
+    }
+}
+
+/// Cameras addressed by `command` and the component id answering for them, or `None`
+/// when the command is not meant for us. `camera_id` is the command's camera id parameter,
+/// only used when the command targets the manager's own component.
+pub(crate) async fn addressed(
+    command: &COMMAND_LONG_DATA,
+    manager_component: u8,
+    camera_id: f32,
+) -> Option<(u8, Vec<Uuid>)> {
+    let target_component = command.target_component;
+    if target_component != 0 && target_component != manager_component {
+        let cameras = camera_protocol::targets(target_component);
+        if cameras.is_empty() {
+            return None;
+        }
+        return Some((
+            target_component,
+            cameras.into_iter().map(|(uuid, _)| uuid).collect(),
+        ));
+    }
+
+    let camera_id = camera_id as u8;
+    let cameras = match camera_id {
+        0 => crate::configured_cameras().await,
+        1..=6 => {
+            let manager = MANAGER.get()?.read().await;
+            manager
+                .settings
+                .actuators
+                .iter()
+                .filter(|(_, actuators)| actuators.parameters.camera_id as u8 == camera_id)
+                .map(|(uuid, _)| uuid)
+                .collect()
+        }
+        component_id => camera_protocol::targets(component_id)
+            .into_iter()
+            .map(|(uuid, _)| uuid)
+            .collect(),
+    };
+
+    Some((manager_component, cameras))
+}
+
+/// Execute a zoom or focus command and answer it with a `COMMAND_ACK`.
+#[instrument(level = "debug", skip(requester, command), fields(command = ?command.command))]
+pub(crate) async fn handle(
+    requester: MavHeader,
+    command: COMMAND_LONG_DATA,
+    component_id: u8,
+    cameras: Vec<Uuid>,
+) {
+    let result = execute(&command, &cameras).await;
+    camera_protocol::reply(component_id, &requester, &command, result, Vec::new()).await;
+}
+
+async fn execute(command: &COMMAND_LONG_DATA, cameras: &[Uuid]) -> MavResult {
+    let Some((axis, motion)) = decode(command) else {
+        return MavResult::MAV_RESULT_UNSUPPORTED;
+    };
+
+    let configured = crate::configured_cameras().await;
+    if cameras.is_empty() || cameras.iter().any(|camera| !configured.contains(camera)) {
+        debug!(
+            ?cameras,
+            "Refusing {axis:?} command: actuators not configured"
+        );
+        return MavResult::MAV_RESULT_DENIED;
+    }
+
+    let mut positions = Vec::with_capacity(cameras.len());
+    for &camera_uuid in cameras {
+        let current = crate::cached_actuators_state(camera_uuid)
+            .await
+            .and_then(|state| axis.get(&state));
+        positions.push((camera_uuid, current));
+    }
+
+    if let Motion::Continuous(direction) = motion {
+        for (camera_uuid, current) in positions {
+            stop_continuous(camera_uuid, axis);
+            if direction != 0.0 {
+                start_continuous(
+                    camera_uuid,
+                    axis,
+                    direction.signum(),
+                    current.unwrap_or(50.0),
+                );
+            }
+        }
+        return MavResult::MAV_RESULT_ACCEPTED;
+    }
+
+    // Every camera must be able to move before any of them does.
+    let Some(targets) = positions
+        .into_iter()
+        .map(|(camera_uuid, current)| Some((camera_uuid, setpoint_for(motion, current)?)))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return MavResult::MAV_RESULT_DENIED;
+    };
+
+    let mut result = MavResult::MAV_RESULT_ACCEPTED;
+    for (camera_uuid, target) in targets {
+        stop_continuous(camera_uuid, axis);
+        if let Err(error) =
+            manager::Manager::apply_camera_state_setpoints(&camera_uuid, &axis.setpoint(target))
+                .await
+        {
+            warn!(%camera_uuid, "Failed applying {axis:?} command: {error:?}");
+            result = MavResult::MAV_RESULT_FAILED;
+        }
+    }
+
+    result
+}
+
+/// Setpoint for a range or step motion; a step needs the current position.
+fn setpoint_for(motion: Motion, current: Option<f32>) -> Option<f32> {
+    let target = match motion {
+        Motion::Range(value) => value,
+        Motion::Step(direction) => current? + direction.signum() * STEP_PERCENT,
+        Motion::Continuous(_) => return None,
+    };
+
+    Some(target.clamp(0.0, 100.0))
+}
+
+fn stop_continuous(camera_uuid: Uuid, axis: Axis) {
+    if let Some(task) = CONTINUOUS.lock().unwrap().remove(&(camera_uuid, axis)) {
+        task.abort();
+    }
+}
+
+fn start_continuous(camera_uuid: Uuid, axis: Axis, direction: f32, from: f32) {
+    let task = tokio::spawn(
+        async move {
+            let step = direction * CONTINUOUS_RATE * CONTINUOUS_TICK.as_secs_f32();
+            let mut position = from;
+            let mut interval = tokio::time::interval(CONTINUOUS_TICK);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+            loop {
+                interval.tick().await;
+                let next = (position + step).clamp(0.0, 100.0);
+                if next == position {
+                    break;
+                }
+                position = next;
+
+                if let Err(error) = manager::Manager::apply_camera_state_setpoints(
+                    &camera_uuid,
+                    &axis.setpoint(position),
+                )
+                .await
+                {
+                    warn!("Stopping continuous {axis:?}: {error:?}");
+                    break;
+                }
+            }
+        }
+        .instrument(Span::current()),
+    );
+
+    CONTINUOUS.lock().unwrap().insert((camera_uuid, axis), task);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(command: MavCmd, mode: f32, value: f32) -> COMMAND_LONG_DATA {
+        COMMAND_LONG_DATA {
+            command,
+            param1: mode,
+            param2: value,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn decodes_supported_modes_only() {
+        let zoom = MavCmd::MAV_CMD_SET_CAMERA_ZOOM;
+        let focus = MavCmd::MAV_CMD_SET_CAMERA_FOCUS;
+
+        assert_eq!(
+            decode(&command(
+                zoom,
+                CameraZoomType::ZOOM_TYPE_RANGE as u8 as f32,
+                40.0
+            )),
+            Some((Axis::Zoom, Motion::Range(40.0)))
+        );
+        assert_eq!(
+            decode(&command(
+                focus,
+                SetFocusType::FOCUS_TYPE_CONTINUOUS as u8 as f32,
+                -1.0
+            )),
+            Some((Axis::Focus, Motion::Continuous(-1.0)))
+        );
+        assert_eq!(
+            decode(&command(
+                zoom,
+                CameraZoomType::ZOOM_TYPE_FOCAL_LENGTH as u8 as f32,
+                24.0
+            )),
+            None
+        );
+        assert_eq!(
+            decode(&command(
+                focus,
+                SetFocusType::FOCUS_TYPE_AUTO as u8 as f32,
+                0.0
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn targets_are_clamped_and_steps_need_a_position() {
+        assert_eq!(setpoint_for(Motion::Range(120.0), None), Some(100.0));
+        assert_eq!(setpoint_for(Motion::Step(1.0), Some(98.0)), Some(100.0));
+        assert_eq!(setpoint_for(Motion::Step(-3.0), Some(50.0)), Some(45.0));
+        assert_eq!(setpoint_for(Motion::Step(1.0), None), None);
+    }
+}
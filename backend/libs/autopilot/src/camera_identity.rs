@@ -0,0 +1,192 @@
+//! Actuators settings filed under the physical camera rather than the `Uuid` MCM assigns it.
+//!
+//! [`ActuatorsByCamera`] is keyed by [`settings::CameraKey`], the hardware serial of each
+//! unit, but is looked up by `Uuid` like the rest of the manager. Once a camera reports its
+//! serial, the `Uuid` it shows up under reads the lens curves of that unit.
+
+use anyhow::{Context, Result};
+use indexmap::{IndexMap, map::Entry};
+use settings::CameraKey;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{
+    CameraActuators,
+    manager::{self, MANAGER},
+};
+
+/// The actuators of every camera, by the unit they belong to.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct ActuatorsByCamera(IndexMap<CameraKey, CameraActuators>);
+
+impl ActuatorsByCamera {
+    pub fn get(&self, camera_uuid: &Uuid) -> Option<&CameraActuators> {
+        self.0.get(&settings::camera_key(camera_uuid))
+    }
+
+    pub fn get_mut(&mut self, camera_uuid: &Uuid) -> Option<&mut CameraActuators> {
+        self.0.get_mut(&settings::camera_key(camera_uuid))
+    }
+
+    pub fn entry(&mut self, camera_uuid: Uuid) -> Entry<'_, CameraKey, CameraActuators> {
+        self.0.entry(settings::camera_key(&camera_uuid))
+    }
+
+    pub fn insert(&mut self, camera_uuid: Uuid, actuators: CameraActuators) {
+        self.0.insert(settings::camera_key(&camera_uuid), actuators);
+    }
+
+    pub fn shift_remove(&mut self, camera_uuid: &Uuid) -> Option<CameraActuators> {
+        self.0.shift_remove(&settings::camera_key(camera_uuid))
+    }
+
+    pub fn contains_key(&self, camera_uuid: &Uuid) -> bool {
+        self.0.contains_key(&settings::camera_key(camera_uuid))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = Uuid> + '_ {
+        self.0.keys().map(settings::camera_uuid)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &CameraActuators> {
+        self.0.values()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Uuid, &CameraActuators)> {
+        self.0
+            .iter()
+            .map(|(key, actuators)| (settings::camera_uuid(key), actuators))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Uuid, &mut CameraActuators)> {
+        self.0
+            .iter_mut()
+            .map(|(key, actuators)| (settings::camera_uuid(key), actuators))
+    }
+
+    /// The actuators as filed in the settings document.
+    pub fn by_key(&self) -> &IndexMap<CameraKey, CameraActuators> {
+        &self.0
+    }
+}
+
+impl FromIterator<(CameraKey, CameraActuators)> for ActuatorsByCamera {
+    fn from_iter<I: IntoIterator<Item = (CameraKey, CameraActuators)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+/// Bind `serial` to `camera_uuid`, filing the actuators and stream preferences recorded
+/// under `camera_uuid` before its serial was known under the serial. Returns whether the
+/// actuators changed.
+#[instrument(level = "debug")]
+pub async fn adopt_camera_serial(camera_uuid: Uuid, serial: &str) -> Result<bool> {
+    let rebound = settings::adopt_serial(camera_uuid, serial).await?;
+
+    let needs_update = {
+        let manager = MANAGER.get().context("Not available")?.read().await;
+        manager
+            .settings
+            .actuators
+            .by_key()
+            .contains_key(&CameraKey::Uuid(camera_uuid))
+    };
+    if !needs_update {
+        if rebound {
+            debug!(%camera_uuid, serial, "Bound camera serial");
+            // Expectations are tracked by `Uuid`, which now reads another entry.
+            manager::owned_parameters::rebuild().await;
+            manager::owned_parameters::reevaluate_after_apply().await;
+        }
+        return Ok(false);
+    }
+
+    let _apply = manager::CONFIG_APPLY.lock().await;
+    let superseded = {
+        let mut manager = MANAGER.get().context("Not available")?.write().await;
+        match file_under_serial(&mut manager.settings.actuators.0, camera_uuid, serial) {
+            Some(superseded) => superseded,
+            None => return Ok(false),
+        }
+    };
+
+    manager::Manager::save_actuators_settings().await?;
+    if superseded {
+        info!(%camera_uuid, serial, "Dropped the settings recorded before the camera was identified");
+    } else {
+        info!(%camera_uuid, serial, "Filed camera settings under its serial");
+    }
+
+    drop(_apply);
+    manager::owned_parameters::rebuild().await;
+    manager::owned_parameters::reevaluate_after_apply().await;
+
+    Ok(true)
+}
+
+/// Move the entry recorded under `camera_uuid` to `serial`. An entry the unit already has
+/// under its serial wins over it. Returns whether it was superseded, or `None` if there
+/// was no such entry.
+fn file_under_serial(
+    actuators: &mut IndexMap<CameraKey, CameraActuators>,
+    camera_uuid: Uuid,
+    serial: &str,
+) -> Option<bool> {
+    let (index, _, entry) = actuators.shift_remove_full(&CameraKey::Uuid(camera_uuid))?;
+
+    let key = CameraKey::Serial(serial.to_string());
+    if actuators.contains_key(&key) {
+        return Some(true);
+    }
+    actuators.shift_insert(index, key, entry);
+
+    Some(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(zoom: f32) -> CameraActuators {
+        let mut actuators = CameraActuators::default();
+        actuators.state.zoom = Some(zoom);
+        actuators
+    }
+
+    #[test]
+    fn unidentified_entry_is_filed_under_its_serial_in_place() {
+        let (first, second) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let mut actuators = IndexMap::from([
+            (CameraKey::Uuid(first), entry(1.0)),
+            (CameraKey::Uuid(second), entry(2.0)),
+        ]);
+
+        assert_eq!(file_under_serial(&mut actuators, first, "SN1"), Some(false));
+        assert_eq!(file_under_serial(&mut actuators, first, "SN1"), None);
+
+        assert_eq!(
+            actuators.keys().cloned().collect::<Vec<_>>(),
+            vec![
+                CameraKey::Serial("SN1".to_string()),
+                CameraKey::Uuid(second)
+            ]
+        );
+    }
+
+    #[test]
+    fn settings_already_filed_for_the_unit_win() {
+        let camera_uuid = Uuid::from_u128(1);
+        let serial = CameraKey::Serial("SN1".to_string());
+        let mut actuators = IndexMap::from([
+            (serial.clone(), entry(1.0)),
+            (CameraKey::Uuid(camera_uuid), entry(2.0)),
+        ]);
+
+        assert_eq!(
+            file_under_serial(&mut actuators, camera_uuid, "SN1"),
+            Some(true)
+        );
+        assert_eq!(actuators.len(), 1);
+        assert_eq!(actuators[&serial], entry(1.0));
+    }
+}
@@ -0,0 +1,486 @@
+//! MAVLink Camera Protocol v2: every registered br4kcam is announced as its own camera
+//! component (`MAV_COMP_ID_CAMERA`..`MAV_COMP_ID_CAMERA6`) and answers
+//! `MAV_CMD_REQUEST_MESSAGE` so ground stations can discover it and its video streams.
+//! Zoom and focus commands are handed to [`crate::camera_commands`], image capture commands
+//! to [`crate::image_capture`].
+//!
+//! The description of each camera comes from outside this crate (MCM streams and the
+//! camera's own video settings), see [`set_mavlink_camera`].
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Result, anyhow};
+use indexmap::IndexMap;
+use mavlink::{
+    MavHeader, MessageData,
+    ardupilotmega::{
+        CAMERA_CAPTURE_STATUS_DATA, CAMERA_INFORMATION_DATA, CAMERA_SETTINGS_DATA,
+        COMMAND_ACK_DATA, COMMAND_LONG_DATA, CameraCapFlags, CameraMode, HEARTBEAT_DATA,
+        MavAutopilot, MavCmd, MavComponent, MavMessage, MavModeFlag, MavResult, MavState, MavType,
+        VIDEO_STREAM_INFORMATION_DATA, VIDEO_STREAM_STATUS_DATA, VideoStreamStatusFlags,
+        VideoStreamType,
+    },
+};
+use once_cell::sync::Lazy;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{camera_commands, image_capture, mavlink::MavlinkComponent, message_loop};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_CAMERAS: u8 = 6;
+
+static BOOT: Lazy<Instant> = Lazy::new(Instant::now);
+static CAMERAS: Lazy<Mutex<IndexMap<Uuid, RegisteredCamera>>> =
+    Lazy::new(|| Mutex::new(IndexMap::new()));
+
+/// What a br4kcam reports about itself over MAVLink.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MavlinkCamera {
+    pub vendor_name: String,
+    pub model_name: String,
+    /// Firmware version as reported by the camera, e.g. `"V13.1.2"`.
+    pub firmware_version: Option<String>,
+    pub streams: Vec<MavlinkVideoStream>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MavlinkVideoStream {
+    pub name: String,
+    pub uri: String,
+    pub running: bool,
+    /// Width and height in pixels.
+    pub resolution: Option<(u16, u16)>,
+    pub framerate: Option<f32>,
+    /// Bitrate in kbit/s.
+    pub bitrate: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct RegisteredCamera {
+    component_id: u8,
+    description: MavlinkCamera,
+}
+
+/// Announce `camera_uuid` as a MAVLink camera, or update its description.
+///
+/// Fails when every camera component id is already taken.
+#[instrument(level = "debug", skip(description))]
+pub fn set_mavlink_camera(camera_uuid: Uuid, description: MavlinkCamera) -> Result<u8> {
+    let mut cameras = CAMERAS.lock().unwrap();
+    if let Some(camera) = cameras.get_mut(&camera_uuid) {
+        camera.description = description;
+        return Ok(camera.component_id);
+    }
+
+    let component_id = free_component_id(&cameras)
+        .ok_or_else(|| anyhow!("All {MAX_CAMERAS} MAVLink camera components are in use"))?;
+    cameras.insert(
+        camera_uuid,
+        RegisteredCamera {
+            component_id,
+            description,
+        },
+    );
+    info!(%camera_uuid, component_id, "Announcing camera over MAVLink");
+
+    Ok(component_id)
+}
+
+/// Stop announcing `camera_uuid` over MAVLink.
+#[instrument(level = "debug")]
+pub fn remove_mavlink_camera(camera_uuid: Uuid) {
+    if CAMERAS.lock().unwrap().shift_remove(&camera_uuid).is_some() {
+        info!(%camera_uuid, "Stopped announcing camera over MAVLink");
+    }
+}
+
+/// Cameras currently announced over MAVLink.
+pub fn mavlink_cameras() -> Vec<Uuid> {
+    CAMERAS.lock().unwrap().keys().copied().collect()
+}
+
+fn free_component_id(cameras: &IndexMap<Uuid, RegisteredCamera>) -> Option<u8> {
+    let first = MavComponent::MAV_COMP_ID_CAMERA as u8;
+    (first..first + MAX_CAMERAS)
+        .find(|id| cameras.values().all(|camera| camera.component_id != *id))
+}
+
+/// Start the camera heartbeats and command handling once.
+pub fn start() {
+    Lazy::force(&BOOT);
+    message_loop::spawn_once("camera heartbeats", heartbeat_task());
+    message_loop::add_message_handler("camera protocol", handle_message);
+}
+
+#[instrument(level = "debug", skip_all)]
+async fn heartbeat_task() {
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+    heartbeat.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        heartbeat.tick().await;
+        send_heartbeats().await;
+    }
+}
+
+/// Answer the camera commands sent to this system.
+async fn handle_message(
+    mavlink: &'static MavlinkComponent,
+    header: MavHeader,
+    message: MavMessage,
+) {
+    let MavMessage::COMMAND_LONG(command) = message else {
+        return;
+    };
+    if command.target_system != mavlink.system_id() && command.target_system != 0 {
+        return;
+    }
+
+    match command.command {
+        MavCmd::MAV_CMD_REQUEST_MESSAGE => {
+            // A broadcast would be acknowledged once per camera: only an addressed camera
+            // acknowledges, while every camera still sends what was requested.
+            let broadcast = command.target_component == 0;
+            for (camera_uuid, component_id) in targets(command.target_component) {
+                let (result, messages) = request_message(camera_uuid, &command).await;
+                if broadcast {
+                    send_answers(component_id, &command, messages).await;
+                } else {
+                    reply(component_id, &header, &command, result, messages).await;
+                }
+            }
+        }
+        MavCmd::MAV_CMD_SET_CAMERA_ZOOM | MavCmd::MAV_CMD_SET_CAMERA_FOCUS => {
+            // The camera id is in param3, after the zoom or focus mode and value.
+            let Some((component_id, cameras)) =
+                camera_commands::addressed(&command, mavlink.component_id(), command.param3).await
+            else {
+                return;
+            };
+            // Spawned: setpoints wait for the autopilot's own ACKs.
+            tokio::spawn(
+                camera_commands::handle(header, command, component_id, cameras)
+                    .instrument(Span::current()),
+            );
+        }
+        MavCmd::MAV_CMD_IMAGE_START_CAPTURE | MavCmd::MAV_CMD_IMAGE_STOP_CAPTURE => {
+            let Some((component_id, cameras)) =
+                camera_commands::addressed(&command, mavlink.component_id(), command.param1).await
+            else {
+                return;
+            };
+            tokio::spawn(
+                image_capture::handle(header, command, component_id, cameras)
+                    .instrument(Span::current()),
+            );
+        }
+        _ => (),
+    }
+}
+
+/// Registered cameras addressed by `target_component` (0 addresses all of them).
+pub(crate) fn targets(target_component: u8) -> Vec<(Uuid, u8)> {
+    CAMERAS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(_, camera)| target_component == 0 || camera.component_id == target_component)
+        .map(|(camera_uuid, camera)| (*camera_uuid, camera.component_id))
+        .collect()
+}
+
+async fn send_heartbeats() {
+    let Ok(mavlink) = crate::mavlink::component() else {
+        return;
+    };
+
+    for (_, component_id) in targets(0) {
+        let heartbeat = MavMessage::HEARTBEAT(HEARTBEAT_DATA {
+            custom_mode: 0,
+            mavtype: MavType::MAV_TYPE_CAMERA,
+            autopilot: MavAutopilot::MAV_AUTOPILOT_INVALID,
+            base_mode: MavModeFlag::empty(),
+            system_status: MavState::MAV_STATE_ACTIVE,
+            mavlink_version: 0x3,
+        });
+        if let Err(error) = mavlink.send_as(component_id, heartbeat).await {
+            warn!("Failed sending camera heartbeat: {error:?}");
+        }
+    }
+}
+
+/// Send the `COMMAND_ACK` for `command`, then `messages` when it was accepted.
+pub(crate) async fn reply(
+    component_id: u8,
+    requester: &MavHeader,
+    command: &COMMAND_LONG_DATA,
+    result: MavResult,
+    messages: Vec<MavMessage>,
+) {
+    let Ok(mavlink) = crate::mavlink::component() else {
+        return;
+    };
+
+    let ack = MavMessage::COMMAND_ACK(COMMAND_ACK_DATA {
+        command: command.command,
+        result,
+        target_system: requester.system_id,
+        target_component: requester.component_id,
+        ..Default::default()
+    });
+    if let Err(error) = mavlink.send_as(component_id, ack).await {
+        warn!("Failed acknowledging {:?}: {error:?}", command.command);
+        return;
+    }
+
+    send_answers(component_id, command, messages).await;
+}
+
+/// Send `messages` answering `command`, without acknowledging it.
+async fn send_answers(component_id: u8, command: &COMMAND_LONG_DATA, messages: Vec<MavMessage>) {
+    let Ok(mavlink) = crate::mavlink::component() else {
+        return;
+    };
+
+    for message in messages {
+        if let Err(error) = mavlink.send_as(component_id, message).await {
+            warn!("Failed answering {:?}: {error:?}", command.command);
+        }
+    }
+}
+
+async fn request_message(
+    camera_uuid: Uuid,
+    command: &COMMAND_LONG_DATA,
+) -> (MavResult, Vec<MavMessage>) {
+    let Some(description) = CAMERAS
+        .lock()
+        .unwrap()
+        .get(&camera_uuid)
+        .map(|camera| camera.description.clone())
+    else {
+        return (MavResult::MAV_RESULT_DENIED, Vec::new());
+    };
+
+    let message_id = command.param1 as u32;
+    match message_id {
+        CAMERA_INFORMATION_DATA::ID => (
+            MavResult::MAV_RESULT_ACCEPTED,
+            vec![MavMessage::CAMERA_INFORMATION(camera_information(
+                &description,
+                crate::configured_cameras().await.contains(&camera_uuid),
+                image_capture::can_capture(),
+            ))],
+        ),
+        CAMERA_SETTINGS_DATA::ID => {
+            let state = crate::cached_actuators_state(camera_uuid).await;
+            (
+                MavResult::MAV_RESULT_ACCEPTED,
+                vec![MavMessage::CAMERA_SETTINGS(camera_settings(state))],
+            )
+        }
+        CAMERA_CAPTURE_STATUS_DATA::ID => (
+            MavResult::MAV_RESULT_ACCEPTED,
+            vec![MavMessage::CAMERA_CAPTURE_STATUS(
+                image_capture::capture_status(camera_uuid),
+            )],
+        ),
+        VIDEO_STREAM_INFORMATION_DATA::ID | VIDEO_STREAM_STATUS_DATA::ID => {
+            let Some(streams) = requested_streams(&description, command.param2) else {
+                return (MavResult::MAV_RESULT_DENIED, Vec::new());
+            };
+            let messages = streams
+                .map(|(stream_id, stream)| {
+                    if message_id == VIDEO_STREAM_INFORMATION_DATA::ID {
+                        MavMessage::VIDEO_STREAM_INFORMATION(video_stream_information(
+                            stream_id,
+                            description.streams.len(),
+                            stream,
+                        ))
+                    } else {
+                        MavMessage::VIDEO_STREAM_STATUS(video_stream_status(stream_id, stream))
+                    }
+                })
+                .collect();
+            (MavResult::MAV_RESULT_ACCEPTED, messages)
+        }
+        _ => (MavResult::MAV_RESULT_UNSUPPORTED, Vec::new()),
+    }
+}
+
+/// Streams selected by a 1-based `stream_id` parameter, where 0 selects all of them.
+fn requested_streams(
+    description: &MavlinkCamera,
+    stream_id: f32,
+) -> Option<impl Iterator<Item = (u8, &MavlinkVideoStream)>> {
+    let stream_id = stream_id as usize;
+    if stream_id > description.streams.len() || description.streams.is_empty() {
+        return None;
+    }
+
+    Some(
+        description
+            .streams
+            .iter()
+            .enumerate()
+            .map(|(index, stream)| ((index + 1) as u8, stream))
+            .filter(move |(id, _)| stream_id == 0 || *id as usize == stream_id),
+    )
+}
+
+pub(crate) fn time_boot_ms() -> u32 {
+    BOOT.elapsed().as_millis() as u32
+}
+
+fn camera_information(
+    description: &MavlinkCamera,
+    has_actuators: bool,
+    can_capture: bool,
+) -> CAMERA_INFORMATION_DATA {
+    let mut flags = CameraCapFlags::empty();
+    if can_capture {
+        flags |= CameraCapFlags::CAMERA_CAP_FLAGS_CAPTURE_IMAGE;
+    }
+    if has_actuators {
+        flags |= CameraCapFlags::CAMERA_CAP_FLAGS_HAS_BASIC_ZOOM
+            | CameraCapFlags::CAMERA_CAP_FLAGS_HAS_BASIC_FOCUS;
+    }
+    if !description.streams.is_empty() {
+        flags |= CameraCapFlags::CAMERA_CAP_FLAGS_HAS_VIDEO_STREAM;
+    }
+    let (resolution_h, resolution_v) = description
+        .streams
+        .first()
+        .and_then(|stream| stream.resolution)
+        .unwrap_or_default();
+
+    CAMERA_INFORMATION_DATA {
+        time_boot_ms: time_boot_ms(),
+        vendor_name: fixed_bytes(&description.vendor_name),
+        model_name: fixed_bytes(&description.model_name),
+        firmware_version: description
+            .firmware_version
+            .as_deref()
+            .map(encode_firmware_version)
+            .unwrap_or_default(),
+        flags,
+        resolution_h,
+        resolution_v,
+        ..Default::default()
+    }
+}
+
+pub(crate) fn camera_settings(state: Option<crate::api::ActuatorsState>) -> CAMERA_SETTINGS_DATA {
+    let state = state.unwrap_or_default();
+
+    CAMERA_SETTINGS_DATA {
+        time_boot_ms: time_boot_ms(),
+        mode_id: CameraMode::CAMERA_MODE_VIDEO,
+        // NaN tells the GCS the level is unknown.
+        zoomLevel: state.zoom.unwrap_or(f32::NAN),
+        focusLevel: state.focus.unwrap_or(f32::NAN),
+        ..Default::default()
+    }
+}
+
+fn video_stream_information(
+    stream_id: u8,
+    count: usize,
+    stream: &MavlinkVideoStream,
+) -> VIDEO_STREAM_INFORMATION_DATA {
+    let (resolution_h, resolution_v) = stream.resolution.unwrap_or_default();
+
+    VIDEO_STREAM_INFORMATION_DATA {
+        stream_id,
+        count: count as u8,
+        mavtype: stream_type(&stream.uri),
+        flags: stream_flags(stream),
+        framerate: stream.framerate.unwrap_or_default(),
+        resolution_h,
+        resolution_v,
+        bitrate: stream.bitrate.unwrap_or_default(),
+        name: stream.name.as_str().into(),
+        uri: stream.uri.as_str().into(),
+        ..Default::default()
+    }
+}
+
+fn video_stream_status(stream_id: u8, stream: &MavlinkVideoStream) -> VIDEO_STREAM_STATUS_DATA {
+    let (resolution_h, resolution_v) = stream.resolution.unwrap_or_default();
+
+    VIDEO_STREAM_STATUS_DATA {
+        stream_id,
+        flags: stream_flags(stream),
+        framerate: stream.framerate.unwrap_or_default(),
+        resolution_h,
+        resolution_v,
+        bitrate: stream.bitrate.unwrap_or_default(),
+        ..Default::default()
+    }
+}
+
+fn stream_flags(stream: &MavlinkVideoStream) -> VideoStreamStatusFlags {
+    if stream.running {
+        VideoStreamStatusFlags::VIDEO_STREAM_STATUS_FLAGS_RUNNING
+    } else {
+        VideoStreamStatusFlags::empty()
+    }
+}
+
+fn stream_type(uri: &str) -> VideoStreamType {
+    match uri.split_once("://").map(|(scheme, _)| scheme) {
+        Some("udp" | "udp265") => VideoStreamType::VIDEO_STREAM_TYPE_RTPUDP,
+        Some("tcp") => VideoStreamType::VIDEO_STREAM_TYPE_TCP_MPEG,
+        _ => VideoStreamType::VIDEO_STREAM_TYPE_RTSP,
+    }
+}
+
+fn fixed_bytes<const N: usize>(text: &str) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    let len = text.len().min(N);
+    bytes[..len].copy_from_slice(&text.as_bytes()[..len]);
+    bytes
+}
+
+/// Pack `"V13.1.2"`-like versions as MAVLink does: major in the lowest byte, then minor,
+/// patch and dev.
+fn encode_firmware_version(version: &str) -> u32 {
+    version
+        .split(|character: char| !character.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .take(4)
+        .enumerate()
+        .map(|(index, part)| (part.parse::<u32>().unwrap_or_default() & 0xff) << (8 * index))
+        .fold(0, |version, part| version | part)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn firmware_version_packs_major_in_lowest_byte() {
+        assert_eq!(encode_firmware_version("V13.1.2"), 13 | 1 << 8 | 2 << 16);
+        assert_eq!(encode_firmware_version("unknown"), 0);
+    }
+
+    #[test]
+    fn stream_selection_is_one_based() {
+        let description = MavlinkCamera {
+            streams: vec![MavlinkVideoStream::default(), MavlinkVideoStream::default()],
+            ..Default::default()
+        };
+
+        let ids = |stream_id| {
+            requested_streams(&description, stream_id)
+                .map(|streams| streams.map(|(id, _)| id).collect::<Vec<_>>())
+        };
+        assert_eq!(ids(0.0), Some(vec![1, 2]));
+        assert_eq!(ids(2.0), Some(vec![2]));
+        assert_eq!(ids(3.0), None);
+    }
+}
@@ -0,0 +1,129 @@
+//! Tilt through the mount with the Gimbal Manager v2 `MAV_CMD_DO_GIMBAL_MANAGER_PITCHYAW`,
+//! whatever drives it. With the gimbal manager backend the mount attitude also comes back
+//! as `GIMBAL_DEVICE_ATTITUDE_STATUS`.
+//!
+//! ArduPilot is the gimbal manager for its mounts: `MNT1` is gimbal device 1 and `MNT2` is
+//! gimbal device 2, which is how each camera finds its mount.
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use mavlink::{
+    MavHeader,
+    ardupilotmega::{COMMAND_LONG_DATA, GimbalManagerFlags, MavCmd, MavComponent, MavMessage},
+};
+use once_cell::sync::Lazy;
+use tracing::*;
+
+use crate::{api, mavlink::MavlinkComponent, message_loop};
+
+/// Attitude reports older than this are not used as tilt feedback.
+const ATTITUDE_STALE_AFTER: Duration = Duration::from_secs(3);
+
+/// Latest pitch in degrees reported for each gimbal device id.
+static PITCHES: Lazy<Mutex<HashMap<u8, (Instant, f32)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Gimbal device id of the mount driving `camera_id`'s tilt.
+pub(crate) fn gimbal_device_id(camera_id: api::CameraID) -> u8 {
+    camera_id as u8
+}
+
+/// Latest pitch in degrees reported by the autopilot for `device_id`, if recent.
+pub(crate) fn pitch(device_id: u8) -> Option<f32> {
+    PITCHES
+        .lock()
+        .unwrap()
+        .get(&device_id)
+        .filter(|(at, _)| at.elapsed() < ATTITUDE_STALE_AFTER)
+        .map(|(_, pitch)| *pitch)
+}
+
+/// Point the mount of gimbal device `device_id` to `pitch` degrees.
+#[instrument(level = "debug")]
+pub(crate) async fn set_pitch(device_id: u8, pitch: f32) -> Result<()> {
+    let mavlink = crate::mavlink::component()?;
+
+    mavlink
+        .send_command(pitch_command(mavlink.system_id(), device_id, pitch))
+        .await
+        .context("Failed sending MAV_CMD_DO_GIMBAL_MANAGER_PITCHYAW command")
+}
+
+/// `MAV_CMD_DO_GIMBAL_MANAGER_PITCHYAW` for the mount of gimbal device `device_id`.
+fn pitch_command(target_system: u8, device_id: u8, pitch: f32) -> COMMAND_LONG_DATA {
+    COMMAND_LONG_DATA {
+        target_system,
+        target_component: MavComponent::MAV_COMP_ID_AUTOPILOT1 as u8,
+        command: MavCmd::MAV_CMD_DO_GIMBAL_MANAGER_PITCHYAW,
+        param1: pitch,
+        // Yaw stays straight ahead in the vehicle frame; rates are unused.
+        param2: 0.0,
+        param3: f32::NAN,
+        param4: f32::NAN,
+        param5: GimbalManagerFlags::empty().bits() as f32,
+        param7: device_id as f32,
+        ..Default::default()
+    }
+}
+
+/// Start following the attitude of the mounts.
+pub fn start() {
+    message_loop::add_message_handler("gimbal attitude watcher", record_attitude);
+}
+
+/// Keep the pitch of every mount reported by the autopilot.
+async fn record_attitude(
+    mavlink: &'static MavlinkComponent,
+    header: MavHeader,
+    message: MavMessage,
+) {
+    let MavMessage::GIMBAL_DEVICE_ATTITUDE_STATUS(data) = message else {
+        return;
+    };
+    if header.system_id != mavlink.system_id()
+        || header.component_id != MavComponent::MAV_COMP_ID_AUTOPILOT1 as u8
+    {
+        return;
+    }
+
+    // 0 means the sender is the gimbal device itself, i.e. the first mount.
+    let device_id = data.gimbal_device_id.max(1);
+    PITCHES
+        .lock()
+        .unwrap()
+        .insert(device_id, (Instant::now(), pitch_from_quaternion(data.q)));
+}
+
+/// Pitch in degrees of a `[w, x, y, z]` attitude quaternion.
+fn pitch_from_quaternion([w, x, y, z]: [f32; 4]) -> f32 {
+    (2.0 * (w * y - z * x)).clamp(-1.0, 1.0).asin().to_degrees()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pitch_is_read_back_from_quaternion() {
+        for pitch in [-70.0f32, -15.5, 0.0, 45.0] {
+            let (sin, cos) = (pitch.to_radians() / 2.0).sin_cos();
+            let read = pitch_from_quaternion([cos, 0.0, sin, 0.0]);
+            assert!((read - pitch).abs() < 1e-3, "{read} != {pitch}");
+        }
+    }
+
+    #[test]
+    fn each_camera_commands_its_own_mount() {
+        for (camera_id, instance) in [(api::CameraID::CAM1, 1.0), (api::CameraID::CAM2, 2.0)] {
+            let command = pitch_command(1, gimbal_device_id(camera_id), -30.0);
+
+            assert_eq!(command.command, MavCmd::MAV_CMD_DO_GIMBAL_MANAGER_PITCHYAW);
+            assert_eq!(command.param1, -30.0);
+            assert_eq!(command.param7, instance);
+        }
+    }
+}
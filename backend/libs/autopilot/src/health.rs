@@ -22,7 +22,7 @@ use crate::{
 };
 
 const FRAME_SILENCE: Duration = Duration::from_secs(5);
-const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
+pub(crate) const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(5);
 const SERVO_SILENCE: Duration = Duration::from_secs(10);
 /// One exhausted command RPC (already multi-second) is enough evidence.
 const RPC_FAILURES_TO_UNRESPONSIVE: u8 = 1;
@@ -122,6 +122,10 @@ pub fn health() -> (AutopilotHealth, Option<String>) {
 
 /// True when BlueOS may have lost our MAVLink endpoint and we should re-create it.
 pub fn needs_mavlink_endpoint_ensure() -> bool {
+    if !crate::platform::platform().manages_mavlink_endpoint() {
+        return false;
+    }
+
     ensure_started();
     let guard = health_state().lock().expect("health lock");
     match guard.state {
@@ -287,6 +291,7 @@ pub fn diagnostics() -> Diagnostics {
         script_reloads: guard.script_reloads,
         backend_version: backend_version_string(),
         settings_error: settings::last_save_error(),
+        settings_recovered_from: settings::recovered_from(),
         ..Default::default()
     };
 
@@ -391,7 +396,7 @@ fn health_sender() -> &'static broadcast::Sender<()> {
     })
 }
 
-fn notify_health() {
+pub(crate) fn notify_health() {
     if let Err(error) = health_sender().send(()) {
         debug!("No autopilot health subscribers: {error}");
     }
@@ -0,0 +1,301 @@
+//! `MAV_CMD_IMAGE_START_CAPTURE` and `MAV_CMD_IMAGE_STOP_CAPTURE` for the cameras announced
+//! by [`crate::camera_protocol`].
+//!
+//! Grabbing and storing the image is done outside this crate: each capture is published
+//! with the vehicle telemetry and actuators state of the moment, see
+//! [`subscribe_image_capture_requests`], and the result is reported back to the GCS with
+//! [`report_image_captured`].
+
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use mavlink::{
+    MavHeader,
+    ardupilotmega::{
+        CAMERA_CAPTURE_STATUS_DATA, CAMERA_IMAGE_CAPTURED_DATA, COMMAND_LONG_DATA, MavCmd,
+        MavMessage, MavResult,
+    },
+};
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{api, camera_protocol, telemetry};
+
+static REQUESTS: Lazy<broadcast::Sender<api::ImageCapture>> =
+    Lazy::new(|| broadcast::channel(16).0);
+static CAPTURES: Lazy<Mutex<HashMap<Uuid, CameraCaptures>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+#[derive(Debug, Default)]
+struct CameraCaptures {
+    image_count: i32,
+    /// Interval in seconds and the task taking the images.
+    sequence: Option<(f32, tokio::task::JoinHandle<()>)>,
+}
+
+/// Subscribe to the captures requested over MAVLink. Captures are refused while nobody
+/// is subscribed.
+pub fn subscribe_image_capture_requests() -> broadcast::Receiver<api::ImageCapture> {
+    REQUESTS.subscribe()
+}
+
+/// Tell the GCS how `capture` went. `file_url` is where the image can be downloaded from.
+#[instrument(level = "debug", skip(capture), fields(camera_uuid = %capture.camera_uuid))]
+pub async fn report_image_captured(
+    capture: &api::ImageCapture,
+    file_url: Option<&str>,
+    success: bool,
+) -> Result<()> {
+    let component_id = camera_protocol::targets(0)
+        .into_iter()
+        .find(|(camera_uuid, _)| *camera_uuid == capture.camera_uuid)
+        .map(|(_, component_id)| component_id)
+        .context("Camera is not announced over MAVLink")?;
+
+    let message = MavMessage::CAMERA_IMAGE_CAPTURED(image_captured(capture, file_url, success));
+    crate::mavlink::component()?
+        .send_as(component_id, message)
+        .await
+}
+
+pub(crate) fn can_capture() -> bool {
+    REQUESTS.receiver_count() > 0
+}
+
+/// Start or stop capturing on `cameras` and answer the command with a `COMMAND_ACK`.
+#[instrument(level = "debug", skip(requester, command), fields(command = ?command.command))]
+pub(crate) async fn handle(
+    requester: MavHeader,
+    command: COMMAND_LONG_DATA,
+    component_id: u8,
+    cameras: Vec<Uuid>,
+) {
+    let result = match command.command {
+        MavCmd::MAV_CMD_IMAGE_START_CAPTURE => start_capture(&command, &cameras).await,
+        MavCmd::MAV_CMD_IMAGE_STOP_CAPTURE => {
+            cameras
+                .iter()
+                .for_each(|camera_uuid| stop_sequence(*camera_uuid));
+            MavResult::MAV_RESULT_ACCEPTED
+        }
+        _ => MavResult::MAV_RESULT_UNSUPPORTED,
+    };
+
+    camera_protocol::reply(component_id, &requester, &command, result, Vec::new()).await;
+}
+
+async fn start_capture(command: &COMMAND_LONG_DATA, cameras: &[Uuid]) -> MavResult {
+    if cameras.is_empty() || !can_capture() {
+        return MavResult::MAV_RESULT_DENIED;
+    }
+
+    let interval = command.param2;
+    let total = command.param3 as u32;
+    for camera_uuid in cameras.iter().copied() {
+        stop_sequence(camera_uuid);
+
+        if total == 1 || !interval.is_finite() || interval <= 0.0 {
+            request_capture(camera_uuid).await;
+            continue;
+        }
+
+        let task = tokio::spawn(
+            async move {
+                let mut ticker = tokio::time::interval(Duration::from_secs_f32(interval));
+                ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                // A total of 0 captures until stopped.
+                let mut taken = 0;
+                while total == 0 || taken < total {
+                    ticker.tick().await;
+                    request_capture(camera_uuid).await;
+                    taken += 1;
+                }
+
+                if let Some(captures) = CAPTURES.lock().unwrap().get_mut(&camera_uuid) {
+                    captures.sequence = None;
+                }
+            }
+            .instrument(Span::current()),
+        );
+        CAPTURES
+            .lock()
+            .unwrap()
+            .entry(camera_uuid)
+            .or_default()
+            .sequence = Some((interval, task));
+    }
+
+    MavResult::MAV_RESULT_ACCEPTED
+}
+
+fn stop_sequence(camera_uuid: Uuid) {
+    let sequence = CAPTURES
+        .lock()
+        .unwrap()
+        .get_mut(&camera_uuid)
+        .and_then(|captures| captures.sequence.take());
+    if let Some((_, task)) = sequence {
+        task.abort();
+    }
+}
+
+async fn request_capture(camera_uuid: Uuid) {
+    let image_index = {
+        let mut captures = CAPTURES.lock().unwrap();
+        let captures = captures.entry(camera_uuid).or_default();
+        let image_index = captures.image_count;
+        captures.image_count += 1;
+        image_index
+    };
+
+    let capture = api::ImageCapture {
+        camera_uuid,
+        image_index,
+        time_utc_us: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64,
+        telemetry: telemetry::snapshot(),
+        actuators_state: crate::cached_actuators_state(camera_uuid).await,
+    };
+
+    if REQUESTS.send(capture).is_err() {
+        warn!(%camera_uuid, image_index, "Nobody is taking image captures");
+    }
+}
+
+/// `CAMERA_CAPTURE_STATUS` for `camera_uuid`.
+pub(crate) fn capture_status(camera_uuid: Uuid) -> CAMERA_CAPTURE_STATUS_DATA {
+    let captures = CAPTURES.lock().unwrap();
+    let captures = captures.get(&camera_uuid);
+    let interval = captures
+        .and_then(|captures| captures.sequence.as_ref())
+        .map(|(interval, _)| *interval);
+
+    CAMERA_CAPTURE_STATUS_DATA {
+        time_boot_ms: camera_protocol::time_boot_ms(),
+        // 0: idle, 3: interval capture in progress.
+        image_status: if interval.is_some() { 3 } else { 0 },
+        image_interval: interval.unwrap_or_default(),
+        image_count: captures
+            .map(|captures| captures.image_count)
+            .unwrap_or_default(),
+        // NaN tells the GCS the free storage is unknown.
+        available_capacity: f32::NAN,
+        ..Default::default()
+    }
+}
+
+fn image_captured(
+    capture: &api::ImageCapture,
+    file_url: Option<&str>,
+    success: bool,
+) -> CAMERA_IMAGE_CAPTURED_DATA {
+    let telemetry = &capture.telemetry;
+    let (lat, lon) = telemetry
+        .latitude
+        .zip(telemetry.longitude)
+        .map(|(lat, lon)| ((lat * 1e7) as i32, (lon * 1e7) as i32))
+        .unwrap_or_default();
+
+    CAMERA_IMAGE_CAPTURED_DATA {
+        time_utc: capture.time_utc_us,
+        time_boot_ms: camera_protocol::time_boot_ms(),
+        lat,
+        lon,
+        alt: telemetry
+            .altitude
+            .map(|altitude| (altitude * 1000.0) as i32)
+            .unwrap_or_default(),
+        relative_alt: telemetry
+            .relative_altitude
+            .map(|altitude| (altitude * 1000.0) as i32)
+            .unwrap_or_default(),
+        q: attitude_quaternion(telemetry),
+        image_index: capture.image_index,
+        capture_result: success as i8,
+        file_url: file_url.unwrap_or_default().into(),
+        ..Default::default()
+    }
+}
+
+/// Camera attitude as a `[w, x, y, z]` quaternion from the vehicle's roll, pitch and yaw.
+/// All zeros when the attitude is unknown, as MAVLink asks.
+fn attitude_quaternion(telemetry: &api::VehicleTelemetry) -> [f32; 4] {
+    let (Some(roll), Some(pitch), Some(yaw)) = (telemetry.roll, telemetry.pitch, telemetry.yaw)
+    else {
+        return [0.0; 4];
+    };
+
+    let (sr, cr) = (roll.to_radians() / 2.0).sin_cos();
+    let (sp, cp) = (pitch.to_radians() / 2.0).sin_cos();
+    let (sy, cy) = (yaw.to_radians() / 2.0).sin_cos();
+
+    [
+        cr * cp * cy + sr * sp * sy,
+        sr * cp * cy - cr * sp * sy,
+        cr * sp * cy + sr * cp * sy,
+        cr * cp * sy - sr * sp * cy,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f32; 4], expected: [f32; 4]) {
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+        }
+    }
+
+    #[test]
+    fn quaternion_follows_yaw_and_is_zero_when_unknown() {
+        let level = api::VehicleTelemetry {
+            roll: Some(0.0),
+            pitch: Some(0.0),
+            yaw: Some(0.0),
+            ..Default::default()
+        };
+        assert_close(attitude_quaternion(&level), [1.0, 0.0, 0.0, 0.0]);
+
+        let east = api::VehicleTelemetry {
+            yaw: Some(90.0),
+            ..level
+        };
+        let half = std::f32::consts::FRAC_1_SQRT_2;
+        assert_close(attitude_quaternion(&east), [half, 0.0, 0.0, half]);
+
+        let unknown = api::VehicleTelemetry { yaw: None, ..level };
+        assert_eq!(attitude_quaternion(&unknown), [0.0; 4]);
+    }
+
+    #[test]
+    fn position_is_reported_in_mavlink_units() {
+        let capture = api::ImageCapture {
+            camera_uuid: Uuid::nil(),
+            image_index: 3,
+            time_utc_us: 1_700_000_000_000_000,
+            telemetry: api::VehicleTelemetry {
+                latitude: Some(-27.5),
+                longitude: Some(-48.25),
+                relative_altitude: Some(-12.5),
+                ..Default::default()
+            },
+            actuators_state: None,
+        };
+
+        let captured = image_captured(&capture, Some("http://host/captures/3"), true);
+        assert_eq!((captured.lat, captured.lon), (-275_000_000, -482_500_000));
+        assert_eq!(captured.relative_alt, -12_500);
+        assert_eq!(captured.alt, 0);
+        assert_eq!(captured.capture_result, 1);
+        assert_eq!(captured.image_index, 3);
+    }
+}
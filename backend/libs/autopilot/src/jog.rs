@@ -0,0 +1,345 @@
+//! Velocity control of focus, zoom and tilt, for joystick-like inputs.
+//!
+//! Clients send a velocity per axis and keep refreshing it; the manager integrates it into
+//! setpoints at a fixed rate. A jog stops by itself once no refresh arrived within
+//! [`DEADMAN_TIMEOUT`], so a dropped connection can never leave an axis moving.
+//!
+//! Single button presses use step jogs instead: a fixed move from the current position.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use once_cell::sync::Lazy;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{
+    api,
+    manager::{self, MANAGER},
+};
+
+/// Percent per second moved at full velocity.
+const FULL_VELOCITY_RATE: f32 = 25.0;
+const TICK: Duration = Duration::from_millis(100);
+/// A jog stops when its velocity was not refreshed for this long.
+const DEADMAN_TIMEOUT: Duration = Duration::from_millis(500);
+
+static JOGS: Lazy<Mutex<HashMap<Uuid, Jog>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_RUN: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug)]
+struct Jog {
+    velocity: api::JogVelocity,
+    refreshed_at: Instant,
+    /// Task integrating this jog, so a stopped one never picks up a later jog.
+    run: u64,
+}
+
+/// Start or refresh the jog of `camera_uuid`. Axes left out do not move.
+#[instrument(level = "debug")]
+pub async fn jog(camera_uuid: Uuid, velocity: &api::JogVelocity) -> Result<api::JogVelocity> {
+    let start_from = {
+        let manager = MANAGER.get().context("Not available")?.read().await;
+        manager
+            .settings
+            .actuators
+            .get(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?
+            .state
+    };
+
+    let velocity = clamped(velocity);
+    let new_run = {
+        let mut jogs = JOGS.lock().unwrap();
+        match jogs.get_mut(&camera_uuid) {
+            Some(jog) => {
+                jog.velocity = velocity;
+                jog.refreshed_at = Instant::now();
+                None
+            }
+            None if is_moving(&velocity) => {
+                let run = NEXT_RUN.fetch_add(1, Ordering::Relaxed);
+                jogs.insert(
+                    camera_uuid,
+                    Jog {
+                        velocity,
+                        refreshed_at: Instant::now(),
+                        run,
+                    },
+                );
+                Some(run)
+            }
+            None => None,
+        }
+    };
+
+    if let Some(run_id) = new_run {
+        // Jogging takes over from any motion towards a setpoint.
+        crate::motion::stop(camera_uuid);
+        tokio::spawn(run(camera_uuid, run_id, start_from).instrument(Span::current()));
+    }
+
+    Ok(velocity)
+}
+
+/// Stop the jog of `camera_uuid` right away, leaving the axes where they are.
+#[instrument(level = "debug")]
+pub(crate) fn stop(camera_uuid: Uuid) {
+    JOGS.lock().unwrap().remove(&camera_uuid);
+}
+
+/// Setpoints moving `state` by `step`. Fails when a stepped axis has no known position.
+pub(crate) fn step_setpoints(
+    state: &api::ActuatorsState,
+    step: &api::ActuatorsStep,
+) -> Result<api::ActuatorsState> {
+    let stepped = |position: Option<f32>, delta: Option<f32>, axis: &str| -> Result<Option<f32>> {
+        let Some(delta) = delta.filter(|delta| delta.is_finite()) else {
+            return Ok(None);
+        };
+        let position = position.with_context(|| format!("The {axis} position is not known yet"))?;
+        Ok(Some(position + delta))
+    };
+
+    let focus = stepped(state.focus, step.focus, "focus")?;
+    let zoom = stepped(state.zoom, step.zoom, "zoom")?;
+    let tilt_degrees = stepped(state.tilt_degrees, step.tilt_degrees, "tilt")?;
+
+    Ok(api::ActuatorsState {
+        focus: focus.map(|focus| focus.clamp(0.0, 100.0)),
+        zoom: zoom.map(|zoom| zoom.clamp(0.0, 100.0)),
+        tilt_degrees,
+        ..Default::default()
+    })
+}
+
+fn clamped(velocity: &api::JogVelocity) -> api::JogVelocity {
+    let clamp = |value: Option<f32>| {
+        value
+            .filter(|value| value.is_finite())
+            .map(|value| value.clamp(-1.0, 1.0))
+    };
+
+    api::JogVelocity {
+        focus: clamp(velocity.focus),
+        zoom: clamp(velocity.zoom),
+        tilt: clamp(velocity.tilt),
+    }
+}
+
+fn is_moving(velocity: &api::JogVelocity) -> bool {
+    [velocity.focus, velocity.zoom, velocity.tilt]
+        .into_iter()
+        .any(|value| value.is_some_and(|value| value != 0.0))
+}
+
+/// The current velocity, or `None` once the jog was stopped or its deadman expired.
+fn current_velocity(camera_uuid: Uuid, run: u64) -> Option<api::JogVelocity> {
+    let mut jogs = JOGS.lock().unwrap();
+    let jog = jogs.get(&camera_uuid).filter(|jog| jog.run == run)?;
+
+    if jog.refreshed_at.elapsed() > DEADMAN_TIMEOUT {
+        debug!(%camera_uuid, "Jog deadman expired");
+        jogs.remove(&camera_uuid);
+        return None;
+    }
+    if !is_moving(&jog.velocity) {
+        jogs.remove(&camera_uuid);
+        return None;
+    }
+
+    Some(jog.velocity)
+}
+
+/// Integrate `velocity` over one tick, keeping positions within the channel ranges.
+fn integrate(position: Option<f32>, velocity: Option<f32>) -> Option<f32> {
+    let step = velocity? * FULL_VELOCITY_RATE * TICK.as_secs_f32();
+    if step == 0.0 {
+        return None;
+    }
+    let position = position.unwrap_or(50.0);
+    let next = (position + step).clamp(0.0, 100.0);
+
+    (next != position).then_some(next)
+}
+
+#[instrument(level = "debug", skip(start_from))]
+async fn run(camera_uuid: Uuid, run_id: u64, start_from: api::ActuatorsState) {
+    // Keep the SERVO stream alive so clients see the axes move.
+    crate::add_actuators_state_interest();
+
+    let mut position = start_from;
+    let mut interval = tokio::time::interval(TICK);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        let Some(velocity) = current_velocity(camera_uuid, run_id) else {
+            break;
+        };
+
+        if let Err(error) = step(camera_uuid, &mut position, &velocity).await {
+            warn!("Stopping jog: {error:?}");
+            let mut jogs = JOGS.lock().unwrap();
+            if jogs.get(&camera_uuid).is_some_and(|jog| jog.run == run_id) {
+                jogs.remove(&camera_uuid);
+            }
+            break;
+        }
+    }
+
+    crate::remove_actuators_state_interest();
+}
+
+async fn step(
+    camera_uuid: Uuid,
+    position: &mut api::ActuatorsState,
+    velocity: &api::JogVelocity,
+) -> Result<()> {
+    let focus = integrate(position.focus, velocity.focus);
+    let zoom = integrate(position.zoom, velocity.zoom);
+    let tilt = integrate(position.tilt, velocity.tilt);
+
+    if focus.is_some() || zoom.is_some() {
+        manager::Manager::apply_state_setpoints(&api::ActuatorsState {
+            focus,
+            zoom,
+            ..Default::default()
+        })
+        .await?;
+        position.focus = focus.or(position.focus);
+        position.zoom = zoom.or(position.zoom);
+    }
+
+    if let Some(tilt) = tilt {
+        let degrees = {
+            let manager = MANAGER.get().context("Not available")?.read().await;
+            let actuators = manager
+                .settings
+                .actuators
+                .get(&camera_uuid)
+                .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+            manager::output_pitch(
+                &actuators.parameters,
+                tilt,
+                crate::telemetry::snapshot().pitch,
+            )
+        };
+        manager::Manager::apply_tilt_degrees(&camera_uuid, degrees).await?;
+        position.tilt = Some(tilt);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integration_stays_within_the_channel_range() {
+        let step = FULL_VELOCITY_RATE * TICK.as_secs_f32();
+
+        assert_eq!(integrate(Some(50.0), Some(1.0)), Some(50.0 + step));
+        assert_eq!(integrate(Some(50.0), Some(-0.5)), Some(50.0 - step / 2.0));
+        assert_eq!(integrate(Some(99.9), Some(1.0)), Some(100.0));
+        assert_eq!(integrate(Some(100.0), Some(1.0)), None);
+        assert_eq!(integrate(Some(0.0), Some(-1.0)), None);
+        assert_eq!(integrate(Some(50.0), None), None);
+        assert_eq!(integrate(Some(50.0), Some(0.0)), None);
+    }
+
+    #[test]
+    fn velocities_are_clamped_and_nan_is_dropped() {
+        let velocity = clamped(&api::JogVelocity {
+            focus: Some(3.0),
+            zoom: Some(f32::NAN),
+            tilt: Some(-0.25),
+        });
+
+        assert_eq!(velocity.focus, Some(1.0));
+        assert_eq!(velocity.zoom, None);
+        assert_eq!(velocity.tilt, Some(-0.25));
+        assert!(is_moving(&velocity));
+        assert!(!is_moving(&api::JogVelocity::default()));
+    }
+
+    #[test]
+    fn steps_move_from_the_current_position() {
+        let state = api::ActuatorsState {
+            focus: Some(97.0),
+            zoom: Some(40.0),
+            tilt_degrees: Some(-10.0),
+            ..Default::default()
+        };
+
+        let setpoints = step_setpoints(
+            &state,
+            &api::ActuatorsStep {
+                focus: Some(5.0),
+                tilt_degrees: Some(-15.0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(setpoints.focus, Some(100.0));
+        assert_eq!(setpoints.zoom, None);
+        assert_eq!(setpoints.tilt_degrees, Some(-25.0));
+        assert!(
+            step_setpoints(
+                &api::ActuatorsState::default(),
+                &api::ActuatorsStep {
+                    zoom: Some(10.0),
+                    ..Default::default()
+                },
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn jogs_stop_once_the_deadman_expires() {
+        let camera_uuid = Uuid::from_u128(0x10c);
+        JOGS.lock().unwrap().insert(
+            camera_uuid,
+            Jog {
+                velocity: api::JogVelocity {
+                    zoom: Some(1.0),
+                    ..Default::default()
+                },
+                refreshed_at: Instant::now() - DEADMAN_TIMEOUT - Duration::from_millis(1),
+                run: 7,
+            },
+        );
+
+        assert!(current_velocity(camera_uuid, 7).is_none());
+        assert!(!JOGS.lock().unwrap().contains_key(&camera_uuid));
+    }
+
+    #[test]
+    fn stopped_runs_do_not_pick_up_a_later_jog() {
+        let camera_uuid = Uuid::from_u128(0x10d);
+        JOGS.lock().unwrap().insert(
+            camera_uuid,
+            Jog {
+                velocity: api::JogVelocity {
+                    focus: Some(-1.0),
+                    ..Default::default()
+                },
+                refreshed_at: Instant::now(),
+                run: 9,
+            },
+        );
+
+        assert!(current_velocity(camera_uuid, 8).is_none());
+        assert_eq!(current_velocity(camera_uuid, 9).unwrap().focus, Some(-1.0));
+    }
+}
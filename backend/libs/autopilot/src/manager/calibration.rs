@@ -1,27 +1,87 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use tracing::*;
 use uuid::Uuid;
 
-use crate::{api, manager::Manager};
+use crate::{CameraActuators, api, manager::Manager};
 
 impl Manager {
-    #[instrument(level = "debug", skip(_points))]
+    /// Store `points` as the closest-focus curve. Returns `true` when the script must be regenerated.
+    #[instrument(level = "debug", skip(points))]
     pub async fn update_closest_points(
-        _camera_uuid: &Uuid,
-        _points: &api::FocusZoomPoints,
-        _overwrite: bool,
+        camera_uuid: &Uuid,
+        points: &api::FocusZoomPoints,
+        overwrite: bool,
     ) -> Result<bool> {
-        warn!("Skipping: unimplemented");
-        Ok(false)
+        let mut manager = crate::manager::MANAGER
+            .get()
+            .context("Not available")?
+            .write()
+            .await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get_mut(camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+        Ok(actuators.set_closest_points(points, overwrite))
     }
 
-    #[instrument(level = "debug", skip(_points))]
+    /// Store `points` as the furthest-focus curve. Returns `true` when the script must be regenerated.
+    #[instrument(level = "debug", skip(points))]
     pub async fn update_furthest_points(
-        _camera_uuid: &Uuid,
-        _points: &api::FocusZoomPoints,
-        _overwrite: bool,
+        camera_uuid: &Uuid,
+        points: &api::FocusZoomPoints,
+        overwrite: bool,
     ) -> Result<bool> {
-        warn!("Skipping: unimplemented");
-        Ok(false)
+        let mut manager = crate::manager::MANAGER
+            .get()
+            .context("Not available")?
+            .write()
+            .await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get_mut(camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+        Ok(actuators.set_furthest_points(points, overwrite))
+    }
+}
+
+impl CameraActuators {
+    /// Store `points` as the closest-focus curve and in the active set, which mirrors it.
+    /// Returns `false` when nothing changed.
+    pub(crate) fn set_closest_points(
+        &mut self,
+        points: &api::FocusZoomPoints,
+        overwrite: bool,
+    ) -> bool {
+        if !overwrite && &self.closest_points == points {
+            return false;
+        }
+        self.closest_points = points.clone();
+        let active = &self.calibration_sets.active;
+        if let Some(set) = self.calibration_sets.sets.get_mut(active) {
+            set.closest_points = points.clone();
+        }
+        true
+    }
+
+    /// Store `points` as the furthest-focus curve and in the active set, which mirrors it.
+    /// Returns `false` when nothing changed.
+    pub(crate) fn set_furthest_points(
+        &mut self,
+        points: &api::FocusZoomPoints,
+        overwrite: bool,
+    ) -> bool {
+        if !overwrite && &self.furthest_points == points {
+            return false;
+        }
+        self.furthest_points = points.clone();
+        let active = &self.calibration_sets.active;
+        if let Some(set) = self.calibration_sets.sets.get_mut(active) {
+            set.furthest_points = points.clone();
+        }
+        true
     }
 }
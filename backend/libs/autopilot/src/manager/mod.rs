@@ -10,18 +10,20 @@ mod zoom;
 use ::mavlink::ardupilotmega::SERVO_OUTPUT_RAW_DATA;
 use anyhow::{Context, Result};
 use futures::future::BoxFuture;
-use indexmap::IndexMap;
 use once_cell::sync::OnceCell;
 use tokio::sync::RwLock;
 use tracing::*;
 use uuid::Uuid;
 
 use script::ScriptHealthTracker;
+pub(crate) use script::check_script;
 use settings::MANAGER as SETTINGS_MANAGER;
+pub(crate) use tilt::{output_percent, output_pitch};
 
 use crate::{
     CameraActuators,
     api::{self, ServoChannel},
+    camera_identity::ActuatorsByCamera,
     mavlink::{self, MavlinkComponent},
 };
 
@@ -128,7 +130,7 @@ pub struct Manager {
 
 #[derive(Debug)]
 pub struct State {
-    pub actuators: IndexMap<Uuid, CameraActuators>,
+    pub(crate) actuators: ActuatorsByCamera,
 }
 
 impl State {
@@ -144,7 +146,7 @@ impl State {
         let actuators = settings
             .get_actuators()
             .iter()
-            .map(|(uuid, actuator_settings)| (*uuid, CameraActuators::from(actuator_settings)))
+            .map(|(key, actuator_settings)| (key.clone(), CameraActuators::from(actuator_settings)))
             .collect();
 
         Ok(Self { actuators })
@@ -155,15 +157,41 @@ impl Manager {
     /// Send focus/zoom setpoints without waiting for SERVO (caller measures separately).
     ///
     /// Does not touch `MANAGER` — caller must validate the camera has actuators first.
+    /// Tilt depends on the camera's mount, see [`Self::apply_tilt_degrees`].
     #[instrument(level = "debug")]
     pub async fn apply_state_setpoints(new_state: &api::ActuatorsState) -> Result<()> {
+        Self::send_state_setpoints(0, new_state).await
+    }
+
+    /// Send focus/zoom setpoints to the autopilot camera instance of `camera_uuid` only.
+    #[instrument(level = "debug")]
+    pub async fn apply_camera_state_setpoints(
+        camera_uuid: &Uuid,
+        new_state: &api::ActuatorsState,
+    ) -> Result<()> {
+        let camera_id = {
+            let manager = MANAGER.get().context("Not available")?.read().await;
+            manager
+                .settings
+                .actuators
+                .get(camera_uuid)
+                .context(crate::ACTUATORS_NOT_CONFIGURED)?
+                .parameters
+                .camera_id as u8
+        };
+
+        Self::send_state_setpoints(camera_id, new_state).await
+    }
+
+    /// `camera_id` is the autopilot camera instance, 0 for all of them.
+    async fn send_state_setpoints(camera_id: u8, new_state: &api::ActuatorsState) -> Result<()> {
         use ::mavlink::ardupilotmega::{COMMAND_LONG_DATA, CameraZoomType, MavCmd, SetFocusType};
 
-        if new_state.tilt.is_some() {
+        if new_state.tilt.is_some() || new_state.tilt_degrees.is_some() {
             if new_state.focus.is_none() && new_state.zoom.is_none() {
-                return Err(anyhow::anyhow!("Tilt setpoint is not implemented"));
+                return Err(anyhow::anyhow!("Tilt setpoints need the camera's mount"));
             }
-            warn!("Ignoring unimplemented tilt setpoint; applying focus/zoom only");
+            warn!("Ignoring tilt setpoint without a camera; applying focus/zoom only");
         }
 
         let mavlink = crate::mavlink::component()?;
@@ -177,7 +205,7 @@ impl Manager {
                     confirmation: 0,
                     param1: SetFocusType::FOCUS_TYPE_RANGE as u8 as f32,
                     param2: focus,
-                    param3: 0 as f32,
+                    param3: camera_id as f32,
                     ..Default::default()
                 })
                 .await
@@ -193,7 +221,7 @@ impl Manager {
                     confirmation: 0,
                     param1: CameraZoomType::ZOOM_TYPE_RANGE as u8 as f32,
                     param2: zoom,
-                    param3: 0 as f32,
+                    param3: camera_id as f32,
                     ..Default::default()
                 })
                 .await
@@ -211,8 +239,9 @@ impl Manager {
             manager
                 .settings
                 .actuators
+                .by_key()
                 .iter()
-                .map(|(uuid, actuator_settings)| (*uuid, actuator_settings.into()))
+                .map(|(key, actuator_settings)| (key.clone(), actuator_settings.into()))
                 .collect()
         };
 
@@ -237,6 +266,8 @@ impl Manager {
         new_config: &api::ActuatorsConfig,
         overwrite: bool,
     ) -> Result<bool> {
+        crate::vehicle_profiles::ensure_known()?;
+
         let mut autopilot_reboot_required = overwrite;
 
         if let Some(parameters) = &new_config.parameters {
@@ -323,6 +354,75 @@ impl Manager {
 
         Self::update_config(camera_uuid, &config, true).await
     }
+
+    /// Take over imported actuators, pushing to the autopilot only what differs from the
+    /// current configuration. The live state is kept.
+    ///
+    /// Caller must hold [`CONFIG_APPLY`]. Returns `true` if the autopilot must be rebooted
+    /// before [`Self::finalize_config_after_reboot`].
+    #[instrument(level = "debug", skip(imported))]
+    pub async fn import_config(camera_uuid: &Uuid, imported: CameraActuators) -> Result<bool> {
+        let config = api::ActuatorsConfig::from(&imported);
+
+        let known = {
+            let mut manager = MANAGER.get().context("Not available")?.write().await;
+            match manager.settings.actuators.get_mut(camera_uuid) {
+                // Parameters and curves are left for update_config to diff and push.
+                Some(actuators) => {
+                    actuators.samples = imported.samples;
+                    actuators.calibration_sets = imported.calibration_sets;
+                    actuators.optics = imported.optics;
+                    actuators.motion_profiles = imported.motion_profiles;
+                    actuators.presets = imported.presets;
+                    true
+                }
+                None => {
+                    manager.settings.actuators.insert(*camera_uuid, imported);
+                    false
+                }
+            }
+        };
+
+        // A camera new to this vehicle has nothing on the autopilot to diff against.
+        Self::update_config(camera_uuid, &config, !known).await
+    }
+
+    /// Drop the actuators and stream preferences of `camera_uuids`, whose actuators must
+    /// already be reset on the autopilot.
+    #[instrument(level = "debug")]
+    pub async fn forget_configs(camera_uuids: &[Uuid]) -> Result<()> {
+        let apply = CONFIG_APPLY.lock().await;
+        let removed: Vec<Uuid> = {
+            let mut manager = MANAGER.get().context("Not available")?.write().await;
+            camera_uuids
+                .iter()
+                .filter(|camera_uuid| {
+                    manager
+                        .settings
+                        .actuators
+                        .shift_remove(camera_uuid)
+                        .is_some()
+                })
+                .copied()
+                .collect()
+        };
+        if !removed.is_empty() {
+            Self::save_actuators_settings().await?;
+            for camera_uuid in &removed {
+                info!(%camera_uuid, "Forgot actuators configuration for camera");
+            }
+
+            drop(apply);
+            owned_parameters::rebuild().await;
+            owned_parameters::reevaluate_after_apply().await;
+        }
+        for camera_uuid in camera_uuids {
+            crate::calibration_session::discard(*camera_uuid);
+        }
+        settings::forget_stream_settings(camera_uuids).await?;
+
+        Ok(())
+    }
 }
 
 /// Constructs our manager, Should be done inside main
@@ -364,7 +464,21 @@ pub async fn init(
         MavlinkComponent::try_new(mavlink_address, mavlink_system_id, mavlink_component_id).await?;
     mavlink::init_component(mavlink)?;
 
+    // Before anything below gets a chance to push parameters to an unknown vehicle.
+    let identified = match crate::vehicle_profiles::identify().await {
+        Ok(()) => true,
+        Err(error) => {
+            warn!("Failed to identify the autopilot: {error:#}");
+            false
+        }
+    };
+    crate::vehicle_profiles::start(identified);
     crate::actuators_watch::start();
+    crate::calibration_sets::start();
+    crate::telemetry::start();
+    crate::camera_protocol::start();
+    crate::state_publisher::start();
+    crate::gimbal::start();
 
     crate::health::refresh_lua_script_status().await;
 
@@ -479,14 +593,49 @@ pub(crate) fn actuators_state_from_servo(
             .map(|value| percentage_within_range(value, min, max))
     };
 
-    let tilt = {
-        let channel = actuators.parameters.tilt_channel;
-        let min = actuators.parameters.tilt_channel_min;
-        let max = actuators.parameters.tilt_channel_max;
+    let (tilt, tilt_degrees) = {
+        let parameters = &actuators.parameters;
+        let vehicle_pitch = crate::telemetry::snapshot().pitch;
 
-        get_output_raw_from_channel(servo_output_raw, channel)
-            .map(|value| percentage_within_range(value, min, max))
+        // A gimbal manager reports the mount attitude itself, which also covers mounts
+        // without a servo output.
+        let reported = match parameters.tilt_backend {
+            api::TiltBackend::GimbalManager => {
+                crate::gimbal::pitch(crate::gimbal::gimbal_device_id(parameters.camera_id))
+            }
+            api::TiltBackend::Servo => None,
+        };
+
+        match reported {
+            Some(degrees) => (
+                Some(output_percent(parameters, degrees, vehicle_pitch).round()),
+                Some(degrees),
+            ),
+            None => {
+                let tilt = get_output_raw_from_channel(servo_output_raw, parameters.tilt_channel)
+                    .map(|value| {
+                        percentage_within_range(
+                            value,
+                            parameters.tilt_channel_min,
+                            parameters.tilt_channel_max,
+                        )
+                    });
+                (
+                    tilt,
+                    tilt.map(|tilt| output_pitch(parameters, tilt, vehicle_pitch)),
+                )
+            }
+        }
+    };
+
+    let mut state = api::ActuatorsState {
+        focus,
+        zoom,
+        tilt,
+        tilt_degrees,
+        ..Default::default()
     };
+    crate::optics::annotate_state(actuators, servo_output_raw, &mut state);
 
-    api::ActuatorsState { focus, zoom, tilt }
+    state
 }
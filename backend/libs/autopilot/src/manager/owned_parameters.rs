@@ -45,11 +45,11 @@ pub(crate) async fn rebuild() {
     let mut merged = IndexMap::new();
     if let Some(manager) = MANAGER.get() {
         let guard = manager.read().await;
-        for (camera_uuid, actuators) in &guard.settings.actuators {
+        for (camera_uuid, actuators) in guard.settings.actuators.iter() {
             let mut per_camera = IndexMap::new();
             push_all_expectations(&actuators.parameters, &mut per_camera);
             if !per_camera.is_empty() {
-                merged.insert(*camera_uuid, per_camera);
+                merged.insert(camera_uuid, per_camera);
             }
         }
     }
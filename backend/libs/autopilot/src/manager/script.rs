@@ -597,6 +597,9 @@ fn generate_lua_script(config: &CameraActuators) -> Result<String> {
     context.insert("k_script", &(config.parameters.script_function as u8));
     context.insert("closest_points", &config.closest_points.to_lua());
     context.insert("furthest_points", &config.furthest_points.to_lua());
+    context.insert("presets", &crate::presets::to_lua(&config.presets));
+    // ArduPilot numbers its mounts from 0.
+    context.insert("mount_instance", &(channel - 1));
     context.insert("version", env!("CARGO_PKG_VERSION"));
 
     let template = include_str!("br4kcam.lua.template");
@@ -648,6 +651,11 @@ async fn remove_conflicting_owned_scripts(keep: &std::path::Path) {
     }
 }
 
+/// Fail when `actuators` would not produce a loadable Lua script.
+pub(crate) fn check_script(actuators: &CameraActuators) -> Result<()> {
+    validate_lua(&generate_lua_script(actuators)?)
+}
+
 fn validate_lua(script: &str) -> Result<()> {
     Lua::new()
         .load(script)
@@ -5,7 +5,7 @@ use tracing::*;
 use uuid::Uuid;
 
 use crate::{
-    api, generate_update_channel_param_function, generate_update_mount_param_function,
+    api, generate_update_channel_param_function, generate_update_mount_param_function, gimbal,
     manager::Manager,
     parameters::{ActuatorsParameters, ChannelFunction, ParamType},
 };
@@ -17,7 +17,110 @@ pub(super) fn tilt_mount_id(camera_id: api::CameraID) -> TiltChannelFunction {
     }
 }
 
+/// Mount pitch in degrees for a tilt percentage, over the configured pitch range.
+///
+/// 0% is the lowest PWM of the tilt channel, which drives the mount to its minimum pitch
+/// unless the channel is reversed.
+pub(crate) fn tilt_degrees(parameters: &ActuatorsParameters, percent: f32) -> f32 {
+    let percent = if parameters.tilt_channel_reversed {
+        100.0 - percent
+    } else {
+        percent
+    };
+    let (min, max) = (
+        parameters.tilt_mnt_pitch_min as f32,
+        parameters.tilt_mnt_pitch_max as f32,
+    );
+
+    min + (max - min) * percent.clamp(0.0, 100.0) / 100.0
+}
+
+/// Tilt percentage for a mount pitch in degrees, the inverse of [`tilt_degrees`].
+pub(crate) fn tilt_percent(parameters: &ActuatorsParameters, degrees: f32) -> f32 {
+    let (min, max) = (
+        parameters.tilt_mnt_pitch_min as f32,
+        parameters.tilt_mnt_pitch_max as f32,
+    );
+    if max == min {
+        return 0.0;
+    }
+
+    let percent = (100.0 * (degrees - min) / (max - min)).clamp(0.0, 100.0);
+    if parameters.tilt_channel_reversed {
+        100.0 - percent
+    } else {
+        percent
+    }
+}
+
+/// Camera pitch over the horizon in degrees for a tilt output of `percent`.
+///
+/// A servo mount is stabilised by the autopilot, so its output is relative to the vehicle
+/// and the vehicle pitch is added back. A brushless gimbal stabilises itself, so its output
+/// already is the pitch over the horizon.
+pub(crate) fn output_pitch(
+    parameters: &ActuatorsParameters,
+    percent: f32,
+    vehicle_pitch: Option<f32>,
+) -> f32 {
+    tilt_degrees(parameters, percent) + stabilisation_offset(parameters, vehicle_pitch)
+}
+
+/// Tilt output in percent for a camera pitch over the horizon, the inverse of
+/// [`output_pitch`].
+pub(crate) fn output_percent(
+    parameters: &ActuatorsParameters,
+    degrees: f32,
+    vehicle_pitch: Option<f32>,
+) -> f32 {
+    tilt_percent(
+        parameters,
+        degrees - stabilisation_offset(parameters, vehicle_pitch),
+    )
+}
+
+fn stabilisation_offset(parameters: &ActuatorsParameters, vehicle_pitch: Option<f32>) -> f32 {
+    match parameters.tilt_mnt_type {
+        api::MountType::Servo => vehicle_pitch.unwrap_or_default(),
+        api::MountType::BrushlessPWM => 0.0,
+    }
+}
+
 impl Manager {
+    /// Point `camera_uuid`'s mount to `degrees` of pitch through its tilt backend.
+    ///
+    /// Does not hold `MANAGER` while waiting for the autopilot.
+    #[instrument(level = "debug")]
+    pub async fn apply_tilt_degrees(camera_uuid: &Uuid, degrees: f32) -> Result<()> {
+        let parameters = {
+            let manager = crate::manager::MANAGER
+                .get()
+                .context("Not available")?
+                .read()
+                .await;
+            manager
+                .settings
+                .actuators
+                .get(camera_uuid)
+                .context(crate::ACTUATORS_NOT_CONFIGURED)?
+                .parameters
+                .clone()
+        };
+
+        let (min, max) = (
+            parameters
+                .tilt_mnt_pitch_min
+                .min(parameters.tilt_mnt_pitch_max) as f32,
+            parameters
+                .tilt_mnt_pitch_min
+                .max(parameters.tilt_mnt_pitch_max) as f32,
+        );
+        let degrees = degrees.clamp(min, max);
+
+        // Both backends address the camera's own mount; they differ only in the feedback.
+        gimbal::set_pitch(gimbal::gimbal_device_id(parameters.camera_id), degrees).await
+    }
+
     #[instrument(level = "debug", skip(parameters))]
     pub async fn update_tilt_parameters(
         camera_uuid: &Uuid,
@@ -120,10 +223,45 @@ impl Manager {
             }
         }
 
+        Self::update_tilt_backend(camera_uuid, parameters).await?;
+
         Self::update_tilt_channel_parameters(camera_uuid, parameters, autopilot_reboot_required)
             .await
             .map(|reboot| autopilot_reboot_required | reboot)
     }
+
+    /// The tilt backend only changes how we command the mount; nothing to write to the
+    /// autopilot.
+    #[instrument(level = "debug", skip(parameters))]
+    pub async fn update_tilt_backend(
+        camera_uuid: &Uuid,
+        parameters: &api::ActuatorsParametersConfig,
+    ) -> Result<()> {
+        let Some(tilt_backend) = parameters.tilt_backend else {
+            return Ok(());
+        };
+
+        let mut manager = crate::manager::MANAGER
+            .get()
+            .context("Not available")?
+            .write()
+            .await;
+        let current_parameters = &mut manager
+            .settings
+            .actuators
+            .entry(*camera_uuid)
+            .or_default()
+            .parameters;
+        if current_parameters.tilt_backend != tilt_backend {
+            info!(
+                "tilt_backend changed from {:?} to {tilt_backend:?}",
+                current_parameters.tilt_backend
+            );
+            current_parameters.tilt_backend = tilt_backend;
+        }
+
+        Ok(())
+    }
     #[instrument(level = "debug", skip(parameters))]
     pub async fn update_tilt_channel_parameters(
         camera_uuid: &Uuid,
@@ -298,3 +436,47 @@ pub(super) fn push_owned_expectations(
     Manager::expect_owned_tilt_mnt_pitch_max(parameters, map);
     Manager::expect_owned_tilt_mnt_type(parameters, map);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tilt_percent_inverts_tilt_degrees() {
+        for tilt_channel_reversed in [false, true] {
+            let parameters = ActuatorsParameters {
+                tilt_channel_reversed,
+                ..Default::default()
+            };
+            for percent in [0.0, 12.5, 50.0, 100.0] {
+                let degrees = tilt_degrees(&parameters, percent);
+                assert!((tilt_percent(&parameters, degrees) - percent).abs() < 1e-3);
+            }
+        }
+
+        let parameters = ActuatorsParameters::default();
+        assert_eq!(tilt_percent(&parameters, -90.0), 0.0);
+        assert_eq!(tilt_percent(&parameters, 90.0), 100.0);
+    }
+
+    #[test]
+    fn only_servo_mounts_add_the_vehicle_pitch() {
+        let servo = ActuatorsParameters {
+            tilt_mnt_type: api::MountType::Servo,
+            ..Default::default()
+        };
+        assert_eq!(output_pitch(&servo, 50.0, Some(10.0)), 10.0);
+        assert_eq!(output_pitch(&servo, 50.0, None), 0.0);
+        assert_eq!(output_percent(&servo, 10.0, Some(10.0)), 50.0);
+
+        let brushless = ActuatorsParameters {
+            tilt_mnt_type: api::MountType::BrushlessPWM,
+            ..servo
+        };
+        assert_eq!(output_pitch(&brushless, 50.0, Some(10.0)), 0.0);
+        assert_eq!(
+            output_percent(&brushless, 10.0, Some(10.0)),
+            tilt_percent(&brushless, 10.0)
+        );
+    }
+}
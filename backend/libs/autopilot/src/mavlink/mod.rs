@@ -9,7 +9,10 @@ use anyhow::{Context, Result, anyhow};
 use indexmap::IndexMap;
 use mavlink::{
     self, MavHeader, Message as _, MessageData,
-    ardupilotmega::{COMMAND_LONG_DATA, MavCmd, MavMessage, MavResult, SERVO_OUTPUT_RAW_DATA},
+    ardupilotmega::{
+        AUTOPILOT_VERSION_DATA, COMMAND_LONG_DATA, MavCmd, MavMessage, MavResult,
+        SERVO_OUTPUT_RAW_DATA,
+    },
 };
 use once_cell::sync::OnceCell;
 use tokio::sync::{RwLock, broadcast};
@@ -52,6 +55,10 @@ impl MavlinkComponent {
         self.inner.system_id
     }
 
+    pub fn component_id(&self) -> u8 {
+        self.inner.component_id
+    }
+
     #[instrument(level = "debug")]
     pub async fn try_new(address: String, system_id: u8, component_id: u8) -> Result<Self> {
         let inner = Arc::new(ComponentInner::try_new(address, system_id, component_id).await?);
@@ -221,22 +228,7 @@ impl MavlinkComponent {
         crate::health::set_rebooting(true);
         let _guard = RebootingGuard;
 
-        // This is a workaround to this issue: https://github.com/bluerobotics/br4kcam-manager/issues/57
-        blueos_client::reboot_autopilot().await?;
-
-        // FIXME: once the aforementioned issue is fixed, we can use the code below:
-        // let target_system = { self.inner.read().await.system_id };
-        // let target_component = mavlink::ardupilotmega::MavComponent::MAV_COMP_ID_AUTOPILOT1 as u8;
-
-        // self.send_command(COMMAND_LONG_DATA {
-        //     target_system,
-        //     target_component,
-        //     confirmation: 0,
-        //     command: MavCmd::MAV_CMD_PREFLIGHT_REBOOT_SHUTDOWN,
-        //     param1: 1.0, // autopilot
-        //     ..Default::default()
-        // })
-        // .await?;
+        crate::platform::platform().reboot_autopilot(self).await?;
 
         // Give autopilot some time to shutdown before waiting for it to come back online
         tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
@@ -420,6 +412,23 @@ impl MavlinkComponent {
         self.inner.get_receiver().await
     }
 
+    /// Queue `message` on behalf of `component_id`, which shares this component's system id.
+    #[instrument(level = "debug", skip(self, message))]
+    pub(crate) async fn send_as(&self, component_id: u8, message: MavMessage) -> Result<()> {
+        let header = MavHeader {
+            system_id: self.inner.system_id,
+            component_id,
+            sequence: 0,
+        };
+
+        self.inner
+            .get_sender()
+            .await
+            .send(Message::ToBeSent((header, message)))?;
+
+        Ok(())
+    }
+
     /// Request the autopilot to stream `message_id` at `interval_us` microseconds.
     #[instrument(level = "debug", skip(self))]
     pub async fn set_message_interval(&self, message_id: u32, interval_us: f32) -> Result<()> {
@@ -504,6 +513,55 @@ impl MavlinkComponent {
             }
         }
     }
+
+    /// One-shot request for `AUTOPILOT_VERSION`. Holds the mavlink txn for the whole RPC.
+    #[instrument(level = "debug", skip(self))]
+    pub async fn request_autopilot_version(&self) -> Result<AUTOPILOT_VERSION_DATA> {
+        let target_system = self.inner.system_id;
+        let target_component = mavlink::ardupilotmega::MavComponent::MAV_COMP_ID_AUTOPILOT1 as u8;
+
+        let _txn = self.inner.txn.lock().await;
+        let mut receiver = self.inner.get_receiver().await;
+
+        self.send_command_locked(COMMAND_LONG_DATA {
+            command: MavCmd::MAV_CMD_REQUEST_MESSAGE,
+            target_system,
+            target_component,
+            confirmation: 0,
+            param1: AUTOPILOT_VERSION_DATA::ID as f32,
+            ..Default::default()
+        })
+        .await?;
+
+        let wait_message = async {
+            loop {
+                use broadcast::error::RecvError;
+
+                match receiver.recv().await {
+                    Ok(Message::Received((
+                        recv_header,
+                        MavMessage::AUTOPILOT_VERSION(autopilot_version),
+                    ))) if recv_header.system_id == target_system
+                        && recv_header.component_id == target_component =>
+                    {
+                        return Ok(autopilot_version);
+                    }
+                    Ok(_) => continue,
+                    Err(RecvError::Closed) => {
+                        return Err(anyhow!("Receiver channel closed"));
+                    }
+                    Err(RecvError::Lagged(n)) => {
+                        warn!("Receiver lagged by {n} messages");
+                        continue;
+                    }
+                }
+            }
+        };
+
+        tokio::time::timeout(tokio::time::Duration::from_secs(1), wait_message)
+            .await
+            .context("AUTOPILOT_VERSION not delivered within 1s after ACK")?
+    }
 }
 
 impl Drop for MavlinkComponent {
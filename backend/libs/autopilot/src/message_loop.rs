@@ -0,0 +1,92 @@
+//! Background tasks of this crate, each spawned once. The ones following the vehicle share
+//! a single task reading the MAVLink messages received by the manager's component, which
+//! hands every message to each of their handlers and reopens the receiver whenever it
+//! closes.
+
+use std::{collections::HashSet, future::Future, sync::Mutex, time::Duration};
+
+use futures::future::{BoxFuture, join_all};
+use mavlink::{MavHeader, ardupilotmega::MavMessage};
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast;
+use tracing::*;
+
+use crate::mavlink::{MavlinkComponent, Message};
+
+const RECEIVER_REOPEN_BACKOFF: Duration = Duration::from_secs(1);
+const MESSAGE_LOOP: &str = "message loop";
+
+type Handler =
+    Box<dyn Fn(&'static MavlinkComponent, MavHeader, MavMessage) -> BoxFuture<'static, ()> + Send>;
+
+static STARTED: Lazy<Mutex<HashSet<&'static str>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+static HANDLERS: Lazy<Mutex<Vec<(&'static str, Handler)>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Spawn `task` unless a task named `name` was already spawned.
+pub(crate) fn spawn_once(name: &'static str, task: impl Future<Output = ()> + Send + 'static) {
+    if !STARTED.lock().unwrap().insert(name) {
+        return;
+    }
+
+    tokio::spawn(task);
+}
+
+/// Call `handler` with every received MAVLink message, once per `name`, from the shared
+/// message loop.
+pub(crate) fn add_message_handler<F, Fut>(name: &'static str, handler: F)
+where
+    F: Fn(&'static MavlinkComponent, MavHeader, MavMessage) -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    {
+        let mut handlers = HANDLERS.lock().unwrap();
+        if handlers.iter().any(|(registered, _)| *registered == name) {
+            return;
+        }
+        handlers.push((
+            name,
+            Box::new(move |mavlink, header, message| {
+                Box::pin(handler(mavlink, header, message)) as BoxFuture<'static, ()>
+            }),
+        ));
+    }
+
+    spawn_once(MESSAGE_LOOP, message_loop());
+}
+
+#[instrument(level = "debug")]
+async fn message_loop() {
+    loop {
+        let Ok(mavlink) = crate::mavlink::component() else {
+            tokio::time::sleep(RECEIVER_REOPEN_BACKOFF).await;
+            continue;
+        };
+        let mut receiver = mavlink.get_receiver().await;
+
+        loop {
+            match receiver.recv().await {
+                Ok(Message::Received((header, message))) => {
+                    let handling = HANDLERS
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .map(|(name, handler)| {
+                            handler(mavlink, header, message.clone())
+                                .instrument(debug_span!("handler", name))
+                        })
+                        .collect::<Vec<_>>();
+                    join_all(handling).await;
+                }
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Closed) => {
+                    debug!("MAVLink receiver closed; reopening");
+                    tokio::time::sleep(RECEIVER_REOPEN_BACKOFF).await;
+                    break;
+                }
+                Err(broadcast::error::RecvError::Lagged(samples)) => {
+                    debug!("Lagged by {samples} MAVLink messages");
+                }
+            }
+        }
+    }
+}
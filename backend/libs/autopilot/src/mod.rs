@@ -1,10 +1,28 @@
 mod actuators_watch;
 pub mod api;
+mod calibration_csv;
+mod calibration_session;
+mod calibration_sets;
+mod camera_commands;
+mod camera_identity;
+mod camera_protocol;
+mod gimbal;
 mod health;
+mod image_capture;
+mod jog;
 mod manager;
 mod mavlink;
+mod message_loop;
+mod motion;
+mod optics;
 pub mod parameters;
+mod platform;
+mod presets;
+mod settings_transfer;
 mod settings_translations;
+mod state_publisher;
+mod telemetry;
+mod vehicle_profiles;
 
 use anyhow::{Context, Result};
 use axum::Json;
@@ -18,12 +36,28 @@ pub use actuators_watch::{
     remove_interest as remove_actuators_state_interest, shutdown as shutdown_actuators_stream,
     subscribe as subscribe_actuators_state,
 };
+pub use camera_identity::adopt_camera_serial;
+pub use camera_protocol::{
+    MavlinkCamera, MavlinkVideoStream, mavlink_cameras, remove_mavlink_camera, set_mavlink_camera,
+};
 pub use health::{
     ParameterDrift, diagnostics, health, lua_script_status, lua_scripting_disabled,
     needs_mavlink_endpoint_ensure, parameter_drifts, report_endpoint_setup, rpc_failed, rpc_ok,
     set_backend_version, set_rebooting, set_syncing, subscribe_health,
 };
+pub use image_capture::{report_image_captured, subscribe_image_capture_requests};
 pub use manager::{clear_saved_settings, init};
+pub use platform::{Platform, platform, set_platform};
+pub use presets::get as actuators_presets;
+pub use settings_transfer::{
+    ImportPlan as SettingsImportPlan, apply as apply_settings_import, export as export_settings,
+    plan as plan_settings_import,
+};
+pub use state_publisher::{
+    DataLakeValue, data_lake_name, set_actuators_state_rate, set_camera_online,
+    set_camera_video_healthy,
+};
+pub use vehicle_profiles::{adopt as adopt_vehicle, unknown_vehicle, vehicles};
 
 use crate::{
     manager::MANAGER,
@@ -33,21 +67,47 @@ use crate::{
 /// Context message when a camera has no actuators entry yet.
 pub const ACTUATORS_NOT_CONFIGURED: &str = "Camera's actuators not configured";
 
+/// Name of the calibration set every camera starts with.
+pub const DEFAULT_CALIBRATION_SET: &str = "default";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 struct CameraActuators {
     pub parameters: ActuatorsParameters,
     pub closest_points: api::FocusZoomPoints,
     pub furthest_points: api::FocusZoomPoints,
+    pub samples: api::CalibrationSamples,
+    pub calibration_sets: api::CalibrationSets,
     pub state: api::ActuatorsState,
+    pub optics: Option<api::OpticsMapping>,
+    pub motion_profiles: api::MotionProfiles,
+    pub presets: indexmap::IndexMap<String, api::ActuatorsPreset>,
 }
 
 impl Default for CameraActuators {
     fn default() -> Self {
+        let closest_points = api::FocusZoomPoints(CLOSEST_POINTS.to_vec());
+        let furthest_points = api::FocusZoomPoints(FURTHEST_POINTS.to_vec());
+
         Self {
             parameters: ActuatorsParameters::default(),
-            closest_points: api::FocusZoomPoints(CLOSEST_POINTS.to_vec()),
-            furthest_points: api::FocusZoomPoints(FURTHEST_POINTS.to_vec()),
+            calibration_sets: api::CalibrationSets {
+                active: DEFAULT_CALIBRATION_SET.to_string(),
+                sets: indexmap::IndexMap::from([(
+                    DEFAULT_CALIBRATION_SET.to_string(),
+                    api::CalibrationSet {
+                        closest_points: closest_points.clone(),
+                        furthest_points: furthest_points.clone(),
+                    },
+                )]),
+                auto_switch: None,
+            },
+            closest_points,
+            furthest_points,
+            samples: api::CalibrationSamples::default(),
             state: api::ActuatorsState::default(),
+            optics: None,
+            motion_profiles: api::MotionProfiles::default(),
+            presets: indexmap::IndexMap::new(),
         }
     }
 }
@@ -61,29 +121,34 @@ pub fn error_indicates_actuators_not_configured(message: &str) -> bool {
 /// install expects to find. Empty when the manager is not up yet.
 pub async fn configured_cameras() -> Vec<Uuid> {
     match MANAGER.get() {
-        Some(manager) => manager
-            .read()
-            .await
-            .settings
-            .actuators
-            .keys()
-            .copied()
-            .collect(),
+        Some(manager) => manager.read().await.settings.actuators.keys().collect(),
         None => Vec::new(),
     }
 }
 
+/// Parameters of `camera_uuid`'s actuators, or `None` when they are not configured.
+pub async fn actuators_parameters(camera_uuid: Uuid) -> Option<ActuatorsParameters> {
+    let manager = MANAGER.get()?.read().await;
+    manager
+        .settings
+        .actuators
+        .get(&camera_uuid)
+        .map(|actuators| actuators.parameters.clone())
+}
+
 /// Shared entry point for REST and WebSocket autopilot control requests.
 #[instrument(level = "debug")]
 pub async fn handle_control(actuators_control: api::ActuatorsControl) -> Result<serde_json::Value> {
-    control_inner(Json(actuators_control)).await
+    Ok(serde_json::to_value(
+        control_inner(Json(actuators_control)).await?,
+    )?)
 }
 
 #[instrument(level = "debug")]
 pub(crate) async fn control_inner(
     actuators_control: Json<api::ActuatorsControl>,
-) -> Result<serde_json::Value> {
-    use api::Action;
+) -> Result<api::ActuatorsControlResult> {
+    use api::{Action, ActuatorsControlResult};
 
     debug!("Got control query: {actuators_control:#?}");
 
@@ -106,7 +171,7 @@ pub(crate) async fn control_inner(
             )
             .await?;
 
-            serde_json::to_value({})?
+            ActuatorsControlResult::None
         }
         Action::GetActuatorsState => {
             // Prefer the SERVO watcher's cache when interest is on *and* a recent
@@ -123,7 +188,7 @@ pub(crate) async fn control_inner(
                     .get(&actuators_control.camera_uuid)
                     .context(crate::ACTUATORS_NOT_CONFIGURED)?;
 
-                serde_json::to_value(actuators.state)?
+                ActuatorsControlResult::State(actuators.state)
             } else {
                 // Wait for SERVO under a read lock so the watcher can still write.
                 {
@@ -151,25 +216,36 @@ pub(crate) async fn control_inner(
                 if !actuators_watch::servo_mark_advanced(age_before, age_after) {
                     actuators.state = state;
                     actuators_watch::mark_servo_from_get_state(actuators_control.camera_uuid);
-                    serde_json::to_value(state)?
+                    ActuatorsControlResult::State(state)
                 } else {
-                    serde_json::to_value(actuators.state)?
+                    ActuatorsControlResult::State(actuators.state)
                 }
             }
         }
         Action::SetActuatorsState(new_state) => {
             let camera_uuid = actuators_control.camera_uuid;
-            let focus_was_set = new_state.focus.is_some();
             // Validate entry, then send MAVLink with no Manager lock held.
-            {
+            let (setpoints, tilt_degrees) = {
                 let manager = MANAGER.get().context("Not available")?.read().await;
-                let _ = manager
+                let actuators = manager
                     .settings
                     .actuators
                     .get(&camera_uuid)
                     .context(crate::ACTUATORS_NOT_CONFIGURED)?;
-            }
-            manager::Manager::apply_state_setpoints(new_state).await?;
+                let tilt_degrees = new_state.tilt_degrees.or_else(|| {
+                    let vehicle_pitch = telemetry::snapshot().pitch;
+                    new_state.tilt.map(|tilt| {
+                        manager::output_pitch(&actuators.parameters, tilt, vehicle_pitch)
+                    })
+                });
+                (
+                    optics::resolve_setpoint(actuators, new_state)?,
+                    tilt_degrees,
+                )
+            };
+            let focus_was_set = setpoints.focus.is_some();
+            jog::stop(camera_uuid);
+            motion::move_to(camera_uuid, setpoints.focus, setpoints.zoom, tilt_degrees).await?;
             let age_before = actuators_watch::last_servo_age(camera_uuid);
             let servo_output_raw = crate::mavlink::component()?
                 .request_servo_output_raw()
@@ -224,24 +300,24 @@ pub(crate) async fn control_inner(
                     }
                 }
             }
-            serde_json::to_value(state)?
+            ActuatorsControlResult::State(state)
         }
         Action::GetActuatorsConfig => {
             let manager = MANAGER.get().context("Not available")?.read().await;
 
-            let config: &api::ActuatorsConfig = &manager
+            let config: api::ActuatorsConfig = manager
                 .settings
                 .actuators
                 .get(&actuators_control.camera_uuid)
                 .context(crate::ACTUATORS_NOT_CONFIGURED)?
                 .into();
 
-            serde_json::to_value(config)?
+            ActuatorsControlResult::Config(Box::new(config))
         }
         Action::GetActuatorsDefaultConfig => {
             let config = api::ActuatorsConfig::from(&CameraActuators::default());
 
-            serde_json::to_value(config)?
+            ActuatorsControlResult::Config(Box::new(config))
         }
         Action::SetActuatorsConfig(new_config) => {
             let camera_uuid = actuators_control.camera_uuid;
@@ -271,14 +347,14 @@ pub(crate) async fn control_inner(
             .await?;
 
             let manager = MANAGER.get().context("Not available")?.read().await;
-            let config: &api::ActuatorsConfig = &manager
+            let config: api::ActuatorsConfig = manager
                 .settings
                 .actuators
                 .get(&camera_uuid)
                 .context(crate::ACTUATORS_NOT_CONFIGURED)?
                 .into();
 
-            serde_json::to_value(config)?
+            ActuatorsControlResult::Config(Box::new(config))
         }
         Action::ResetActuatorsConfig => {
             let camera_uuid = actuators_control.camera_uuid;
@@ -296,14 +372,14 @@ pub(crate) async fn control_inner(
             .await?;
 
             let manager = MANAGER.get().context("Not available")?.read().await;
-            let config: &api::ActuatorsConfig = &manager
+            let config: api::ActuatorsConfig = manager
                 .settings
                 .actuators
                 .get(&camera_uuid)
                 .context(crate::ACTUATORS_NOT_CONFIGURED)?
                 .into();
 
-            serde_json::to_value(config)?
+            ActuatorsControlResult::Config(Box::new(config))
         }
         Action::ForgetActuatorsConfig => {
             let camera_uuid = actuators_control.camera_uuid;
@@ -330,20 +406,213 @@ pub(crate) async fn control_inner(
                 .await?;
             }
 
-            let _apply = manager::CONFIG_APPLY.lock().await;
-            let removed = {
-                let mut manager = MANAGER.get().context("Not available")?.write().await;
-                manager.settings.actuators.shift_remove(&camera_uuid)
+            manager::Manager::forget_configs(&[camera_uuid]).await?;
+            ActuatorsControlResult::None
+        }
+        Action::StartCalibrationSession(start) => ActuatorsControlResult::CalibrationSession(
+            Box::new(calibration_session::start(actuators_control.camera_uuid, start).await?),
+        ),
+        Action::GetCalibrationSession => calibration_session::get(actuators_control.camera_uuid)
+            .map_or(ActuatorsControlResult::None, |session| {
+                ActuatorsControlResult::CalibrationSession(Box::new(session))
+            }),
+        Action::GoToCalibrationStep(step) => ActuatorsControlResult::CalibrationSession(Box::new(
+            calibration_session::go_to_step(actuators_control.camera_uuid, step.step).await?,
+        )),
+        Action::NudgeCalibrationFocus(nudge) => {
+            ActuatorsControlResult::CalibrationSession(Box::new(
+                calibration_session::nudge_focus(actuators_control.camera_uuid, nudge.delta)
+                    .await?,
+            ))
+        }
+        Action::CaptureCalibrationPoint => ActuatorsControlResult::CalibrationSession(Box::new(
+            calibration_session::capture(actuators_control.camera_uuid).await?,
+        )),
+        Action::PreviewCalibrationSession => {
+            let session = calibration_session::get(actuators_control.camera_uuid)
+                .context("No calibration session running")?;
+
+            ActuatorsControlResult::CalibrationPoints(session.points()?)
+        }
+        Action::CommitCalibrationSession => {
+            let camera_uuid = actuators_control.camera_uuid;
+            calibration_session::commit(camera_uuid).await?;
+
+            let manager = MANAGER.get().context("Not available")?.read().await;
+            let config: api::ActuatorsConfig = manager
+                .settings
+                .actuators
+                .get(&camera_uuid)
+                .context(crate::ACTUATORS_NOT_CONFIGURED)?
+                .into();
+
+            ActuatorsControlResult::Config(Box::new(config))
+        }
+        Action::DiscardCalibrationSession => {
+            calibration_session::discard(actuators_control.camera_uuid);
+            ActuatorsControlResult::None
+        }
+        Action::ImportCalibrationCsv(import) => {
+            let camera_uuid = actuators_control.camera_uuid;
+            let report = {
+                let manager = MANAGER.get().context("Not available")?.read().await;
+                let actuators = manager
+                    .settings
+                    .actuators
+                    .get(&camera_uuid)
+                    .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+                calibration_csv::parse(import, &actuators.parameters)?
             };
-            if removed.is_some() {
-                manager::Manager::save_actuators_settings().await?;
-                info!(%camera_uuid, "Forgot actuators configuration for camera");
 
-                drop(_apply);
-                manager::owned_parameters::rebuild().await;
-                manager::owned_parameters::reevaluate_after_apply().await;
+            match report.target {
+                api::CalibrationImportTarget::Samples => {
+                    let _apply = manager::CONFIG_APPLY.lock().await;
+                    {
+                        let mut manager = MANAGER.get().context("Not available")?.write().await;
+                        let actuators = manager
+                            .settings
+                            .actuators
+                            .get_mut(&camera_uuid)
+                            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+                        if let Some(points) = &report.closest {
+                            actuators.samples.closest = points.clone();
+                        }
+                        if let Some(points) = &report.furthest {
+                            actuators.samples.furthest = points.clone();
+                        }
+                    }
+                    manager::Manager::save_actuators_settings().await?;
+                }
+                api::CalibrationImportTarget::Calibration => {
+                    for points in [&report.closest, &report.furthest].into_iter().flatten() {
+                        if points.0.len() < 2 {
+                            return Err(anyhow::anyhow!(
+                                "A calibration curve requires at least 2 points"
+                            ));
+                        }
+                    }
+                    let new_config = api::ActuatorsConfig {
+                        closest_points: report.closest.clone(),
+                        furthest_points: report.furthest.clone(),
+                        ..Default::default()
+                    };
+                    manager::reboot_outside_apply(
+                        Box::pin(async {
+                            manager::Manager::update_config(&camera_uuid, &new_config, false).await
+                        }),
+                        Box::pin(async {
+                            manager::Manager::finalize_config_after_reboot(&camera_uuid, None).await
+                        }),
+                    )
+                    .await?;
+                }
             }
-            serde_json::Value::Null
+
+            ActuatorsControlResult::CalibrationCsvImport(report)
+        }
+        Action::GetCalibrationSamples => {
+            let manager = MANAGER.get().context("Not available")?.read().await;
+            let actuators = manager
+                .settings
+                .actuators
+                .get(&actuators_control.camera_uuid)
+                .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+            ActuatorsControlResult::CalibrationSamples(actuators.samples.clone())
+        }
+        Action::GetCalibrationSets => ActuatorsControlResult::CalibrationSets(Box::new(
+            calibration_sets::get(actuators_control.camera_uuid).await?,
+        )),
+        Action::SaveCalibrationSet(save) => {
+            calibration_sets::save(actuators_control.camera_uuid, save).await?;
+            ActuatorsControlResult::CalibrationSets(Box::new(
+                calibration_sets::get(actuators_control.camera_uuid).await?,
+            ))
+        }
+        Action::DeleteCalibrationSet(set) => {
+            calibration_sets::delete(actuators_control.camera_uuid, &set.name).await?;
+            ActuatorsControlResult::CalibrationSets(Box::new(
+                calibration_sets::get(actuators_control.camera_uuid).await?,
+            ))
+        }
+        Action::SwitchCalibrationSet(set) => {
+            calibration_sets::switch(actuators_control.camera_uuid, &set.name).await?;
+            ActuatorsControlResult::CalibrationSets(Box::new(
+                calibration_sets::get(actuators_control.camera_uuid).await?,
+            ))
+        }
+        Action::SetCalibrationAutoSwitch(auto_switch) => {
+            calibration_sets::set_auto_switch(actuators_control.camera_uuid, auto_switch.clone())
+                .await?;
+            ActuatorsControlResult::CalibrationSets(Box::new(
+                calibration_sets::get(actuators_control.camera_uuid).await?,
+            ))
+        }
+        Action::GetOpticsMapping => optics::get(actuators_control.camera_uuid).await?.map_or(
+            ActuatorsControlResult::None,
+            ActuatorsControlResult::OpticsMapping,
+        ),
+        Action::SetOpticsMapping(mapping) => {
+            optics::set(actuators_control.camera_uuid, mapping.clone()).await?;
+            optics::get(actuators_control.camera_uuid).await?.map_or(
+                ActuatorsControlResult::None,
+                ActuatorsControlResult::OpticsMapping,
+            )
+        }
+        Action::Jog(velocity) => {
+            ActuatorsControlResult::Jog(jog::jog(actuators_control.camera_uuid, velocity).await?)
+        }
+        Action::StopJog => {
+            jog::stop(actuators_control.camera_uuid);
+            ActuatorsControlResult::None
+        }
+        Action::StepActuators(step) => {
+            let camera_uuid = actuators_control.camera_uuid;
+            let setpoints = {
+                let manager = MANAGER.get().context("Not available")?.read().await;
+                let actuators = manager
+                    .settings
+                    .actuators
+                    .get(&camera_uuid)
+                    .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+                jog::step_setpoints(&actuators.state, step)?
+            };
+            Box::pin(control_inner(Json(api::ActuatorsControl {
+                camera_uuid,
+                action: Action::SetActuatorsState(setpoints),
+            })))
+            .await?
+        }
+        Action::GetMotionProfiles => ActuatorsControlResult::MotionProfiles(
+            motion::get(actuators_control.camera_uuid).await?,
+        ),
+        Action::SetMotionProfiles(profiles) => {
+            motion::set(actuators_control.camera_uuid, *profiles).await?;
+            ActuatorsControlResult::MotionProfiles(
+                motion::get(actuators_control.camera_uuid).await?,
+            )
+        }
+        Action::GetPresets => {
+            ActuatorsControlResult::Presets(presets::get(actuators_control.camera_uuid).await?)
+        }
+        Action::SavePreset(save) => {
+            presets::save(actuators_control.camera_uuid, save).await?;
+            ActuatorsControlResult::Presets(presets::get(actuators_control.camera_uuid).await?)
+        }
+        Action::DeletePreset(preset) => {
+            presets::delete(actuators_control.camera_uuid, &preset.name).await?;
+            ActuatorsControlResult::Presets(presets::get(actuators_control.camera_uuid).await?)
+        }
+        Action::RecallPreset(preset) => {
+            let camera_uuid = actuators_control.camera_uuid;
+            let setpoints = presets::setpoints(camera_uuid, &preset.name).await?;
+            info!(%camera_uuid, name = %preset.name, "Recalling preset");
+            // Same path as a client setpoint, so motion profiles and state reporting apply.
+            Box::pin(control_inner(Json(api::ActuatorsControl {
+                camera_uuid,
+                action: Action::SetActuatorsState(setpoints),
+            })))
+            .await?
         }
     };
 
@@ -0,0 +1,394 @@
+//! Rate-limited motion towards focus, zoom and tilt setpoints.
+//!
+//! Axes with a motion profile are not sent straight to their target: a task per camera and
+//! axis streams intermediate setpoints, limited to the profile's maximum rate and, for tilt,
+//! its acceleration. A new target preempts the motion in progress, carrying its velocity
+//! over.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::{Context, Result, anyhow};
+use once_cell::sync::Lazy;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{
+    api,
+    manager::{self, MANAGER},
+};
+
+const TICK: Duration = Duration::from_millis(50);
+/// Closer than this to the target counts as arrived.
+const ARRIVED: f32 = 0.01;
+
+static MOTIONS: Lazy<Mutex<HashMap<(Uuid, Axis), Motion>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_RUN: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Axis {
+    Focus,
+    Zoom,
+    Tilt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Limits {
+    max_rate: f32,
+    max_acceleration: Option<f32>,
+}
+
+#[derive(Debug)]
+struct Motion {
+    target: f32,
+    position: f32,
+    velocity: f32,
+    limits: Limits,
+    /// Task streaming this motion, so a cancelled one never picks up a later motion.
+    run: u64,
+}
+
+impl Axis {
+    /// Focus and zoom move in percent per second, tilt in degrees per second.
+    fn limits(self, profiles: &api::MotionProfiles) -> Option<Limits> {
+        let (max_rate, max_acceleration) = match self {
+            Axis::Focus => (profiles.focus_max_rate, None),
+            Axis::Zoom => (profiles.zoom_max_rate, None),
+            Axis::Tilt => (profiles.tilt_max_rate, profiles.tilt_max_acceleration),
+        };
+
+        Some(Limits {
+            max_rate: max_rate?,
+            max_acceleration,
+        })
+    }
+
+    fn position(self, state: &api::ActuatorsState) -> Option<f32> {
+        match self {
+            Axis::Focus => state.focus,
+            Axis::Zoom => state.zoom,
+            Axis::Tilt => state.tilt_degrees,
+        }
+    }
+
+    async fn apply(self, camera_uuid: Uuid, value: f32) -> Result<()> {
+        match self {
+            Axis::Focus => {
+                manager::Manager::apply_state_setpoints(&api::ActuatorsState {
+                    focus: Some(value),
+                    ..Default::default()
+                })
+                .await
+            }
+            Axis::Zoom => {
+                manager::Manager::apply_state_setpoints(&api::ActuatorsState {
+                    zoom: Some(value),
+                    ..Default::default()
+                })
+                .await
+            }
+            Axis::Tilt => manager::Manager::apply_tilt_degrees(&camera_uuid, value).await,
+        }
+    }
+}
+
+#[instrument(level = "debug")]
+pub async fn get(camera_uuid: Uuid) -> Result<api::MotionProfiles> {
+    let manager = MANAGER.get().context("Not available")?.read().await;
+    let actuators = manager
+        .settings
+        .actuators
+        .get(&camera_uuid)
+        .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+    Ok(actuators.motion_profiles)
+}
+
+#[instrument(level = "debug")]
+pub async fn set(camera_uuid: Uuid, profiles: api::MotionProfiles) -> Result<()> {
+    let limits = [
+        profiles.focus_max_rate,
+        profiles.zoom_max_rate,
+        profiles.tilt_max_rate,
+        profiles.tilt_max_acceleration,
+    ];
+    if limits
+        .into_iter()
+        .flatten()
+        .any(|limit| !limit.is_finite() || limit <= 0.0)
+    {
+        return Err(anyhow!("Motion limits must be positive"));
+    }
+    if profiles.tilt_max_acceleration.is_some() && profiles.tilt_max_rate.is_none() {
+        return Err(anyhow!("A tilt acceleration limit needs a tilt rate limit"));
+    }
+
+    {
+        let _apply = manager::CONFIG_APPLY.lock().await;
+        let mut manager = MANAGER.get().context("Not available")?.write().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get_mut(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+        actuators.motion_profiles = profiles;
+    }
+
+    manager::Manager::save_actuators_settings().await
+}
+
+/// Move `camera_uuid`'s axes to the given targets, following its motion profiles. Axes
+/// without a profile, or whose position is unknown, are sent straight to their target.
+#[instrument(level = "debug")]
+pub(crate) async fn move_to(
+    camera_uuid: Uuid,
+    focus: Option<f32>,
+    zoom: Option<f32>,
+    tilt_degrees: Option<f32>,
+) -> Result<()> {
+    let (profiles, state, tilt_range) = {
+        let manager = MANAGER.get().context("Not available")?.read().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+        let (min, max) = (
+            actuators.parameters.tilt_mnt_pitch_min as f32,
+            actuators.parameters.tilt_mnt_pitch_max as f32,
+        );
+        (
+            actuators.motion_profiles,
+            actuators.state,
+            (min.min(max), min.max(max)),
+        )
+    };
+
+    let mut immediate = Vec::new();
+    for (axis, target) in [
+        (Axis::Focus, focus.map(|focus| focus.clamp(0.0, 100.0))),
+        (Axis::Zoom, zoom.map(|zoom| zoom.clamp(0.0, 100.0))),
+        (
+            Axis::Tilt,
+            tilt_degrees.map(|tilt| tilt.clamp(tilt_range.0, tilt_range.1)),
+        ),
+    ] {
+        let Some(target) = target else {
+            continue;
+        };
+
+        match (axis.limits(&profiles), axis.position(&state)) {
+            (Some(limits), Some(position)) => schedule(camera_uuid, axis, target, position, limits),
+            _ => {
+                cancel(camera_uuid, axis);
+                immediate.push((axis, target));
+            }
+        }
+    }
+
+    let focus = immediate
+        .iter()
+        .find(|(axis, _)| *axis == Axis::Focus)
+        .map(|(_, target)| *target);
+    let zoom = immediate
+        .iter()
+        .find(|(axis, _)| *axis == Axis::Zoom)
+        .map(|(_, target)| *target);
+    if focus.is_some() || zoom.is_some() {
+        manager::Manager::apply_state_setpoints(&api::ActuatorsState {
+            focus,
+            zoom,
+            ..Default::default()
+        })
+        .await?;
+    }
+    if let Some((_, tilt)) = immediate.iter().find(|(axis, _)| *axis == Axis::Tilt) {
+        manager::Manager::apply_tilt_degrees(&camera_uuid, *tilt).await?;
+    }
+
+    Ok(())
+}
+
+/// Stop every motion of `camera_uuid` where it is.
+pub(crate) fn stop(camera_uuid: Uuid) {
+    MOTIONS
+        .lock()
+        .unwrap()
+        .retain(|(motion_camera, _), _| *motion_camera != camera_uuid);
+}
+
+fn cancel(camera_uuid: Uuid, axis: Axis) {
+    MOTIONS.lock().unwrap().remove(&(camera_uuid, axis));
+}
+
+fn schedule(camera_uuid: Uuid, axis: Axis, target: f32, position: f32, limits: Limits) {
+    let run = {
+        let mut motions = MOTIONS.lock().unwrap();
+        if let Some(motion) = motions.get_mut(&(camera_uuid, axis)) {
+            motion.target = target;
+            motion.limits = limits;
+            return;
+        }
+
+        let run = NEXT_RUN.fetch_add(1, Ordering::Relaxed);
+        motions.insert(
+            (camera_uuid, axis),
+            Motion {
+                target,
+                position,
+                velocity: 0.0,
+                limits,
+                run,
+            },
+        );
+        run
+    };
+
+    tokio::spawn(stream(camera_uuid, axis, run).instrument(Span::current()));
+}
+
+#[instrument(level = "debug")]
+async fn stream(camera_uuid: Uuid, axis: Axis, run: u64) {
+    let mut interval = tokio::time::interval(TICK);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+
+        let (position, arrived) = {
+            let mut motions = MOTIONS.lock().unwrap();
+            let Some(motion) = motions
+                .get_mut(&(camera_uuid, axis))
+                .filter(|motion| motion.run == run)
+            else {
+                break;
+            };
+
+            (motion.position, motion.velocity) = advance(
+                motion.position,
+                motion.velocity,
+                motion.target,
+                motion.limits,
+                TICK.as_secs_f32(),
+            );
+            let position = motion.position;
+            let arrived = position == motion.target && motion.velocity == 0.0;
+            if arrived {
+                motions.remove(&(camera_uuid, axis));
+            }
+            (position, arrived)
+        };
+
+        if let Err(error) = axis.apply(camera_uuid, position).await {
+            warn!("Stopping {axis:?} motion: {error:?}");
+            let mut motions = MOTIONS.lock().unwrap();
+            if motions
+                .get(&(camera_uuid, axis))
+                .is_some_and(|motion| motion.run == run)
+            {
+                motions.remove(&(camera_uuid, axis));
+            }
+            break;
+        }
+        if arrived {
+            break;
+        }
+    }
+}
+
+/// Position and velocity after `dt` seconds moving towards `target`.
+///
+/// Without an acceleration limit the axis moves at its maximum rate. With one, it speeds up
+/// and slows down at most that much, braking in time to stop on the target.
+fn advance(position: f32, velocity: f32, target: f32, limits: Limits, dt: f32) -> (f32, f32) {
+    let distance = target - position;
+    if distance.abs() < ARRIVED && velocity.abs() * dt < ARRIVED {
+        return (target, 0.0);
+    }
+
+    let direction = distance.signum();
+    let velocity = match limits.max_acceleration {
+        Some(acceleration) => {
+            let braking_speed = (2.0 * acceleration * distance.abs()).sqrt();
+            let desired = direction * limits.max_rate.min(braking_speed);
+            let change = acceleration * dt;
+            velocity + (desired - velocity).clamp(-change, change)
+        }
+        None => direction * limits.max_rate,
+    };
+
+    let next = position + velocity * dt;
+    // Do not overshoot: land on the target once this step would cross it.
+    if (target - next) * direction <= 0.0 {
+        return (target, 0.0);
+    }
+
+    (next, velocity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_until_arrived(mut position: f32, target: f32, limits: Limits) -> (usize, f32) {
+        let (mut velocity, mut peak) = (0.0f32, 0.0f32);
+        for tick in 1..10_000 {
+            (position, velocity) = advance(position, velocity, target, limits, 0.05);
+            peak = peak.max(velocity.abs());
+            if position == target && velocity == 0.0 {
+                return (tick, peak);
+            }
+        }
+        panic!("Never arrived at {target}, stuck at {position}");
+    }
+
+    #[test]
+    fn rate_limit_moves_at_constant_speed() {
+        let limits = Limits {
+            max_rate: 20.0,
+            max_acceleration: None,
+        };
+
+        assert_eq!(advance(0.0, 0.0, 50.0, limits, 0.05), (1.0, 20.0));
+        assert_eq!(advance(50.0, 0.0, 0.0, limits, 0.05), (49.0, -20.0));
+        assert_eq!(advance(49.5, 20.0, 50.0, limits, 0.05), (50.0, 0.0));
+        assert_eq!(run_until_arrived(0.0, 50.0, limits).0, 50);
+    }
+
+    #[test]
+    fn acceleration_limit_ramps_up_and_brakes_on_target() {
+        let limits = Limits {
+            max_rate: 30.0,
+            max_acceleration: Some(60.0),
+        };
+
+        let (position, velocity) = advance(0.0, 0.0, 45.0, limits, 0.05);
+        assert_eq!(velocity, 3.0);
+        assert!((position - 0.15).abs() < 1e-6);
+
+        let (ticks, peak) = run_until_arrived(-30.0, 15.0, limits);
+        assert!(peak <= 30.0);
+        // About 1 s at full speed, plus 0.5 s ramping up and 0.5 s braking.
+        assert!((35..=50).contains(&ticks), "{ticks}");
+    }
+
+    #[test]
+    fn preemption_reverses_through_the_acceleration_limit() {
+        let limits = Limits {
+            max_rate: 30.0,
+            max_acceleration: Some(60.0),
+        };
+
+        // Moving up at full speed when the target switches below.
+        let (_, velocity) = advance(10.0, 30.0, -20.0, limits, 0.05);
+        assert_eq!(velocity, 27.0);
+        run_until_arrived(10.0, -20.0, limits);
+    }
+}
@@ -0,0 +1,409 @@
+//! Optional per-camera tables turning the zoom and focus percentages into units a pilot
+//! understands: optical magnification, horizontal field of view and subject distance.
+//!
+//! Subject distance follows the focus calibration: the lens position is measured between
+//! the closest and furthest focus curves at the current zoom, 0% being the closest focus
+//! and 100% the furthest, and the table maps that position to meters.
+
+use anyhow::{Context, Result, anyhow};
+use mavlink::ardupilotmega::SERVO_OUTPUT_RAW_DATA;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{
+    CameraActuators, api,
+    manager::{self, MANAGER},
+};
+
+/// Focus input PWM the Lua script treats as the middle between both focus curves, and the
+/// PWM span covering them; see `calculate_focus` in `br4kcam.lua.template`.
+const SCRIPT_FOCUS_CENTER: f32 = 1500.0;
+const SCRIPT_FOCUS_SPAN: f32 = 400.0;
+
+#[instrument(level = "debug")]
+pub async fn get(camera_uuid: Uuid) -> Result<Option<api::OpticsMapping>> {
+    let manager = MANAGER.get().context("Not available")?.read().await;
+    let actuators = manager
+        .settings
+        .actuators
+        .get(&camera_uuid)
+        .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+    Ok(actuators.optics.clone())
+}
+
+/// Replace the camera's mapping tables; `None` removes them.
+#[instrument(level = "debug", skip(mapping))]
+pub async fn set(camera_uuid: Uuid, mapping: Option<api::OpticsMapping>) -> Result<()> {
+    if let Some(mapping) = &mapping {
+        validate(mapping)?;
+    }
+
+    {
+        let _apply = manager::CONFIG_APPLY.lock().await;
+        let mut manager = MANAGER.get().context("Not available")?.write().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get_mut(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+        actuators.optics = mapping;
+    }
+
+    manager::Manager::save_actuators_settings().await
+}
+
+/// Tables are interpolated both ways, so each column has to move in a single direction.
+pub(crate) fn validate(mapping: &api::OpticsMapping) -> Result<()> {
+    if mapping.zoom.len() == 1 || mapping.focus.len() == 1 {
+        return Err(anyhow!("A mapping table requires at least 2 points"));
+    }
+
+    for pair in mapping.zoom.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        if b.zoom <= a.zoom
+            || b.magnification <= a.magnification
+            || b.horizontal_fov >= a.horizontal_fov
+        {
+            return Err(anyhow!(
+                "Zoom points must increase in zoom and magnification while the field of view narrows"
+            ));
+        }
+    }
+    for point in &mapping.zoom {
+        if !(0.0..=100.0).contains(&point.zoom)
+            || point.magnification <= 0.0
+            || !(0.0..180.0).contains(&point.horizontal_fov)
+        {
+            return Err(anyhow!("Zoom point {point:?} is out of range"));
+        }
+    }
+
+    for pair in mapping.focus.windows(2) {
+        if pair[1].focus <= pair[0].focus || pair[1].distance <= pair[0].distance {
+            return Err(anyhow!(
+                "Focus points must increase in focus and subject distance"
+            ));
+        }
+    }
+    for point in &mapping.focus {
+        if !(0.0..=100.0).contains(&point.focus) || point.distance <= 0.0 {
+            return Err(anyhow!("Focus point {point:?} is out of range"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Fill in the calibrated units of `state` from a raw `SERVO_OUTPUT_RAW` sample.
+pub(crate) fn annotate_state(
+    actuators: &CameraActuators,
+    servo_output_raw: &SERVO_OUTPUT_RAW_DATA,
+    state: &mut api::ActuatorsState,
+) {
+    let Some(mapping) = &actuators.optics else {
+        return;
+    };
+
+    if let Some(zoom) = state.zoom {
+        state.zoom_magnification = interpolate(
+            mapping
+                .zoom
+                .iter()
+                .map(|point| (point.zoom, point.magnification)),
+            zoom,
+        );
+        state.horizontal_fov = interpolate(
+            mapping
+                .zoom
+                .iter()
+                .map(|point| (point.zoom, point.horizontal_fov)),
+            zoom,
+        );
+    }
+
+    let parameters = &actuators.parameters;
+    let lens = manager::get_output_raw_from_channel(servo_output_raw, parameters.script_channel);
+    let zoom = manager::get_output_raw_from_channel(servo_output_raw, parameters.zoom_channel);
+    if let (Some(lens), Some(zoom)) = (lens, zoom) {
+        state.focus_distance =
+            lens_position(actuators, zoom as f32, lens as f32).and_then(|position| {
+                interpolate(
+                    mapping
+                        .focus
+                        .iter()
+                        .map(|point| (point.focus, point.distance)),
+                    position,
+                )
+            });
+    }
+}
+
+/// Turn the calibrated units of a setpoint into percentages. Units take precedence over
+/// the percentages they stand for.
+pub(crate) fn resolve_setpoint(
+    actuators: &CameraActuators,
+    new_state: &api::ActuatorsState,
+) -> Result<api::ActuatorsState> {
+    let wants_units = new_state.zoom_magnification.is_some()
+        || new_state.horizontal_fov.is_some()
+        || new_state.focus_distance.is_some();
+    if !wants_units {
+        return Ok(*new_state);
+    }
+    let mapping = actuators
+        .optics
+        .as_ref()
+        .context("Camera has no zoom and focus mapping")?;
+
+    let mut zoom = new_state.zoom;
+    if let Some(magnification) = new_state.zoom_magnification {
+        zoom = Some(
+            interpolate(
+                mapping
+                    .zoom
+                    .iter()
+                    .map(|point| (point.magnification, point.zoom)),
+                magnification,
+            )
+            .context("Camera has no zoom mapping")?,
+        );
+    } else if let Some(horizontal_fov) = new_state.horizontal_fov {
+        // The field of view narrows as the zoom goes up; interpolate over increasing values.
+        zoom = Some(
+            interpolate(
+                mapping
+                    .zoom
+                    .iter()
+                    .rev()
+                    .map(|point| (point.horizontal_fov, point.zoom)),
+                horizontal_fov,
+            )
+            .context("Camera has no zoom mapping")?,
+        );
+    }
+
+    let mut focus = new_state.focus;
+    if let Some(distance) = new_state.focus_distance {
+        let position = interpolate(
+            mapping
+                .focus
+                .iter()
+                .map(|point| (point.distance, point.focus)),
+            distance,
+        )
+        .context("Camera has no focus mapping")?;
+        // The focus curves depend on where the zoom will be once both setpoints apply.
+        let zoom_percent = zoom
+            .or(actuators.state.zoom)
+            .context("Zoom position is unknown")?;
+        focus = Some(focus_percent(actuators, zoom_percent, position)?);
+    }
+
+    Ok(api::ActuatorsState {
+        focus,
+        zoom,
+        zoom_magnification: None,
+        horizontal_fov: None,
+        focus_distance: None,
+        ..*new_state
+    })
+}
+
+/// Lens position in percent between the closest (0) and furthest (100) focus curves.
+fn lens_position(actuators: &CameraActuators, zoom_pwm: f32, lens_pwm: f32) -> Option<f32> {
+    let (closest, furthest) = focus_curves_at(actuators, zoom_pwm)?;
+    if closest == furthest {
+        return None;
+    }
+
+    Some((100.0 * (lens_pwm - closest) / (furthest - closest)).clamp(0.0, 100.0))
+}
+
+/// Focus percentage that puts the lens at `position` between the focus curves.
+fn focus_percent(actuators: &CameraActuators, zoom_percent: f32, position: f32) -> Result<f32> {
+    let parameters = &actuators.parameters;
+    let zoom_pwm = pwm_from_percent(
+        zoom_percent,
+        parameters.zoom_channel_min,
+        parameters.zoom_channel_max,
+    );
+    let (closest, furthest) =
+        focus_curves_at(actuators, zoom_pwm).context("Focus calibration curves are empty")?;
+
+    let position = position / 100.0;
+    let focus_pwm = if parameters.enable_focus_and_zoom_correlation {
+        // The script places the lens at `gain * delta` of the way between both curves.
+        let gain = if parameters.focus_margin_gain > 0.0 {
+            parameters.focus_margin_gain
+        } else {
+            1.0
+        };
+        SCRIPT_FOCUS_CENTER + SCRIPT_FOCUS_SPAN * (position / gain - 0.5)
+    } else {
+        // Without the script, the focus output drives the lens directly.
+        closest + position * (furthest - closest)
+    };
+
+    let (min, max) = (
+        parameters.focus_channel_min as f32,
+        parameters.focus_channel_max as f32,
+    );
+    if max == min {
+        return Err(anyhow!("Focus channel range is empty"));
+    }
+
+    Ok((100.0 * (focus_pwm - min) / (max - min)).clamp(0.0, 100.0))
+}
+
+/// Closest and furthest focus PWMs at `zoom_pwm`, as the Lua script interpolates them.
+fn focus_curves_at(actuators: &CameraActuators, zoom_pwm: f32) -> Option<(f32, f32)> {
+    let curve = |points: &api::FocusZoomPoints| {
+        interpolate(
+            points
+                .0
+                .iter()
+                .map(|point| (point.zoom as f32, point.focus as f32)),
+            zoom_pwm,
+        )
+    };
+
+    Some((
+        curve(&actuators.closest_points)?,
+        curve(&actuators.furthest_points)?,
+    ))
+}
+
+fn pwm_from_percent(percent: f32, min: u16, max: u16) -> f32 {
+    min as f32 + (max as f32 - min as f32) * percent.clamp(0.0, 100.0) / 100.0
+}
+
+/// Piecewise linear interpolation over points sorted by `x`, clamped at both ends.
+fn interpolate(points: impl Iterator<Item = (f32, f32)>, x: f32) -> Option<f32> {
+    let points: Vec<(f32, f32)> = points.collect();
+    let (first, last) = (points.first()?, points.last()?);
+
+    if x <= first.0 {
+        return Some(first.1);
+    }
+    if x >= last.0 {
+        return Some(last.1);
+    }
+
+    points.windows(2).find_map(|pair| {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        (x0..=x1)
+            .contains(&x)
+            .then(|| y0 + (y1 - y0) * (x - x0) / (x1 - x0))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping() -> api::OpticsMapping {
+        api::OpticsMapping {
+            zoom: vec![
+                api::ZoomMappingPoint {
+                    zoom: 0.0,
+                    magnification: 1.0,
+                    horizontal_fov: 80.0,
+                },
+                api::ZoomMappingPoint {
+                    zoom: 100.0,
+                    magnification: 4.0,
+                    horizontal_fov: 20.0,
+                },
+            ],
+            focus: vec![
+                api::FocusDistancePoint {
+                    focus: 0.0,
+                    distance: 0.2,
+                },
+                api::FocusDistancePoint {
+                    focus: 100.0,
+                    distance: 10.2,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn interpolation_clamps_at_both_ends() {
+        let points = [(0.0, 10.0), (10.0, 20.0), (20.0, 40.0)];
+        assert_eq!(interpolate(points.into_iter(), -5.0), Some(10.0));
+        assert_eq!(interpolate(points.into_iter(), 15.0), Some(30.0));
+        assert_eq!(interpolate(points.into_iter(), 25.0), Some(40.0));
+        assert_eq!(interpolate(std::iter::empty(), 1.0), None);
+    }
+
+    #[test]
+    fn units_resolve_to_percentages() {
+        let actuators = CameraActuators {
+            optics: Some(mapping()),
+            ..Default::default()
+        };
+
+        let resolved = resolve_setpoint(
+            &actuators,
+            &api::ActuatorsState {
+                horizontal_fov: Some(50.0),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(resolved.zoom, Some(50.0));
+        assert_eq!(resolved.horizontal_fov, None);
+
+        let resolved = resolve_setpoint(
+            &actuators,
+            &api::ActuatorsState {
+                zoom: Some(10.0),
+                zoom_magnification: Some(2.5),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(resolved.zoom, Some(50.0));
+    }
+
+    #[test]
+    fn focus_distance_round_trips_through_the_lens_position() {
+        let actuators = CameraActuators {
+            parameters: crate::parameters::ActuatorsParameters {
+                enable_focus_and_zoom_correlation: false,
+                ..Default::default()
+            },
+            optics: Some(mapping()),
+            ..Default::default()
+        };
+        let zoom_pwm = pwm_from_percent(
+            40.0,
+            actuators.parameters.zoom_channel_min,
+            actuators.parameters.zoom_channel_max,
+        );
+
+        let focus = focus_percent(&actuators, 40.0, 25.0).unwrap();
+        let lens_pwm = pwm_from_percent(
+            focus,
+            actuators.parameters.focus_channel_min,
+            actuators.parameters.focus_channel_max,
+        );
+        let position = lens_position(&actuators, zoom_pwm, lens_pwm).unwrap();
+        assert!((position - 25.0).abs() < 0.5, "{position}");
+    }
+
+    #[test]
+    fn non_monotonic_tables_are_rejected() {
+        assert!(validate(&mapping()).is_ok());
+
+        let mut flipped = mapping();
+        flipped.zoom[1].horizontal_fov = 90.0;
+        assert!(validate(&flipped).is_err());
+
+        let mut single = mapping();
+        single.focus.pop();
+        assert!(validate(&single).is_err());
+    }
+}
@@ -218,6 +218,7 @@ pub struct ActuatorsParameters {
     pub tilt_mnt_type: api::MountType,
     pub tilt_mnt_pitch_min: i32,
     pub tilt_mnt_pitch_max: i32,
+    pub tilt_backend: api::TiltBackend,
 }
 
 impl Default for ActuatorsParameters {
@@ -258,6 +259,7 @@ impl Default for ActuatorsParameters {
             tilt_mnt_type: api::MountType::Servo,
             tilt_mnt_pitch_min: -70,
             tilt_mnt_pitch_max: 70,
+            tilt_backend: api::TiltBackend::Servo,
         }
     }
 }
@@ -307,6 +309,7 @@ impl From<ActuatorsParametersConfig> for ActuatorsParameters {
             tilt_mnt_pitch_max: value
                 .tilt_mnt_pitch_max
                 .unwrap_or(default.tilt_mnt_pitch_max),
+            tilt_backend: value.tilt_backend.unwrap_or(default.tilt_backend),
         }
     }
 }
@@ -0,0 +1,64 @@
+//! What hosts the autopilot: BlueOS, or a plain Linux system where something like
+//! mavlink-router already forwards the autopilot's MAVLink to us.
+//!
+//! BlueOS owns the MAVLink routing and the autopilot process, so there we ask it for an
+//! endpoint and for reboots. Standalone, the link is all we have.
+
+use ::mavlink::ardupilotmega::{COMMAND_LONG_DATA, MavCmd, MavComponent};
+use anyhow::Result;
+use once_cell::sync::OnceCell;
+use tracing::*;
+
+use crate::mavlink::MavlinkComponent;
+
+static PLATFORM: OnceCell<Platform> = OnceCell::new();
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    #[default]
+    BlueOs,
+    Standalone,
+}
+
+/// Pick the platform for the process lifetime. Later calls, e.g. from a soft restart, are
+/// no-ops.
+pub fn set_platform(platform: Platform) {
+    if PLATFORM.set(platform).is_err() && PLATFORM.get() != Some(&platform) {
+        warn!(
+            "Ignoring platform {platform:?}, already running on {:?}",
+            PLATFORM.get()
+        );
+    }
+}
+
+pub fn platform() -> Platform {
+    PLATFORM.get().copied().unwrap_or_default()
+}
+
+impl Platform {
+    /// Whether MAVLink reaches us through an endpoint this service has to create and keep.
+    pub fn manages_mavlink_endpoint(self) -> bool {
+        matches!(self, Platform::BlueOs)
+    }
+
+    #[instrument(level = "debug", skip(component))]
+    pub(crate) async fn reboot_autopilot(self, component: &MavlinkComponent) -> Result<()> {
+        match self {
+            // Works around https://github.com/bluerobotics/br4kcam-manager/issues/57: the
+            // autopilot BlueOS runs does not come back from a MAVLink reboot request.
+            Platform::BlueOs => blueos_client::reboot_autopilot().await,
+            Platform::Standalone => {
+                component
+                    .send_command(COMMAND_LONG_DATA {
+                        target_system: component.system_id(),
+                        target_component: MavComponent::MAV_COMP_ID_AUTOPILOT1 as u8,
+                        confirmation: 0,
+                        command: MavCmd::MAV_CMD_PREFLIGHT_REBOOT_SHUTDOWN,
+                        param1: 1.0, // Reboot the autopilot
+                        ..Default::default()
+                    })
+                    .await
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Named focus/zoom/tilt positions per camera, e.g. "wide inspection" or "look down".
+//!
+//! Recalling a preset goes through the regular setpoint path, so motion profiles apply. The
+//! first [`JOYSTICK_PRESETS`] presets are also written into the Lua script, where ArduSub's
+//! `script_1`..`script_4` joystick functions recall them without the manager in the loop;
+//! changes reach the joystick with the next script export, until which the script status
+//! reports it as outdated.
+
+use anyhow::{Context, Result, anyhow};
+use indexmap::IndexMap;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{
+    api,
+    manager::{self, MANAGER},
+};
+
+/// ArduSub has four script button functions.
+pub(crate) const JOYSTICK_PRESETS: usize = 4;
+
+#[instrument(level = "debug")]
+pub async fn get(camera_uuid: Uuid) -> Result<IndexMap<String, api::ActuatorsPreset>> {
+    let manager = MANAGER.get().context("Not available")?.read().await;
+    let actuators = manager
+        .settings
+        .actuators
+        .get(&camera_uuid)
+        .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+    Ok(actuators.presets.clone())
+}
+
+/// Create or replace the preset `save.name`, capturing the current position when no
+/// position is given.
+#[instrument(level = "debug", skip(save))]
+pub async fn save(camera_uuid: Uuid, save: &api::PresetSave) -> Result<()> {
+    let name = save.name.trim();
+    if name.is_empty() {
+        return Err(anyhow!("Preset name cannot be empty"));
+    }
+
+    {
+        let _apply = manager::CONFIG_APPLY.lock().await;
+        let mut manager = MANAGER.get().context("Not available")?.write().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get_mut(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+
+        let preset = save.preset.unwrap_or(api::ActuatorsPreset {
+            focus: actuators.state.focus,
+            zoom: actuators.state.zoom,
+            tilt_degrees: actuators.state.tilt_degrees,
+        });
+        validate(&preset)?;
+
+        actuators.presets.insert(name.to_string(), preset);
+    }
+
+    manager::Manager::save_actuators_settings().await
+}
+
+#[instrument(level = "debug")]
+pub async fn delete(camera_uuid: Uuid, name: &str) -> Result<()> {
+    {
+        let _apply = manager::CONFIG_APPLY.lock().await;
+        let mut manager = MANAGER.get().context("Not available")?.write().await;
+        let actuators = manager
+            .settings
+            .actuators
+            .get_mut(&camera_uuid)
+            .context(crate::ACTUATORS_NOT_CONFIGURED)?;
+        actuators
+            .presets
+            .shift_remove(name)
+            .with_context(|| format!("Unknown preset {name:?}"))?;
+    }
+
+    manager::Manager::save_actuators_settings().await
+}
+
+/// The setpoints recalling `name` sends.
+#[instrument(level = "debug")]
+pub(crate) async fn setpoints(camera_uuid: Uuid, name: &str) -> Result<api::ActuatorsState> {
+    let preset = get(camera_uuid)
+        .await?
+        .shift_remove(name)
+        .with_context(|| format!("Unknown preset {name:?}"))?;
+
+    Ok(api::ActuatorsState {
+        focus: preset.focus,
+        zoom: preset.zoom,
+        tilt_degrees: preset.tilt_degrees,
+        ..Default::default()
+    })
+}
+
+pub(crate) fn validate(preset: &api::ActuatorsPreset) -> Result<()> {
+    let axes = [preset.focus, preset.zoom, preset.tilt_degrees];
+    if axes.iter().all(Option::is_none) {
+        return Err(anyhow!("A preset needs at least one axis"));
+    }
+    if axes.into_iter().flatten().any(|value| !value.is_finite()) {
+        return Err(anyhow!("Preset positions must be finite numbers"));
+    }
+    for value in [preset.focus, preset.zoom].into_iter().flatten() {
+        if !(0.0..=100.0).contains(&value) {
+            return Err(anyhow!("Focus and zoom presets must be within 0-100 %"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Lua table of the joystick presets, in the script's order. Axes left out are `nil`.
+pub(crate) fn to_lua(presets: &IndexMap<String, api::ActuatorsPreset>) -> String {
+    let lua_value = |value: Option<f32>| value.map_or_else(|| "nil".to_string(), |v| v.to_string());
+    let entries: Vec<String> = presets
+        .iter()
+        .take(JOYSTICK_PRESETS)
+        .map(|(name, preset)| {
+            format!(
+                "    {{name = {name:?}, focus = {}, zoom = {}, tilt = {}}}",
+                lua_value(preset.focus),
+                lua_value(preset.zoom),
+                lua_value(preset.tilt_degrees),
+            )
+        })
+        .collect();
+
+    if entries.is_empty() {
+        return "{}".to_string();
+    }
+    format!("{{\n{}\n}}", entries.join(",\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_need_a_valid_axis() {
+        assert!(validate(&api::ActuatorsPreset::default()).is_err());
+        assert!(
+            validate(&api::ActuatorsPreset {
+                zoom: Some(120.0),
+                ..Default::default()
+            })
+            .is_err()
+        );
+        assert!(
+            validate(&api::ActuatorsPreset {
+                tilt_degrees: Some(f32::NAN),
+                ..Default::default()
+            })
+            .is_err()
+        );
+        assert!(
+            validate(&api::ActuatorsPreset {
+                tilt_degrees: Some(-90.0),
+                ..Default::default()
+            })
+            .is_ok()
+        );
+    }
+
+    #[test]
+    fn only_the_first_presets_reach_the_script() {
+        let presets: IndexMap<String, api::ActuatorsPreset> = (0..6)
+            .map(|index| {
+                (
+                    format!("preset {index}"),
+                    api::ActuatorsPreset {
+                        zoom: Some(index as f32 * 10.0),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+
+        let lua = to_lua(&presets);
+
+        assert!(lua.contains("{name = \"preset 0\", focus = nil, zoom = 0, tilt = nil}"));
+        assert!(lua.contains("\"preset 3\""));
+        assert!(!lua.contains("\"preset 4\""));
+        assert_eq!(to_lua(&IndexMap::new()), "{}");
+    }
+}
@@ -0,0 +1,313 @@
+//! Backup and restore of the whole settings document.
+//!
+//! An import replaces the settings of every camera. Every camera is validated before any
+//! is touched; then only what differs from the current configuration reaches the
+//! autopilot, which reboots at most once for the whole import. Cameras the import leaves
+//! out are reset and forgotten. Stream preferences are taken over as they are.
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use settings::{CameraKey, CameraStreamSettings};
+use std::sync::Mutex;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{
+    CameraActuators, api,
+    manager::{self, MANAGER},
+};
+
+/// A validated import, with the cameras it would affect.
+#[derive(Debug)]
+pub struct ImportPlan {
+    actuators: IndexMap<Uuid, CameraActuators>,
+    streams: IndexMap<CameraKey, CameraStreamSettings>,
+    pub summary: api::SettingsImportSummary,
+}
+
+/// The persisted settings document.
+#[instrument(level = "debug")]
+pub async fn export() -> Result<serde_json::Value> {
+    serde_json::to_value(settings::export().await?).context("Failed to serialize settings")
+}
+
+/// Validate and migrate `document`, and compare it with the current settings.
+#[instrument(level = "debug", skip(document))]
+pub async fn plan(document: serde_json::Value) -> Result<ImportPlan> {
+    let document = settings::parse_document(document)?;
+    let actuators: IndexMap<Uuid, CameraActuators> = document
+        .actuators
+        .iter()
+        .map(|(key, actuators_settings)| {
+            (
+                settings::camera_uuid(key),
+                CameraActuators::from(actuators_settings),
+            )
+        })
+        .collect();
+    validate(&actuators)?;
+
+    let current: IndexMap<Uuid, CameraActuators> = {
+        let manager = MANAGER.get().context("Not available")?.read().await;
+        manager
+            .settings
+            .actuators
+            .iter()
+            .map(|(camera_uuid, actuators)| (camera_uuid, actuators.clone()))
+            .collect()
+    };
+    let mut summary = summarize(&current, &actuators);
+
+    let streams = document.streams;
+    summary.streams_changed = {
+        let manager = settings::MANAGER
+            .get()
+            .context("Not available")?
+            .read()
+            .await;
+        *manager.settings.get_streams() != streams
+    };
+
+    Ok(ImportPlan {
+        actuators,
+        streams,
+        summary,
+    })
+}
+
+/// Apply `plan`: push what changed for every camera, then reboot the autopilot once if
+/// anything needs it.
+#[instrument(level = "debug", skip(plan), fields(summary = ?plan.summary))]
+pub async fn apply(plan: ImportPlan) -> Result<api::SettingsImportSummary> {
+    let ImportPlan {
+        mut actuators,
+        streams,
+        mut summary,
+    } = plan;
+
+    crate::vehicle_profiles::ensure_known()?;
+
+    let unchanged: Vec<(Uuid, CameraActuators)> = summary
+        .unchanged
+        .iter()
+        .filter_map(|camera_uuid| Some((*camera_uuid, actuators.shift_remove(camera_uuid)?)))
+        .collect();
+
+    // Cameras whose configuration is finished only after the reboot.
+    let awaiting_reboot: Mutex<Vec<(Uuid, Option<api::ActuatorsParametersConfig>)>> =
+        Mutex::new(Vec::new());
+
+    manager::reboot_outside_apply(
+        Box::pin(async {
+            let mut needs_reboot = false;
+
+            for camera_uuid in summary.added.iter().chain(&summary.changed) {
+                let imported = actuators
+                    .shift_remove(camera_uuid)
+                    .context("Imported camera vanished from the plan")?;
+                let parameters = api::ActuatorsConfig::from(&imported).parameters;
+
+                let reboot = manager::Manager::import_config(camera_uuid, imported)
+                    .await
+                    .with_context(|| {
+                        format!("Failed to apply the imported settings of {camera_uuid}")
+                    })?;
+                if reboot {
+                    awaiting_reboot
+                        .lock()
+                        .unwrap()
+                        .push((*camera_uuid, parameters));
+                }
+                needs_reboot |= reboot;
+            }
+
+            for camera_uuid in &summary.removed {
+                let reboot = manager::Manager::reset_config(camera_uuid)
+                    .await
+                    .with_context(|| format!("Failed to reset {camera_uuid}"))?;
+                if reboot {
+                    let defaults = api::ActuatorsConfig::from(&CameraActuators::default());
+                    awaiting_reboot
+                        .lock()
+                        .unwrap()
+                        .push((*camera_uuid, defaults.parameters));
+                }
+                needs_reboot |= reboot;
+            }
+
+            Ok(needs_reboot)
+        }),
+        Box::pin(async {
+            let awaiting_reboot = std::mem::take(&mut *awaiting_reboot.lock().unwrap());
+            for (camera_uuid, parameters) in awaiting_reboot {
+                manager::Manager::finalize_config_after_reboot(&camera_uuid, parameters.as_ref())
+                    .await
+                    .with_context(|| format!("Failed to finish applying {camera_uuid}"))?;
+            }
+            Ok(())
+        }),
+    )
+    .await?;
+
+    keep_unchanged(unchanged).await?;
+    manager::Manager::forget_configs(&summary.removed).await?;
+    settings::import_stream_settings(streams).await?;
+
+    info!(
+        added = summary.added.len(),
+        changed = summary.changed.len(),
+        removed = summary.removed.len(),
+        "Imported settings"
+    );
+
+    summary.applied = true;
+    Ok(summary)
+}
+
+/// Fail on the first camera the autopilot could not be configured with.
+fn validate(imported: &IndexMap<Uuid, CameraActuators>) -> Result<()> {
+    for (camera_uuid, actuators) in imported {
+        validate_camera(actuators)
+            .with_context(|| format!("Invalid settings for camera {camera_uuid}"))?;
+    }
+
+    Ok(())
+}
+
+fn validate_camera(actuators: &CameraActuators) -> Result<()> {
+    for (name, preset) in &actuators.presets {
+        crate::presets::validate(preset).with_context(|| format!("Invalid preset {name:?}"))?;
+    }
+    if let Some(optics) = &actuators.optics {
+        crate::optics::validate(optics).context("Invalid optics mapping")?;
+    }
+
+    manager::check_script(actuators)
+}
+
+/// Whether importing `imported` over `current` changes the camera's persisted
+/// configuration. The live state, optics and motion profiles never need the autopilot.
+fn configuration_differs(current: &CameraActuators, imported: &CameraActuators) -> bool {
+    current.parameters != imported.parameters
+        || current.closest_points != imported.closest_points
+        || current.furthest_points != imported.furthest_points
+        || current.samples != imported.samples
+        || current.calibration_sets != imported.calibration_sets
+        || current.presets != imported.presets
+}
+
+/// Store the optics and motion profiles of cameras whose configuration stays the same.
+async fn keep_unchanged(unchanged: Vec<(Uuid, CameraActuators)>) -> Result<()> {
+    let _apply = manager::CONFIG_APPLY.lock().await;
+    let updated = {
+        let mut manager = MANAGER.get().context("Not available")?.write().await;
+        let mut updated = false;
+        for (camera_uuid, imported) in unchanged {
+            let Some(actuators) = manager.settings.actuators.get_mut(&camera_uuid) else {
+                continue;
+            };
+            if actuators.optics != imported.optics
+                || actuators.motion_profiles != imported.motion_profiles
+            {
+                actuators.optics = imported.optics;
+                actuators.motion_profiles = imported.motion_profiles;
+                updated = true;
+            }
+        }
+        updated
+    };
+
+    if updated {
+        manager::Manager::save_actuators_settings().await?;
+    }
+
+    Ok(())
+}
+
+fn summarize(
+    current: &IndexMap<Uuid, CameraActuators>,
+    imported: &IndexMap<Uuid, CameraActuators>,
+) -> api::SettingsImportSummary {
+    let mut summary = api::SettingsImportSummary::default();
+
+    for (camera_uuid, actuators) in imported {
+        match current.get(camera_uuid) {
+            None => summary.added.push(*camera_uuid),
+            Some(existing) if configuration_differs(existing, actuators) => {
+                summary.changed.push(*camera_uuid)
+            }
+            Some(_) => summary.unchanged.push(*camera_uuid),
+        }
+    }
+    summary.removed = current
+        .keys()
+        .filter(|camera_uuid| !imported.contains_key(*camera_uuid))
+        .copied()
+        .collect();
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cameras_are_sorted_by_what_the_import_does_to_them() {
+        let [kept, edited, dropped, new, moved] = [1, 2, 3, 4, 5].map(Uuid::from_u128);
+        let mut edited_actuators = CameraActuators::default();
+        edited_actuators.presets.insert(
+            "dive".to_string(),
+            api::ActuatorsPreset {
+                zoom: Some(42.0),
+                ..Default::default()
+            },
+        );
+        // Only the live state differs: nothing to import.
+        let mut moved_actuators = CameraActuators::default();
+        moved_actuators.state.zoom = Some(42.0);
+
+        let current: IndexMap<Uuid, CameraActuators> = [kept, edited, dropped, moved]
+            .into_iter()
+            .map(|uuid| (uuid, CameraActuators::default()))
+            .collect();
+        let imported: IndexMap<Uuid, CameraActuators> = [
+            (kept, CameraActuators::default()),
+            (edited, edited_actuators),
+            (new, CameraActuators::default()),
+            (moved, moved_actuators),
+        ]
+        .into_iter()
+        .collect();
+
+        let summary = summarize(&current, &imported);
+
+        assert_eq!(summary.added, vec![new]);
+        assert_eq!(summary.changed, vec![edited]);
+        assert_eq!(summary.removed, vec![dropped]);
+        assert_eq!(summary.unchanged, vec![kept, moved]);
+        assert!(!summary.applied);
+    }
+
+    #[test]
+    fn one_invalid_camera_rejects_the_whole_import() {
+        let [valid, invalid] = [1, 2].map(Uuid::from_u128);
+        let mut invalid_actuators = CameraActuators::default();
+        invalid_actuators
+            .presets
+            .insert("empty".to_string(), api::ActuatorsPreset::default());
+
+        let mut imported: IndexMap<Uuid, CameraActuators> = [
+            (valid, CameraActuators::default()),
+            (invalid, invalid_actuators),
+        ]
+        .into_iter()
+        .collect();
+
+        let error = validate(&imported).unwrap_err();
+        assert!(format!("{error:#}").contains(&invalid.to_string()));
+
+        imported.shift_remove(&invalid);
+        assert!(validate(&imported).is_ok());
+    }
+}
@@ -6,7 +6,19 @@ impl From<&settings::CameraActuatorsSettings> for CameraActuators {
             parameters: (&value.parameters).into(),
             closest_points: (&value.calibration.closest_points).into(),
             furthest_points: (&value.calibration.furthest_points).into(),
+            samples: api::CalibrationSamples {
+                closest: (&value.calibration.samples.closest).into(),
+                furthest: (&value.calibration.samples.furthest).into(),
+            },
+            calibration_sets: (&value.calibration).into(),
             state: (&value.state).into(),
+            optics: value.optics.as_ref().map(api::OpticsMapping::from),
+            motion_profiles: (&value.motion_profiles).into(),
+            presets: value
+                .presets
+                .iter()
+                .map(|(name, preset)| (name.clone(), preset.into()))
+                .collect(),
         }
     }
 }
@@ -17,8 +29,100 @@ impl From<&CameraActuators> for settings::CameraActuatorsSettings {
             calibration: settings::Calibration {
                 closest_points: (&value.closest_points).into(),
                 furthest_points: (&value.furthest_points).into(),
+                samples: settings::CalibrationSamples {
+                    closest: (&value.samples.closest).into(),
+                    furthest: (&value.samples.furthest).into(),
+                },
+                sets: value
+                    .calibration_sets
+                    .sets
+                    .iter()
+                    .map(|(name, set)| (name.clone(), set.into()))
+                    .collect(),
+                active_set: Some(value.calibration_sets.active.clone()),
+                auto_switch: value
+                    .calibration_sets
+                    .auto_switch
+                    .as_ref()
+                    .map(settings::CalibrationAutoSwitch::from),
             },
             state: (&value.state).into(),
+            optics: value.optics.as_ref().map(settings::OpticsMapping::from),
+            motion_profiles: (&value.motion_profiles).into(),
+            presets: value
+                .presets
+                .iter()
+                .map(|(name, preset)| (name.clone(), preset.into()))
+                .collect(),
+        }
+    }
+}
+
+/// Settings written before calibration sets existed only carry the active curves: they
+/// become the sole set. The persisted curves always win over a stale copy in `sets`.
+impl From<&settings::Calibration> for api::CalibrationSets {
+    fn from(value: &settings::Calibration) -> Self {
+        let active = value
+            .active_set
+            .clone()
+            .unwrap_or_else(|| crate::DEFAULT_CALIBRATION_SET.to_string());
+
+        let mut sets: indexmap::IndexMap<String, api::CalibrationSet> = value
+            .sets
+            .iter()
+            .map(|(name, set)| (name.clone(), set.into()))
+            .collect();
+        sets.insert(
+            active.clone(),
+            api::CalibrationSet {
+                closest_points: (&value.closest_points).into(),
+                furthest_points: (&value.furthest_points).into(),
+            },
+        );
+
+        Self {
+            active,
+            sets,
+            auto_switch: value
+                .auto_switch
+                .as_ref()
+                .map(api::CalibrationAutoSwitch::from),
+        }
+    }
+}
+
+impl From<&settings::CalibrationSet> for api::CalibrationSet {
+    fn from(value: &settings::CalibrationSet) -> Self {
+        Self {
+            closest_points: (&value.closest_points).into(),
+            furthest_points: (&value.furthest_points).into(),
+        }
+    }
+}
+impl From<&api::CalibrationSet> for settings::CalibrationSet {
+    fn from(value: &api::CalibrationSet) -> Self {
+        Self {
+            closest_points: (&value.closest_points).into(),
+            furthest_points: (&value.furthest_points).into(),
+        }
+    }
+}
+
+impl From<&settings::CalibrationAutoSwitch> for api::CalibrationAutoSwitch {
+    fn from(value: &settings::CalibrationAutoSwitch) -> Self {
+        Self {
+            air_set: value.air_set.clone(),
+            water_set: value.water_set.clone(),
+            depth_threshold: value.depth_threshold,
+        }
+    }
+}
+impl From<&api::CalibrationAutoSwitch> for settings::CalibrationAutoSwitch {
+    fn from(value: &api::CalibrationAutoSwitch) -> Self {
+        Self {
+            air_set: value.air_set.clone(),
+            water_set: value.water_set.clone(),
+            depth_threshold: value.depth_threshold,
         }
     }
 }
@@ -50,6 +154,7 @@ impl From<&settings::ActuatorsParameters> for parameters::ActuatorsParameters {
             tilt_mnt_type: (&value.tilt_mnt_type).into(),
             tilt_mnt_pitch_min: value.tilt_mnt_pitch_min,
             tilt_mnt_pitch_max: value.tilt_mnt_pitch_max,
+            tilt_backend: (&value.tilt_backend).into(),
         }
     }
 }
@@ -80,6 +185,7 @@ impl From<&parameters::ActuatorsParameters> for settings::ActuatorsParameters {
             tilt_mnt_type: (&value.tilt_mnt_type).into(),
             tilt_mnt_pitch_min: value.tilt_mnt_pitch_min,
             tilt_mnt_pitch_max: value.tilt_mnt_pitch_max,
+            tilt_backend: (&value.tilt_backend).into(),
         }
     }
 }
@@ -240,6 +346,23 @@ impl From<&api::MountType> for settings::MountType {
     }
 }
 
+impl From<&settings::TiltBackend> for api::TiltBackend {
+    fn from(value: &settings::TiltBackend) -> Self {
+        match value {
+            settings::TiltBackend::Servo => Self::Servo,
+            settings::TiltBackend::GimbalManager => Self::GimbalManager,
+        }
+    }
+}
+impl From<&api::TiltBackend> for settings::TiltBackend {
+    fn from(value: &api::TiltBackend) -> Self {
+        match value {
+            api::TiltBackend::Servo => Self::Servo,
+            api::TiltBackend::GimbalManager => Self::GimbalManager,
+        }
+    }
+}
+
 impl From<&settings::FocusZoomPoints> for api::FocusZoomPoints {
     fn from(value: &settings::FocusZoomPoints) -> Self {
         Self(value.iter().map(|v| v.into()).collect())
@@ -274,6 +397,8 @@ impl From<&settings::ActuatorsState> for api::ActuatorsState {
             focus: value.focus,
             zoom: value.zoom,
             tilt: value.tilt,
+            // The calibrated units are only known once measured again.
+            ..Default::default()
         }
     }
 }
@@ -286,3 +411,90 @@ impl From<&api::ActuatorsState> for settings::ActuatorsState {
         }
     }
 }
+
+impl From<&settings::OpticsMapping> for api::OpticsMapping {
+    fn from(value: &settings::OpticsMapping) -> Self {
+        Self {
+            zoom: value
+                .zoom
+                .iter()
+                .map(|point| api::ZoomMappingPoint {
+                    zoom: point.zoom,
+                    magnification: point.magnification,
+                    horizontal_fov: point.horizontal_fov,
+                })
+                .collect(),
+            focus: value
+                .focus
+                .iter()
+                .map(|point| api::FocusDistancePoint {
+                    focus: point.focus,
+                    distance: point.distance,
+                })
+                .collect(),
+        }
+    }
+}
+impl From<&api::OpticsMapping> for settings::OpticsMapping {
+    fn from(value: &api::OpticsMapping) -> Self {
+        Self {
+            zoom: value
+                .zoom
+                .iter()
+                .map(|point| settings::ZoomMappingPoint {
+                    zoom: point.zoom,
+                    magnification: point.magnification,
+                    horizontal_fov: point.horizontal_fov,
+                })
+                .collect(),
+            focus: value
+                .focus
+                .iter()
+                .map(|point| settings::FocusDistancePoint {
+                    focus: point.focus,
+                    distance: point.distance,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&settings::MotionProfiles> for api::MotionProfiles {
+    fn from(value: &settings::MotionProfiles) -> Self {
+        Self {
+            focus_max_rate: value.focus_max_rate,
+            zoom_max_rate: value.zoom_max_rate,
+            tilt_max_rate: value.tilt_max_rate,
+            tilt_max_acceleration: value.tilt_max_acceleration,
+        }
+    }
+}
+impl From<&api::MotionProfiles> for settings::MotionProfiles {
+    fn from(value: &api::MotionProfiles) -> Self {
+        Self {
+            focus_max_rate: value.focus_max_rate,
+            zoom_max_rate: value.zoom_max_rate,
+            tilt_max_rate: value.tilt_max_rate,
+            tilt_max_acceleration: value.tilt_max_acceleration,
+        }
+    }
+}
+
+impl From<&settings::ActuatorsPreset> for api::ActuatorsPreset {
+    fn from(value: &settings::ActuatorsPreset) -> Self {
+        Self {
+            focus: value.focus,
+            zoom: value.zoom,
+            tilt_degrees: value.tilt_degrees,
+        }
+    }
+}
+impl From<&api::ActuatorsPreset> for settings::ActuatorsPreset {
+    fn from(value: &api::ActuatorsPreset) -> Self {
+        Self {
+            focus: value.focus,
+            zoom: value.zoom,
+            tilt_degrees: value.tilt_degrees,
+        }
+    }
+}
@@ -0,0 +1,428 @@
+//! Publishes the focus, zoom and tilt of every configured camera over MAVLink, so they show
+//! up in the GCS and in telemetry logs.
+//!
+//! Every camera gets `NAMED_VALUE_FLOAT`s from the manager's component (`CAM1_FOCUS`,
+//! `CAM1_ZOOM`, `CAM1_TILT`, ...), which Cockpit turns into data-lake variables; see
+//! [`DataLakeValue`]. The number is the camera's own, given out as cameras are first seen,
+//! not the autopilot camera instance. Besides the actuators, every discovered camera
+//! reports whether it is reachable (`CAM1_LINK`), whether its video stream works
+//! (`CAM1_VIDEO`), both 1 or 0, and the position of the active calibration set, from 1
+//! (`CAM1_PROF`), once a second whatever the actuators state rate. Cameras announced by
+//! [`crate::camera_protocol`] also send `CAMERA_SETTINGS` and, when the tilt is known,
+//! `GIMBAL_DEVICE_ATTITUDE_STATUS` from their own component.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use indexmap::IndexMap;
+use mavlink::ardupilotmega::{
+    GIMBAL_DEVICE_ATTITUDE_STATUS_DATA, GimbalDeviceErrorFlags, GimbalDeviceFlags, MavMessage,
+    NAMED_VALUE_FLOAT_DATA,
+};
+use once_cell::sync::Lazy;
+use tracing::*;
+use uuid::Uuid;
+
+use crate::{api, camera_protocol, manager::MANAGER, message_loop};
+
+const DEFAULT_RATE_HZ: f32 = 1.0;
+const DISABLED_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const STATUS_INTERVAL: Duration = Duration::from_secs(1);
+/// `NAMED_VALUE_FLOAT` names are at most 10 characters, so `CAM9_FOCUS` is the last one.
+const MAX_DATA_LAKE_CAMERAS: usize = 9;
+
+/// `f32` bits of the publishing rate in Hz.
+static RATE_HZ: AtomicU32 = AtomicU32::new(DEFAULT_RATE_HZ.to_bits());
+static CAMERA_STATUS: Lazy<Mutex<HashMap<Uuid, CameraStatus>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static DATA_LAKE_NUMBERS: Lazy<Mutex<IndexMap<Uuid, usize>>> =
+    Lazy::new(|| Mutex::new(IndexMap::new()));
+
+/// A value published for every camera as a `NAMED_VALUE_FLOAT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataLakeValue {
+    Focus,
+    Zoom,
+    Tilt,
+    Link,
+    Video,
+    Profile,
+}
+
+impl DataLakeValue {
+    pub const ALL: [DataLakeValue; 6] = [
+        DataLakeValue::Focus,
+        DataLakeValue::Zoom,
+        DataLakeValue::Tilt,
+        DataLakeValue::Link,
+        DataLakeValue::Video,
+        DataLakeValue::Profile,
+    ];
+
+    fn suffix(self) -> &'static str {
+        match self {
+            DataLakeValue::Focus => "FOCUS",
+            DataLakeValue::Zoom => "ZOOM",
+            DataLakeValue::Tilt => "TILT",
+            DataLakeValue::Link => "LINK",
+            DataLakeValue::Video => "VIDEO",
+            DataLakeValue::Profile => "PROF",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            DataLakeValue::Focus => "Focus %",
+            DataLakeValue::Zoom => "Zoom %",
+            DataLakeValue::Tilt => "Tilt %",
+            DataLakeValue::Link => "Online",
+            DataLakeValue::Video => "Video healthy",
+            DataLakeValue::Profile => "Calibration set",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            DataLakeValue::Focus => "Focus position, 0-100 %",
+            DataLakeValue::Zoom => "Zoom position, 0-100 %",
+            DataLakeValue::Tilt => "Tilt position, 0-100 %",
+            DataLakeValue::Link => "1 while the camera can be reached, 0 otherwise",
+            DataLakeValue::Video => "1 while the video stream works, 0 while it is broken",
+            DataLakeValue::Profile => "Position of the active calibration set, from 1",
+        }
+    }
+}
+
+/// The `NAMED_VALUE_FLOAT` name carrying `value` for `camera_uuid`, e.g. `CAM1_ZOOM`, or
+/// `None` when every name is taken by other cameras.
+pub fn data_lake_name(camera_uuid: Uuid, value: DataLakeValue) -> Option<String> {
+    data_lake_number(camera_uuid).map(|number| format!("CAM{number}_{}", value.suffix()))
+}
+
+/// Number of `camera_uuid` in its data-lake names, kept until the manager restarts.
+fn data_lake_number(camera_uuid: Uuid) -> Option<usize> {
+    let mut numbers = DATA_LAKE_NUMBERS.lock().unwrap();
+    if let Some(number) = numbers.get(&camera_uuid) {
+        return Some(*number);
+    }
+    if numbers.len() >= MAX_DATA_LAKE_CAMERAS {
+        return None;
+    }
+
+    let number = numbers.len() + 1;
+    numbers.insert(camera_uuid, number);
+    Some(number)
+}
+
+/// What the manager knows of a camera besides its actuators. Unknown values are not
+/// published.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct CameraStatus {
+    online: Option<bool>,
+    video_healthy: Option<bool>,
+}
+
+/// Set how many times per second the actuators state is published; 0 disables it.
+pub fn set_actuators_state_rate(rate_hz: f32) {
+    let rate_hz = if rate_hz.is_finite() {
+        rate_hz.max(0.0)
+    } else {
+        0.0
+    };
+    RATE_HZ.store(rate_hz.to_bits(), Ordering::SeqCst);
+}
+
+/// Record whether `camera_uuid` is reachable, or `None` while that is not known yet.
+pub fn set_camera_online(camera_uuid: Uuid, online: Option<bool>) {
+    CAMERA_STATUS
+        .lock()
+        .unwrap()
+        .entry(camera_uuid)
+        .or_default()
+        .online = online;
+}
+
+/// Record whether the video stream of `camera_uuid` works.
+pub fn set_camera_video_healthy(camera_uuid: Uuid, healthy: bool) {
+    CAMERA_STATUS
+        .lock()
+        .unwrap()
+        .entry(camera_uuid)
+        .or_default()
+        .video_healthy = Some(healthy);
+}
+
+fn rate_hz() -> f32 {
+    f32::from_bits(RATE_HZ.load(Ordering::SeqCst))
+}
+
+/// Spawn the publishers once.
+pub fn start() {
+    message_loop::spawn_once("state publisher", state_publisher());
+    message_loop::spawn_once("status publisher", status_publisher());
+}
+
+#[instrument(level = "debug", skip_all)]
+async fn state_publisher() {
+    // Publishing keeps the SERVO stream alive, so the published state is never stale.
+    let mut interested = false;
+
+    loop {
+        let rate_hz = rate_hz();
+        if rate_hz <= 0.0 {
+            if std::mem::take(&mut interested) {
+                crate::remove_actuators_state_interest();
+            }
+            tokio::time::sleep(DISABLED_POLL_INTERVAL).await;
+            continue;
+        }
+        if !std::mem::replace(&mut interested, true) {
+            crate::add_actuators_state_interest();
+        }
+
+        tokio::time::sleep(Duration::from_secs_f32(1.0 / rate_hz)).await;
+        publish_actuators().await;
+    }
+}
+
+#[instrument(level = "debug", skip_all)]
+async fn status_publisher() {
+    let mut interval = tokio::time::interval(STATUS_INTERVAL);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        interval.tick().await;
+        publish_status().await;
+    }
+}
+
+async fn publish_actuators() {
+    let Ok(mavlink) = crate::mavlink::component() else {
+        return;
+    };
+    let Some(manager) = MANAGER.get() else {
+        return;
+    };
+
+    let cameras: Vec<_> = manager
+        .read()
+        .await
+        .settings
+        .actuators
+        .iter()
+        .map(|(camera_uuid, actuators)| {
+            (camera_uuid, actuators.parameters.clone(), actuators.state)
+        })
+        .collect();
+    let announced = camera_protocol::targets(0);
+
+    for (camera_uuid, parameters, state) in cameras {
+        if let Some(number) = data_lake_number(camera_uuid) {
+            for message in actuator_values(number, &state) {
+                if let Err(error) = mavlink.send_as(mavlink.component_id(), message).await {
+                    debug!("Failed publishing actuators state: {error:?}");
+                    return;
+                }
+            }
+        }
+
+        let Some(component_id) = announced
+            .iter()
+            .find(|(announced_uuid, _)| *announced_uuid == camera_uuid)
+            .map(|(_, component_id)| *component_id)
+        else {
+            continue;
+        };
+
+        let mut messages = Vec::new();
+        if state.focus.is_some() || state.zoom.is_some() {
+            messages.push(MavMessage::CAMERA_SETTINGS(
+                camera_protocol::camera_settings(Some(state)),
+            ));
+        }
+        // With a gimbal manager the autopilot already reports the mount attitude.
+        if let Some(tilt_degrees) = state.tilt_degrees
+            && parameters.tilt_backend == api::TiltBackend::Servo
+        {
+            messages.push(MavMessage::GIMBAL_DEVICE_ATTITUDE_STATUS(gimbal_attitude(
+                tilt_degrees,
+            )));
+        }
+        for message in messages {
+            if let Err(error) = mavlink.send_as(component_id, message).await {
+                debug!(%camera_uuid, "Failed publishing actuators state: {error:?}");
+            }
+        }
+    }
+}
+
+/// Publish the status of every discovered camera, configured or not.
+async fn publish_status() {
+    let Ok(mavlink) = crate::mavlink::component() else {
+        return;
+    };
+
+    let profiles: HashMap<Uuid, usize> = match MANAGER.get() {
+        Some(manager) => manager
+            .read()
+            .await
+            .settings
+            .actuators
+            .iter()
+            .filter_map(|(camera_uuid, actuators)| {
+                let sets = &actuators.calibration_sets;
+                Some((camera_uuid, sets.sets.get_index_of(&sets.active)?))
+            })
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    for camera_uuid in cameras::cameras().await.into_keys() {
+        let Some(number) = data_lake_number(camera_uuid) else {
+            continue;
+        };
+        let status = CAMERA_STATUS
+            .lock()
+            .unwrap()
+            .get(&camera_uuid)
+            .copied()
+            .unwrap_or_default();
+
+        for message in status_values(number, status, profiles.get(&camera_uuid).copied()) {
+            if let Err(error) = mavlink.send_as(mavlink.component_id(), message).await {
+                debug!("Failed publishing camera status: {error:?}");
+                return;
+            }
+        }
+    }
+}
+
+fn actuator_values(number: usize, state: &api::ActuatorsState) -> Vec<MavMessage> {
+    named_values(
+        number,
+        [
+            (DataLakeValue::Focus, state.focus),
+            (DataLakeValue::Zoom, state.zoom),
+            (DataLakeValue::Tilt, state.tilt),
+        ],
+    )
+}
+
+fn status_values(number: usize, status: CameraStatus, profile: Option<usize>) -> Vec<MavMessage> {
+    let flag = |value: bool| if value { 1.0 } else { 0.0 };
+
+    named_values(
+        number,
+        [
+            (DataLakeValue::Link, status.online.map(flag)),
+            (DataLakeValue::Video, status.video_healthy.map(flag)),
+            (
+                DataLakeValue::Profile,
+                profile.map(|index| (index + 1) as f32),
+            ),
+        ],
+    )
+}
+
+/// Unknown values are left out.
+fn named_values(
+    number: usize,
+    values: impl IntoIterator<Item = (DataLakeValue, Option<f32>)>,
+) -> Vec<MavMessage> {
+    values
+        .into_iter()
+        .filter_map(|(kind, value)| {
+            Some(MavMessage::NAMED_VALUE_FLOAT(NAMED_VALUE_FLOAT_DATA {
+                time_boot_ms: camera_protocol::time_boot_ms(),
+                name: format!("CAM{number}_{}", kind.suffix()).as_str().into(),
+                value: value?,
+            }))
+        })
+        .collect()
+}
+
+/// Attitude of a tilt-only mount, pitched `pitch` degrees in the vehicle frame.
+fn gimbal_attitude(pitch: f32) -> GIMBAL_DEVICE_ATTITUDE_STATUS_DATA {
+    let (sin, cos) = (pitch.to_radians() / 2.0).sin_cos();
+
+    GIMBAL_DEVICE_ATTITUDE_STATUS_DATA {
+        time_boot_ms: camera_protocol::time_boot_ms(),
+        q: [cos, 0.0, sin, 0.0],
+        angular_velocity_x: f32::NAN,
+        angular_velocity_y: f32::NAN,
+        angular_velocity_z: f32::NAN,
+        flags: GimbalDeviceFlags::empty(),
+        failure_flags: GimbalDeviceErrorFlags::empty(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(messages: Vec<MavMessage>) -> Vec<(String, f32)> {
+        messages
+            .into_iter()
+            .map(|message| match message {
+                MavMessage::NAMED_VALUE_FLOAT(data) => {
+                    (data.name.to_str().unwrap().to_string(), data.value)
+                }
+                message => panic!("Unexpected {message:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn named_values_skip_unknown_values() {
+        let state = api::ActuatorsState {
+            focus: Some(42.0),
+            zoom: None,
+            tilt: Some(10.0),
+            ..Default::default()
+        };
+        let status = CameraStatus {
+            online: Some(false),
+            video_healthy: None,
+        };
+
+        assert_eq!(
+            names(actuator_values(2, &state)),
+            vec![
+                ("CAM2_FOCUS".to_string(), 42.0),
+                ("CAM2_TILT".to_string(), 10.0)
+            ]
+        );
+        assert_eq!(
+            names(status_values(2, status, Some(1))),
+            vec![
+                ("CAM2_LINK".to_string(), 0.0),
+                ("CAM2_PROF".to_string(), 2.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn every_camera_gets_its_own_names() {
+        let first = Uuid::from_u128(1);
+        let second = Uuid::from_u128(2);
+
+        let first_name = data_lake_name(first, DataLakeValue::Zoom).unwrap();
+        let second_name = data_lake_name(second, DataLakeValue::Zoom).unwrap();
+
+        assert_ne!(first_name, second_name);
+        assert_eq!(
+            data_lake_name(first, DataLakeValue::Zoom).unwrap(),
+            first_name
+        );
+        for value in DataLakeValue::ALL {
+            assert!(data_lake_name(second, value).unwrap().len() <= 10);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Latest position and attitude of the vehicle, for tagging what the cameras capture.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use mavlink::{
+    MavHeader,
+    ardupilotmega::{
+        ATTITUDE_DATA, GLOBAL_POSITION_INT_DATA, MavMessage, SCALED_PRESSURE_DATA, VFR_HUD_DATA,
+    },
+};
+use once_cell::sync::Lazy;
+
+use crate::{api, mavlink::MavlinkComponent, message_loop};
+
+/// Older reports are left out of the snapshot rather than tagging images with them.
+const STALE_AFTER: Duration = Duration::from_secs(5);
+/// `VFR_HUD` carries the autopilot's own depth estimate; only fall back to raw
+/// `SCALED_PRESSURE` when it has gone quiet for this long.
+const VFR_HUD_STALE_AFTER: Duration = Duration::from_secs(3);
+const SURFACE_PRESSURE_HPA: f32 = 1013.25;
+const WATER_DENSITY_KG_M3: f32 = 1000.0;
+const GRAVITY_M_S2: f32 = 9.80665;
+
+static LATEST: Lazy<Mutex<Latest>> = Lazy::new(|| Mutex::new(Latest::default()));
+
+#[derive(Debug, Default)]
+struct Latest {
+    position: Option<(Instant, GLOBAL_POSITION_INT_DATA)>,
+    attitude: Option<(Instant, ATTITUDE_DATA)>,
+    vfr_hud: Option<(Instant, VFR_HUD_DATA)>,
+    scaled_pressure: Option<(Instant, SCALED_PRESSURE_DATA)>,
+}
+
+impl Latest {
+    fn depth(&self) -> Option<f32> {
+        if let Some((_, vfr_hud)) = self
+            .vfr_hud
+            .as_ref()
+            .filter(|(at, _)| at.elapsed() < VFR_HUD_STALE_AFTER)
+        {
+            // ArduSub reports depth as a negative altitude.
+            return Some(-vfr_hud.alt);
+        }
+
+        self.scaled_pressure
+            .as_ref()
+            .filter(|(at, _)| at.elapsed() < STALE_AFTER)
+            .map(|(_, scaled_pressure)| depth_from_pressure(scaled_pressure.press_abs))
+    }
+}
+
+/// Start following the vehicle telemetry.
+pub fn start() {
+    message_loop::add_message_handler("telemetry watcher", record);
+}
+
+/// Depth of the vehicle in meters, from `VFR_HUD` or else from the raw pressure, or
+/// `None` when neither was reported recently. Negative above the surface.
+pub(crate) fn depth() -> Option<f32> {
+    LATEST.lock().unwrap().depth()
+}
+
+/// The vehicle telemetry as of now, leaving out anything not reported recently.
+pub(crate) fn snapshot() -> api::VehicleTelemetry {
+    let latest = LATEST.lock().unwrap();
+    let fresh = |at: &Instant| at.elapsed() < STALE_AFTER;
+
+    let mut telemetry = api::VehicleTelemetry::default();
+
+    if let Some((_, position)) = latest.position.as_ref().filter(|(at, _)| fresh(at)) {
+        // Both zero means the autopilot has no position fix.
+        if position.lat != 0 || position.lon != 0 {
+            telemetry.latitude = Some(position.lat as f64 / 1e7);
+            telemetry.longitude = Some(position.lon as f64 / 1e7);
+            telemetry.altitude = Some(position.alt as f32 / 1000.0);
+        }
+        telemetry.relative_altitude = Some(position.relative_alt as f32 / 1000.0);
+        if position.hdg != u16::MAX {
+            telemetry.heading = Some(position.hdg as f32 / 100.0);
+        }
+    }
+
+    telemetry.depth = latest.depth().map(|depth| depth.max(0.0));
+    if let Some((_, vfr_hud)) = latest.vfr_hud.as_ref().filter(|(at, _)| fresh(at)) {
+        telemetry
+            .heading
+            .get_or_insert(vfr_hud.heading.rem_euclid(360) as f32);
+    }
+
+    if let Some((_, attitude)) = latest.attitude.as_ref().filter(|(at, _)| fresh(at)) {
+        telemetry.roll = Some(attitude.roll.to_degrees());
+        telemetry.pitch = Some(attitude.pitch.to_degrees());
+        telemetry.yaw = Some(attitude.yaw.to_degrees());
+    }
+
+    telemetry
+}
+
+/// Keep the latest report of each kind sent by the vehicle.
+async fn record(mavlink: &'static MavlinkComponent, header: MavHeader, message: MavMessage) {
+    if header.system_id != mavlink.system_id() {
+        return;
+    }
+
+    let now = Instant::now();
+    let mut latest = LATEST.lock().unwrap();
+    match message {
+        MavMessage::GLOBAL_POSITION_INT(data) => latest.position = Some((now, data)),
+        MavMessage::ATTITUDE(data) => latest.attitude = Some((now, data)),
+        MavMessage::VFR_HUD(data) => latest.vfr_hud = Some((now, data)),
+        MavMessage::SCALED_PRESSURE(data) => latest.scaled_pressure = Some((now, data)),
+        _ => (),
+    }
+}
+
+/// Depth in meters from an absolute pressure in hPa, assuming a standard atmosphere at the surface.
+fn depth_from_pressure(press_abs: f32) -> f32 {
+    (press_abs - SURFACE_PRESSURE_HPA) * 100.0 / (WATER_DENSITY_KG_M3 * GRAVITY_M_S2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depth_from_pressure_is_about_one_meter_per_hundred_hpa() {
+        assert!(depth_from_pressure(SURFACE_PRESSURE_HPA).abs() < f32::EPSILON);
+        assert!((depth_from_pressure(SURFACE_PRESSURE_HPA + 98.0665) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn depth_prefers_vfr_hud_over_pressure() {
+        let now = Instant::now();
+        let mut latest = Latest {
+            scaled_pressure: Some((
+                now,
+                SCALED_PRESSURE_DATA {
+                    press_abs: SURFACE_PRESSURE_HPA + 98.0665,
+                    ..Default::default()
+                },
+            )),
+            ..Default::default()
+        };
+        assert!((latest.depth().unwrap() - 1.0).abs() < 1e-3);
+
+        latest.vfr_hud = Some((
+            now,
+            VFR_HUD_DATA {
+                alt: -2.5,
+                ..Default::default()
+            },
+        ));
+        assert_eq!(latest.depth(), Some(2.5));
+    }
+}
@@ -0,0 +1,263 @@
+//! Actuator profiles per vehicle, keyed by the connected autopilot's identity.
+//!
+//! The autopilot is identified once the parameters are synced, and again whenever its
+//! heartbeat returns after a silence, since that is when a different vehicle may have been
+//! plugged in. A vehicle with a stored profile gets it activated; an unknown one blocks
+//! every parameter push until it is adopted, so another vehicle's configuration is never
+//! written to it by accident.
+
+use std::{sync::Mutex, time::Instant};
+
+use ::mavlink::ardupilotmega::{MavComponent, MavMessage};
+use anyhow::{Context, Result, anyhow};
+use tokio::sync::broadcast;
+use tracing::*;
+
+use crate::{
+    api,
+    health::{self, HEARTBEAT_TIMEOUT},
+    manager::{self, CONFIG_APPLY, MANAGER, Manager, owned_parameters},
+    mavlink::{self, MavlinkComponent, Message},
+    parameters::ParamType,
+};
+
+/// Identity of the connected autopilot while it has no stored profile.
+static UNKNOWN_VEHICLE: Mutex<Option<String>> = Mutex::new(None);
+
+/// The connected autopilot's identity, when no profile is stored for it.
+pub fn unknown_vehicle() -> Option<String> {
+    UNKNOWN_VEHICLE.lock().expect("vehicle lock").clone()
+}
+
+/// Refuse to push parameters to an autopilot that has not been adopted.
+pub(crate) fn ensure_known() -> Result<()> {
+    match unknown_vehicle() {
+        Some(identity) => Err(anyhow!(
+            "Autopilot {identity} has no stored profile, adopt it before configuring it"
+        )),
+        None => Ok(()),
+    }
+}
+
+#[instrument(level = "debug")]
+pub async fn vehicles() -> Result<api::VehicleProfiles> {
+    let (active, stored) = settings::vehicles().await?;
+
+    Ok(api::VehicleProfiles {
+        active,
+        stored,
+        unknown: unknown_vehicle(),
+    })
+}
+
+/// Start a profile for the connected, unknown autopilot and make it the active one.
+#[instrument(level = "debug")]
+pub async fn adopt(request: api::AdoptVehicle) -> Result<api::VehicleProfiles> {
+    let apply = CONFIG_APPLY.lock().await;
+
+    let identity = unknown_vehicle().context("The connected autopilot is already known")?;
+
+    Manager::save_actuators_settings().await?;
+    settings::adopt_vehicle(&identity, request.start_empty).await?;
+    info!("Adopted autopilot {identity}");
+
+    activate_profile(apply).await?;
+    set_unknown_vehicle(None);
+
+    vehicles().await
+}
+
+/// Identify the connected autopilot and activate its profile.
+#[instrument(level = "debug")]
+pub(crate) async fn identify() -> Result<()> {
+    let component = mavlink::component()?;
+    let identity = identity(component).await?;
+
+    let apply = CONFIG_APPLY.lock().await;
+
+    // Stash the outgoing vehicle's latest state along with its profile.
+    Manager::save_actuators_settings().await?;
+
+    match settings::select_vehicle(&identity).await? {
+        settings::VehicleSelection::Unchanged => {}
+        settings::VehicleSelection::Claimed => {
+            info!("Autopilot {identity} claimed the current settings");
+        }
+        settings::VehicleSelection::Switched => {
+            info!("Autopilot {identity} is known, activating its profile");
+            activate_profile(apply).await?;
+        }
+        settings::VehicleSelection::Unknown => {
+            warn!(
+                "Autopilot {identity} has no stored profile. No parameters will be pushed to it until it is adopted"
+            );
+            set_unknown_vehicle(Some(identity));
+            return Ok(());
+        }
+    }
+
+    set_unknown_vehicle(None);
+    Ok(())
+}
+
+/// Re-identify the autopilot whenever its heartbeat returns after a silence, and on the
+/// next heartbeat if the startup identification failed.
+pub(crate) fn start(identified: bool) {
+    tokio::spawn(watch_task(!identified));
+}
+
+#[instrument(level = "debug")]
+async fn watch_task(mut pending: bool) {
+    let Ok(component) = mavlink::component() else {
+        error!("MAVLink component not initialized, vehicle profiles will not follow swaps");
+        return;
+    };
+
+    let target_system = component.system_id();
+    let target_component = MavComponent::MAV_COMP_ID_AUTOPILOT1 as u8;
+    let mut receiver = component.get_receiver().await;
+
+    // `init` tried identifying the autopilot right before starting this task.
+    let mut last_heartbeat = Instant::now();
+
+    loop {
+        match receiver.recv().await {
+            Ok(Message::Received((header, MavMessage::HEARTBEAT(_))))
+                if header.system_id == target_system && header.component_id == target_component =>
+            {
+                let returned = last_heartbeat.elapsed() > HEARTBEAT_TIMEOUT;
+                last_heartbeat = Instant::now();
+
+                if !returned && !pending {
+                    continue;
+                }
+
+                pending = match identify().await {
+                    Ok(()) => false,
+                    Err(error) => {
+                        warn!("Failed to identify the autopilot, retrying: {error:#}");
+                        true
+                    }
+                };
+            }
+            Ok(_) => continue,
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => return,
+        }
+    }
+}
+
+/// Reload the actuators from the active profile. Consumes the caller's [`CONFIG_APPLY`].
+async fn activate_profile(apply: tokio::sync::MutexGuard<'_, ()>) -> Result<()> {
+    let settings = manager::State::from_settings().await?;
+    MANAGER
+        .get()
+        .context("Not available")?
+        .write()
+        .await
+        .settings = settings;
+    drop(apply);
+
+    owned_parameters::rebuild().await;
+    if let Ok(component) = mavlink::component() {
+        let cache = component.inner.parameters.read().await;
+        owned_parameters::establish_baseline_from_cache(&cache);
+    }
+
+    Ok(())
+}
+
+fn set_unknown_vehicle(identity: Option<String>) {
+    let mut guard = UNKNOWN_VEHICLE.lock().expect("vehicle lock");
+    if *guard == identity {
+        return;
+    }
+
+    *guard = identity;
+    drop(guard);
+
+    health::notify_health();
+}
+
+/// The autopilot's hardware UID when it reports one, else its system ID and board serial.
+async fn identity(component: &MavlinkComponent) -> Result<String> {
+    match component.request_autopilot_version().await {
+        Ok(version) => {
+            if let Some(identity) = hardware_identity(version.uid, &version.uid2) {
+                return Ok(identity);
+            }
+            debug!("Autopilot reports no hardware UID");
+        }
+        Err(error) => debug!("AUTOPILOT_VERSION unavailable: {error:#}"),
+    }
+
+    let system_id = component
+        .get_param("SYSID_THISMAV", true)
+        .await
+        .context("Failed to read SYSID_THISMAV")?;
+    let serial = component
+        .get_param("BRD_SERIAL_NUM", true)
+        .await
+        .context("Failed to read BRD_SERIAL_NUM")?;
+
+    Ok(format!(
+        "sysid:{}:serial:{}",
+        param_text(system_id.value),
+        param_text(serial.value)
+    ))
+}
+
+fn hardware_identity(uid: u64, uid2: &[u8]) -> Option<String> {
+    if uid != 0 {
+        return Some(format!("uid:{uid:016x}"));
+    }
+
+    if uid2.iter().any(|byte| *byte != 0) {
+        let hex: String = uid2.iter().map(|byte| format!("{byte:02x}")).collect();
+        return Some(format!("uid2:{hex}"));
+    }
+
+    None
+}
+
+fn param_text(value: ParamType) -> String {
+    match value {
+        ParamType::UINT8(value) => value.to_string(),
+        ParamType::INT8(value) => value.to_string(),
+        ParamType::UINT16(value) => value.to_string(),
+        ParamType::INT16(value) => value.to_string(),
+        ParamType::UINT32(value) => value.to_string(),
+        ParamType::INT32(value) => value.to_string(),
+        ParamType::UINT64(value) => value.to_string(),
+        ParamType::INT64(value) => value.to_string(),
+        ParamType::REAL32(value) => value.to_string(),
+        ParamType::REAL64(value) => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uid_is_preferred_over_uid2() {
+        let mut uid2 = [0u8; 18];
+        assert_eq!(hardware_identity(0, &uid2), None);
+
+        uid2[17] = 0xab;
+        assert_eq!(
+            hardware_identity(0, &uid2).as_deref(),
+            Some("uid2:0000000000000000000000000000000000ab")
+        );
+        assert_eq!(
+            hardware_identity(0x1234, &uid2).as_deref(),
+            Some("uid:0000000000001234")
+        );
+    }
+
+    #[test]
+    fn serial_fallback_reads_like_the_parameter() {
+        assert_eq!(param_text(ParamType::INT32(-42)), "-42");
+        assert_eq!(param_text(ParamType::REAL32(1.0)), "1");
+    }
+}
@@ -13,13 +13,14 @@
 //! that reason.
 
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde_derive::{Deserialize, Serialize};
 use serde_json::Value;
 use ts_rs::TS;
 use uuid::Uuid;
 
 /// Reachability of one camera from the 4K Cam Manager.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum CameraConnectivity {
     /// No probe has completed yet since this camera became interesting.
@@ -38,7 +39,7 @@ pub enum CameraConnectivity {
 }
 
 /// Whether the autopilot has the Lua script this install expects.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum LuaScriptStatus {
     /// No camera is configured yet, or the script file could not be read at all.
@@ -61,7 +62,7 @@ pub enum LuaScriptStatus {
 ///
 /// "Refused" and "wedged" are deliberately one state: the user action is the
 /// same for both. The distinction lives in [`SystemHealth::mcm_detail`].
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum McmHealth {
     /// No poll cycle has completed yet.
@@ -71,10 +72,12 @@ pub enum McmHealth {
     Online,
     /// MCM is not usable; see `mcm_detail`.
     Down,
+    /// This build has no MCM support; cameras are registered by hand.
+    Disabled,
 }
 
 /// Health of the autopilot control path, from outermost failure to innermost.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, TS, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AutopilotHealth {
     /// No assessment yet, or the first endpoint attempt has not returned.
@@ -92,10 +95,12 @@ pub enum AutopilotHealth {
     Syncing,
     /// Fully usable.
     Online,
+    /// This build has no autopilot support.
+    Disabled,
 }
 
 /// A configured camera that is absent from MCM's current list.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema)]
 pub struct ExpectedCamera {
     /// Camera UUID from the persisted actuators settings.
     #[ts(as = "String")]
@@ -107,7 +112,7 @@ pub struct ExpectedCamera {
 }
 
 /// One autopilot parameter whose live value no longer matches persisted actuator settings.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS, JsonSchema)]
 pub struct ParameterDrift {
     /// MAVLink parameter name, e.g. `SERVO1_FUNCTION`.
     pub name: String,
@@ -119,7 +124,7 @@ pub struct ParameterDrift {
 
 /// Support-oriented counters. Never a primary signal; rendered only inside the
 /// collapsed "Health diagnostics" panel and included in the copy-to-clipboard blob.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default, TS, JsonSchema)]
 pub struct Diagnostics {
     /// Negotiated MAVLink parameter encoding, e.g. `CCast`. `None` before sync.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -158,11 +163,16 @@ pub struct Diagnostics {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub settings_error: Option<String>,
+    /// The backup the settings were restored from at startup, because the settings file
+    /// was corrupted, e.g. by a power cut during a save.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub settings_recovered_from: Option<String>,
 }
 
 /// Backend-wide health, pushed on the `system/health` WebSocket event and
 /// served by `GET /v1/health`.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, TS, JsonSchema)]
 pub struct SystemHealth {
     /// Manager to MCM link state.
     pub mcm: McmHealth,
@@ -174,6 +184,8 @@ pub struct SystemHealth {
     pub cameras_discovered: usize,
     /// Cameras this install has configured that MCM is not currently listing.
     pub expected_missing: Vec<ExpectedCamera>,
+    /// True when running without BlueOS, where MAVLink is routed by something else.
+    pub standalone: bool,
     /// Autopilot control path state.
     pub autopilot: AutopilotHealth,
     /// Concrete failure text for the current non-`Online` autopilot state.
@@ -192,12 +204,17 @@ pub struct SystemHealth {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
     pub parameter_drifts: Option<Vec<ParameterDrift>>,
+    /// Identity of a connected autopilot with no stored profile, which no parameters are
+    /// pushed to until it is adopted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub unknown_vehicle: Option<String>,
     /// Support counters.
     pub diagnostics: Diagnostics,
 }
 
 /// Shared UI overlay state synchronized to every subscribed frontend.
-#[derive(Debug, Clone, Serialize, Default, PartialEq, TS)]
+#[derive(Debug, Clone, Serialize, Default, PartialEq, TS, JsonSchema)]
 pub struct CameraUiState {
     /// True while a long-running backend action is in progress.
     pub loading: bool,
@@ -234,7 +251,7 @@ pub struct CameraUiState {
 }
 
 /// Phase of a backend-owned one-push white balance run.
-#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, TS, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum OnePushAwbPhase {
     /// Camera is adjusting gains after `onceAWB`.
@@ -244,14 +261,14 @@ pub enum OnePushAwbPhase {
 }
 
 /// Shared one-push white balance status broadcast on [`CameraUiState`].
-#[derive(Debug, Clone, Serialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, TS, JsonSchema)]
 pub struct OnePushAwbStatus {
     /// Current phase of the run.
     pub phase: OnePushAwbPhase,
 }
 
 /// Partial camera state pushed to subscribed WebSocket clients.
-#[derive(Debug, Clone, Serialize, Default, TS)]
+#[derive(Debug, Clone, Serialize, Default, TS, JsonSchema)]
 pub struct CameraStateEvent {
     /// Camera this event belongs to.
     #[ts(as = "String")]
@@ -284,6 +301,10 @@ pub struct CameraStateEvent {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional, type = "unknown")]
     pub advanced_parameters: Option<serde_json::Value>,
+    /// Guided focus calibration session; `null` once the session ends.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional, type = "unknown")]
+    pub calibration_session: Option<serde_json::Value>,
     /// Shared UI overlay state for this camera.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[ts(optional)]
@@ -291,7 +312,7 @@ pub struct CameraStateEvent {
 }
 
 /// Per-connection and aggregate bandwidth statistics for the connection icon tooltip.
-#[derive(Debug, Clone, Serialize, TS)]
+#[derive(Debug, Clone, Serialize, TS, JsonSchema)]
 pub struct ConnectionStats {
     /// Always true for live connections; false if the id is unknown.
     pub connected: bool,
@@ -311,7 +332,7 @@ pub struct ConnectionStats {
 }
 
 /// Request-response call sent by a client over the WebSocket.
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 pub struct WsRequest {
     /// Client-chosen correlation id echoed back in the [`WsResponse`].
     pub id: u32,
@@ -326,7 +347,7 @@ pub struct WsRequest {
 }
 
 /// Fire-and-forget message sent by a client over the WebSocket.
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum WsClientMessage {
     /// Start receiving [`CameraStateEvent`]s for a camera.
@@ -352,7 +373,7 @@ pub enum WsClientMessage {
 }
 
 /// Shared [`CameraUiState`] overlay a client can dismiss.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum UiDismissField {
     /// The modal error dialog.
@@ -365,7 +386,7 @@ pub enum UiDismissField {
 ///
 /// `response_type` is a `String` (not an enum) so the TypeScript binding can pin it
 /// to the literal `"response"` via `ts_rs` without a tagged enum.
-#[derive(Debug, Clone, Serialize, TS)]
+#[derive(Debug, Clone, Serialize, TS, JsonSchema)]
 pub struct WsResponse {
     /// Message discriminator, always `response`.
     #[serde(rename = "type")]
@@ -384,7 +405,7 @@ pub struct WsResponse {
 ///
 /// `event_type` is a `String` (not an enum) so the TypeScript binding can pin it
 /// to the literal `"event"` via `ts_rs` without a tagged enum.
-#[derive(Debug, Clone, Serialize, TS)]
+#[derive(Debug, Clone, Serialize, TS, JsonSchema)]
 pub struct WsEvent {
     /// Message discriminator, always `event`.
     #[serde(rename = "type")]
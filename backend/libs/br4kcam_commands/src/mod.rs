@@ -2,11 +2,13 @@ use std::{future::Future, pin::Pin};
 
 use anyhow::{Context, Result};
 use axum::{Json, http::StatusCode, response::IntoResponse};
-use mcm_client::camera_address;
+use cameras::camera_address;
 use protocol::{
     display::{advanced_display::AdvancedParameterSetting, base_display::BaseParameterSetting},
+    system::sys_config::SysConfig,
     video::video_parameters::VideoParameterSettings,
 };
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use tracing::*;
 use ts_rs::TS;
@@ -24,7 +26,7 @@ const REBOOT_OFFLINE_TIMEOUT: std::time::Duration = std::time::Duration::from_se
 /// How long to wait for the camera to answer again after it went offline.
 const REBOOT_ONLINE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(90);
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 // #[tsync] // FIXME: Disabled for now, see https://github.com/Wulf/tsync/issues/58
 pub struct CameraControl {
     #[serde(default)]
@@ -34,7 +36,7 @@ pub struct CameraControl {
     pub action: Action,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS, JsonSchema)]
 #[serde(tag = "action", content = "json")]
 // #[tsync] // FIXME: Disabled for now, see https://github.com/Wulf/tsync/issues/58
 pub enum Action {
@@ -62,6 +64,26 @@ pub enum Action {
     SetRecommendedCameraSettings,
 }
 
+/// Result of a [`CameraControl`], shaped after its action. Except for the wrappers, this is
+/// the camera's answer as is, the settings actions answering with the settings read back.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum CameraControlResult {
+    /// `getImageAdjustment` and `setImageAdjustment`.
+    ImageAdjustment(BaseParameterSetting),
+    /// `getImageAdjustmentEx` and `setImageAdjustmentEx`.
+    ImageAdjustmentEx(AdvancedParameterSetting),
+    /// `getVencConf` and `setVencConf`.
+    VideoParameterSettings(VideoParameterSettings),
+    /// `getSysConfig`.
+    SysConfig(SysConfig),
+    /// `restart`.
+    Answer(serde_json::Map<String, serde_json::Value>),
+    /// `setImageAdjustmentExAll`, `setRecommendedCameraSettings`, and a one-push white
+    /// balance requested while one runs.
+    None,
+}
+
 /// Outcome of one reachability probe while waiting for a camera reboot.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ProbeStatus {
@@ -166,6 +188,18 @@ pub async fn handle_control(camera_control: CameraControl) -> Result<serde_json:
     control_inner(Json(camera_control)).await
 }
 
+/// The system configuration `camera_uuid` reports, which identifies the unit.
+#[instrument(level = "debug")]
+pub async fn sys_config(camera_uuid: Uuid) -> Result<SysConfig> {
+    let answer = handle_control(CameraControl {
+        camera_uuid,
+        action: Action::GetSysConfig,
+    })
+    .await?;
+
+    serde_json::from_value(answer).context("Failed parsing the system configuration")
+}
+
 #[instrument(level = "debug")]
 pub async fn control(camera_control: Json<CameraControl>) -> impl IntoResponse {
     match handle_control(camera_control.0).await {
@@ -179,7 +213,7 @@ pub async fn control(camera_control: Json<CameraControl>) -> impl IntoResponse {
 
 #[instrument(level = "debug")]
 pub async fn list() -> impl IntoResponse {
-    let cameras = mcm_client::cameras().await;
+    let cameras = cameras::cameras().await;
 
     let json = match serde_json::to_string(&cameras) {
         Ok(json) => json,
@@ -195,7 +229,7 @@ pub async fn list() -> impl IntoResponse {
 async fn apply_set_image_adjustment_ex_all(
     params: &AdvancedParameterSetting,
 ) -> Result<serde_json::Value> {
-    let cameras = mcm_client::cameras().await;
+    let cameras = cameras::cameras().await;
     let mut errors = vec![];
 
     for (camera_uuid, camera) in &cameras {
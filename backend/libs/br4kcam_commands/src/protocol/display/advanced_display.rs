@@ -1,10 +1,13 @@
+use schemars::{JsonSchema, JsonSchema_repr};
 use serde::{Deserialize, Serialize};
 use serde_repr::*;
 use serde_with::skip_serializing_none;
 use ts_rs::TS;
 use tsync::tsync;
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayMirrorValue {
@@ -13,7 +16,9 @@ pub enum AdvancedDisplayMirrorValue {
     Close = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayFlipValue {
@@ -22,7 +27,9 @@ pub enum AdvancedDisplayFlipValue {
     Close = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayPowerFreqValue {
@@ -31,7 +38,9 @@ pub enum AdvancedDisplayPowerFreqValue {
     PAL = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayColorBlackValue {
@@ -40,7 +49,9 @@ pub enum AdvancedDisplayColorBlackValue {
     Auto = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayInfrDetectModeValue {
@@ -50,7 +61,9 @@ pub enum AdvancedDisplayInfrDetectModeValue {
     PhotosensitiveDetection = 2,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayLensCorrectionValue {
@@ -59,7 +72,9 @@ pub enum AdvancedDisplayLensCorrectionValue {
     Close = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayIRCUTLevelValue {
@@ -68,7 +83,9 @@ pub enum AdvancedDisplayIRCUTLevelValue {
     HighLevel = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayLDRLevelValue {
@@ -77,7 +94,9 @@ pub enum AdvancedDisplayLDRLevelValue {
     HighLevel = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayAntiflickerValue {
@@ -88,7 +107,9 @@ pub enum AdvancedDisplayAntiflickerValue {
     _60HZ = 3,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplaySceneModeValue {
@@ -98,7 +119,9 @@ pub enum AdvancedDisplaySceneModeValue {
     LicensePlateCapture = 2,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayHlcEnableValue {
@@ -107,7 +130,9 @@ pub enum AdvancedDisplayHlcEnableValue {
     Open = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayLowFramerateValue {
@@ -116,7 +141,9 @@ pub enum AdvancedDisplayLowFramerateValue {
     Open = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplay2dNrLevelValue {
@@ -126,7 +153,9 @@ pub enum AdvancedDisplay2dNrLevelValue {
     High = 2,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayWDRSensorValue {
@@ -135,7 +164,9 @@ pub enum AdvancedDisplayWDRSensorValue {
     Open = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayNoiseReductionValue {
@@ -146,7 +177,9 @@ pub enum AdvancedDisplayNoiseReductionValue {
     High = 3,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayAutoIrisValue {
@@ -156,7 +189,9 @@ pub enum AdvancedDisplayAutoIrisValue {
     Manual = 2,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayLedControlValue {
@@ -166,7 +201,9 @@ pub enum AdvancedDisplayLedControlValue {
     Close = 2,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayLedControlAvailValue {
@@ -175,7 +212,9 @@ pub enum AdvancedDisplayLedControlAvailValue {
     HighLevel = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayLightControlModeValue {
@@ -184,7 +223,9 @@ pub enum AdvancedDisplayLightControlModeValue {
     PWM = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum AdvancedDisplayLampTypeValue {
@@ -195,7 +236,7 @@ pub enum AdvancedDisplayLampTypeValue {
 }
 
 #[skip_serializing_none]
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
 pub struct AdvancedParameterSetting {
     /// Mirror.
     pub mirror: Option<AdvancedDisplayMirrorValue>,
@@ -5,7 +5,7 @@ use tsync::tsync;
 use super::*;
 
 #[skip_serializing_none]
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
 pub struct BaseParameterSetting {
     /// Tonal. Range: [0..=255]
     pub hue: Option<u8>,
@@ -96,7 +96,9 @@ pub struct BaseParameterSetting {
     pub rotate: Option<BaseRotateValue>,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u32)]
 pub enum BaseMaxExposureValue {
@@ -116,7 +118,9 @@ pub enum BaseMaxExposureValue {
     _8000 = 8000,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseAntiFogValue {
@@ -125,7 +129,9 @@ pub enum BaseAntiFogValue {
     Open = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseFrameTurboValue {
@@ -135,7 +141,9 @@ pub enum BaseFrameTurboValue {
     UltraHighFrameRates = 2,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseSceneModeValue {
@@ -146,7 +154,9 @@ pub enum BaseSceneModeValue {
     LicensePlateCapture = 2,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseAutoExposureStrategyModeValue {
@@ -155,7 +165,9 @@ pub enum BaseAutoExposureStrategyModeValue {
     LowLightPriority = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseExposureModeValue {
@@ -164,7 +176,9 @@ pub enum BaseExposureModeValue {
     Manual = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u32)]
 pub enum BaseExposureTimeValue {
@@ -186,7 +200,9 @@ pub enum BaseExposureTimeValue {
     _34464 = 34464,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseAutoWhiteBalanceModeValue {
@@ -195,7 +211,9 @@ pub enum BaseAutoWhiteBalanceModeValue {
     Manual = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseAutoWhiteBalanceSceneValue {
@@ -208,7 +226,9 @@ pub enum BaseAutoWhiteBalanceSceneValue {
     // Scene6 = 5,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseAutoGainModeValue {
@@ -217,7 +237,9 @@ pub enum BaseAutoGainModeValue {
     Manual = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseManualAGainEnableValue {
@@ -226,7 +248,9 @@ pub enum BaseManualAGainEnableValue {
     Open = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseManualDGainEnableValue {
@@ -235,7 +259,9 @@ pub enum BaseManualDGainEnableValue {
     Open = 1,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[tsync]
 #[repr(u8)]
 pub enum BaseRotateValue {
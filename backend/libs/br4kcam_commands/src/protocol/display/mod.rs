@@ -1,3 +1,4 @@
+use schemars::{JsonSchema, JsonSchema_repr};
 use serde::{Deserialize, Serialize};
 use serde_repr::*;
 use ts_rs::TS;
@@ -1,6 +1,7 @@
 use serde::Deserialize;
 
 pub mod display;
+pub mod system;
 pub mod video;
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
@@ -0,0 +1 @@
+pub mod sys_config;
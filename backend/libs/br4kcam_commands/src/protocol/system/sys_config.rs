@@ -0,0 +1,64 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+
+/// Answer of `getSysConfig`. Only the identity of the unit is read; the rest of the
+/// configuration is passed through as the camera reports it.
+#[skip_serializing_none]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SysConfig {
+    /// Serial number of the unit, under whichever name the firmware reports it.
+    #[serde(alias = "serialNumber", alias = "serial_number", alias = "sn")]
+    pub serial: Option<String>,
+    /// Identifier of the unit, included in every answer of the camera.
+    pub device_id: Option<String>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SysConfig {
+    /// The hardware serial of the unit: its serial number, else its device identifier.
+    pub fn unit_serial(&self) -> Option<&str> {
+        [&self.serial, &self.device_id]
+            .into_iter()
+            .flatten()
+            .map(|value| value.trim())
+            .find(|value| !value.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use utils::deserialize;
+
+    #[test]
+    fn the_serial_number_comes_before_the_device_identifier() {
+        let json = r##"{
+              "serialNumber": " SN1 ",
+              "code": 0,
+              "device_mac": "bc-07-18-01-c5-0f",
+              "deviceID": "H01000118160100011616",
+              "device_id": "H01000118160100011616",
+              "log": "",
+              "device_ip": "192.168.0.106",
+              "sign_tby": "b14701e44da7d83b064a974cf61a4a6c"
+            }"##;
+
+        let config = deserialize::<SysConfig>(json).expect("Failed deserializing");
+        assert_eq!(config.unit_serial(), Some("SN1"));
+        assert_eq!(config.other["device_ip"], "192.168.0.106");
+    }
+
+    #[test]
+    fn a_unit_without_a_serial_number_is_known_by_its_device_identifier() {
+        let json = r##"{"serialNumber": "", "device_id": "H01000118160100011616"}"##;
+
+        let config = deserialize::<SysConfig>(json).expect("Failed deserializing");
+        assert_eq!(config.unit_serial(), Some("H01000118160100011616"));
+
+        let config = deserialize::<SysConfig>("{}").expect("Failed deserializing");
+        assert_eq!(config.unit_serial(), None);
+    }
+}
@@ -1,3 +1,4 @@
+use schemars::{JsonSchema, JsonSchema_repr};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use ts_rs::TS;
@@ -6,7 +7,7 @@ use tsync::tsync;
 use super::*;
 
 #[skip_serializing_none]
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
 #[tsync]
 pub struct VideoParameterSettings {
     /// Video stream flow channel.
@@ -33,14 +34,16 @@ pub struct VideoParameterSettings {
     pub gop: Option<u16>,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize, TS, JsonSchema)]
 #[tsync]
 pub struct VideoResolutionValue {
     width: u16,
     height: u16,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[repr(u8)]
 #[tsync]
 pub enum VideoChannelValue {
@@ -50,7 +53,9 @@ pub enum VideoChannelValue {
     ThirdStream = 2,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[repr(u8)]
 #[tsync]
 pub enum VideoEncodingProfileValue {
@@ -60,7 +65,9 @@ pub enum VideoEncodingProfileValue {
     HighProfile = 2,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[repr(u8)]
 #[tsync]
 pub enum VideoEncodeTypeValue {
@@ -69,7 +76,9 @@ pub enum VideoEncodeTypeValue {
     H265 = 5,
 }
 
-#[derive(Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS)]
+#[derive(
+    Default, Debug, Clone, PartialEq, Serialize_repr, Deserialize_repr, TS, JsonSchema_repr,
+)]
 #[repr(u8)]
 #[tsync]
 pub enum VideoRcModeValue {
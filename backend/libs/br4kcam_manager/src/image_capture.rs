@@ -0,0 +1,300 @@
+//! Takes the still images requested over MAVLink and stores them on the vehicle computer.
+//!
+//! Each capture is a frame grabbed from the camera's MCM stream, saved as `<id>.jpg` next
+//! to a `<id>.json` with the telemetry and actuators state it was tagged with. The camera
+//! HTTP API has no still image action (see [`br4kcam_commands::Action`]), so the stream is
+//! the only source. The newest [`CAPTURES_KEPT`] captures are kept.
+
+use std::{
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use anyhow::{Context, Result, anyhow};
+use autopilot::api::ImageCapture;
+use chrono::{DateTime, Utc};
+use once_cell::sync::OnceCell;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::{net::UdpSocket, sync::broadcast};
+use tracing::*;
+use uuid::Uuid;
+
+/// Older captures are deleted as new ones are stored.
+pub const CAPTURES_KEPT: usize = 1000;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static CAPTURES_PATH: OnceCell<PathBuf> = OnceCell::new();
+/// Where the REST API is served, to tell the GCS where to download captures from.
+static SERVED_ADDRESS: OnceCell<SocketAddr> = OnceCell::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct StoredCapture {
+    pub id: String,
+    #[serde(flatten)]
+    pub capture: ImageCapture,
+}
+
+/// Start taking the captures requested over MAVLink, storing them in `captures_path` and
+/// serving them from the REST API at `served_address`.
+pub fn start(captures_path: impl Into<PathBuf>, served_address: SocketAddr) {
+    if STARTED
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return;
+    }
+
+    let _ = CAPTURES_PATH.set(captures_path.into());
+    let _ = SERVED_ADDRESS.set(served_address);
+
+    // Subscribed before spawning, so capture commands are accepted from now on.
+    let mut requests = autopilot::subscribe_image_capture_requests();
+    tokio::spawn(async move {
+        loop {
+            let capture = match requests.recv().await {
+                Ok(capture) => capture,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Dropped {skipped} image capture requests");
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            tokio::spawn(take(capture).instrument(Span::current()));
+        }
+    });
+}
+
+#[instrument(level = "debug", skip(capture), fields(camera_uuid = %capture.camera_uuid, image_index = capture.image_index))]
+async fn take(capture: ImageCapture) {
+    let stored = store(&capture).await;
+    if let Err(error) = &stored {
+        warn!("Failed capturing image: {error:?}");
+    }
+
+    let file_url = match &stored {
+        Ok(id) => file_url(&capture.camera_uuid, id).await,
+        Err(_) => None,
+    };
+    if let Err(error) =
+        autopilot::report_image_captured(&capture, file_url.as_deref(), stored.is_ok()).await
+    {
+        debug!("Failed reporting image capture: {error:?}");
+    }
+}
+
+async fn store(capture: &ImageCapture) -> Result<String> {
+    let directory = captures_path()?;
+    let image = mcm_client::grab_frame(&capture.camera_uuid).await?;
+
+    let id = capture_id(capture);
+    let metadata = StoredCapture {
+        id: id.clone(),
+        capture: capture.clone(),
+    };
+
+    tokio::fs::create_dir_all(directory)
+        .await
+        .with_context(|| format!("Failed creating {directory:?}"))?;
+    tokio::fs::write(directory.join(format!("{id}.jpg")), image).await?;
+    tokio::fs::write(
+        directory.join(format!("{id}.json")),
+        serde_json::to_vec_pretty(&metadata)?,
+    )
+    .await?;
+    info!(id, "Stored image capture");
+
+    prune(directory).await;
+
+    Ok(id)
+}
+
+/// Delete all but the newest [`CAPTURES_KEPT`] captures.
+async fn prune(directory: &Path) {
+    let mut entries = match tokio::fs::read_dir(directory).await {
+        Ok(entries) => entries,
+        Err(error) => {
+            warn!("Failed to list image captures in {directory:?}: {error:?}");
+            return;
+        }
+    };
+
+    let mut ids = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+            && let Some(id) = path.file_stem().and_then(|stem| stem.to_str())
+        {
+            ids.push(id.to_string());
+        }
+    }
+
+    for stale in stale_ids(ids) {
+        for extension in ["jpg", "json"] {
+            let path = directory.join(format!("{stale}.{extension}"));
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => debug!("Removed old image capture {path:?}"),
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(error) => warn!("Failed to remove old image capture {path:?}: {error:?}"),
+            }
+        }
+    }
+}
+
+/// The ids beyond the newest [`CAPTURES_KEPT`], which sort by time.
+fn stale_ids(mut ids: Vec<String>) -> Vec<String> {
+    ids.sort_unstable_by(|left, right| right.cmp(left));
+    ids.into_iter().skip(CAPTURES_KEPT).collect()
+}
+
+/// Absolute URL the GCS downloads the capture `id` from.
+///
+/// When the REST API listens on every interface, the address is the one this computer
+/// reaches the camera from: the GCS shares the camera network to receive its video.
+async fn file_url(camera_uuid: &Uuid, id: &str) -> Option<String> {
+    let served_address = *SERVED_ADDRESS.get()?;
+
+    let ip = if served_address.ip().is_unspecified() {
+        match local_address_towards(camera_uuid).await {
+            Ok(ip) => ip,
+            Err(error) => {
+                warn!("Failed finding the address captures are served at: {error:?}");
+                return None;
+            }
+        }
+    } else {
+        served_address.ip()
+    };
+
+    Some(format!(
+        "http://{}/v1/captures/{id}",
+        SocketAddr::new(ip, served_address.port())
+    ))
+}
+
+/// Local address of the route to the camera. Connecting a UDP socket sends nothing.
+async fn local_address_towards(camera_uuid: &Uuid) -> Result<IpAddr> {
+    let camera_address = cameras::camera_address(camera_uuid)
+        .await
+        .context("Camera not registered")?;
+
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect((camera_address, 80)).await?;
+
+    Ok(socket.local_addr()?.ip())
+}
+
+/// Every stored capture, newest first.
+#[instrument(level = "debug")]
+pub async fn list() -> Result<Vec<StoredCapture>> {
+    let directory = captures_path()?;
+    let mut entries = match tokio::fs::read_dir(directory).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error).with_context(|| format!("Failed reading {directory:?}")),
+    };
+
+    let mut captures = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().is_none_or(|extension| extension != "json") {
+            continue;
+        }
+
+        match read_metadata(&path).await {
+            Ok(capture) => captures.push(capture),
+            Err(error) => debug!("Skipping {path:?}: {error:?}"),
+        }
+    }
+    captures.sort_by_key(|stored| std::cmp::Reverse(stored.capture.time_utc_us));
+
+    Ok(captures)
+}
+
+/// The JPEG image of the capture `id`.
+#[instrument(level = "debug")]
+pub async fn read(id: &str) -> Result<Vec<u8>> {
+    if !is_valid_id(id) {
+        return Err(anyhow!("Invalid capture id"));
+    }
+
+    let path = captures_path()?.join(format!("{id}.jpg"));
+    tokio::fs::read(&path)
+        .await
+        .with_context(|| format!("Failed reading {path:?}"))
+}
+
+async fn read_metadata(path: &Path) -> Result<StoredCapture> {
+    let content = tokio::fs::read(path).await?;
+    Ok(serde_json::from_slice(&content)?)
+}
+
+fn captures_path() -> Result<&'static Path> {
+    CAPTURES_PATH
+        .get()
+        .map(PathBuf::as_path)
+        .context("Image capture not started")
+}
+
+/// `<UTC time>_<camera>_<image index>`, sortable and safe to use as a file name.
+fn capture_id(capture: &ImageCapture) -> String {
+    let time = DateTime::<Utc>::from_timestamp_micros(capture.time_utc_us as i64)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%S%.3fZ");
+    let camera = capture.camera_uuid.simple().to_string();
+
+    format!("{time}_{}_{}", &camera[..8], capture.image_index)
+}
+
+/// Capture ids come from URLs; refuse anything that could leave the captures directory.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || matches!(character, '_' | '.'))
+        && !id.contains("..")
+}
+
+#[cfg(test)]
+mod tests {
+    use autopilot::api::VehicleTelemetry;
+
+    use super::*;
+
+    #[test]
+    fn capture_ids_are_valid_file_names() {
+        let capture = ImageCapture {
+            camera_uuid: Uuid::from_u128(0x1234_5678_9abc_def0_0000_0000_0000_0001),
+            image_index: 7,
+            time_utc_us: 1_700_000_000_123_000,
+            telemetry: VehicleTelemetry::default(),
+            actuators_state: None,
+        };
+
+        let id = capture_id(&capture);
+        assert_eq!(id, "20231114T221320.123Z_12345678_7");
+        assert!(is_valid_id(&id));
+    }
+
+    #[test]
+    fn only_the_newest_captures_are_kept() {
+        let ids: Vec<String> = (0..CAPTURES_KEPT + 2)
+            .map(|index| format!("20231114T{index:06}.000Z_12345678_{index}"))
+            .collect();
+
+        assert_eq!(stale_ids(ids.clone()), vec![ids[1].clone(), ids[0].clone()]);
+    }
+
+    #[test]
+    fn ids_cannot_escape_the_captures_directory() {
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id("../settings"));
+        assert!(!is_valid_id(".."));
+        assert!(!is_valid_id("a/b"));
+        assert!(!is_valid_id("a\\b"));
+    }
+}
@@ -0,0 +1,166 @@
+//! Keeps the cameras announced over MAVLink in step with the cameras MCM discovers.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use autopilot::{MavlinkCamera, MavlinkVideoStream};
+use br4kcam_commands::{
+    Action as CameraAction, CameraControl,
+    protocol::video::video_parameters::{VideoChannelValue, VideoParameterSettings},
+};
+use once_cell::sync::Lazy;
+use tracing::*;
+use uuid::Uuid;
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(5);
+/// Encoder settings rarely change; don't poll every camera's HTTP API on each sync.
+const VIDEO_PARAMETERS_MAX_AGE: Duration = Duration::from_secs(30);
+const CAMERA_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static VIDEO_PARAMETERS: Lazy<Mutex<HashMap<Uuid, (Instant, VideoParameterSettings)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Start announcing discovered cameras over MAVLink, once.
+pub fn start() {
+    if STARTED
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        return;
+    }
+
+    tokio::spawn(async {
+        let mut cameras = cameras::subscribe_cameras();
+        let mut interval = tokio::time::interval(SYNC_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = cameras.recv() => {}
+                _ = interval.tick() => {}
+            }
+            sync().await;
+        }
+    });
+}
+
+#[instrument(level = "debug")]
+async fn sync() {
+    let discovered = cameras::cameras().await;
+
+    for camera_uuid in autopilot::mavlink_cameras() {
+        if !discovered.contains_key(&camera_uuid) {
+            autopilot::remove_mavlink_camera(camera_uuid);
+            VIDEO_PARAMETERS.lock().unwrap().remove(&camera_uuid);
+        }
+    }
+
+    for camera_uuid in discovered.keys().copied() {
+        let description = describe(camera_uuid).await;
+        if let Err(error) = autopilot::set_mavlink_camera(camera_uuid, description) {
+            debug!(%camera_uuid, "Not announcing camera over MAVLink: {error:?}");
+        }
+    }
+}
+
+async fn describe(camera_uuid: Uuid) -> MavlinkCamera {
+    let streams = mcm_client::camera_streams(&camera_uuid).await;
+    let video_parameters = video_parameters(camera_uuid).await;
+
+    let resolution = video_parameters
+        .as_ref()
+        .and_then(|parameters| Some((parameters.pic_width?, parameters.pic_height?)));
+    let framerate = video_parameters
+        .as_ref()
+        .and_then(|parameters| parameters.frame_rate)
+        .map(f32::from);
+    let bitrate = video_parameters
+        .as_ref()
+        .and_then(|parameters| parameters.bitrate)
+        .map(u32::from);
+
+    let device_information = streams
+        .iter()
+        .find_map(|stream| stream.device_information())
+        .cloned();
+
+    MavlinkCamera {
+        vendor_name: device_information
+            .as_ref()
+            .map(|device| device.manufacturer.clone())
+            .unwrap_or_else(|| "Blue Robotics".to_string()),
+        model_name: device_information
+            .as_ref()
+            .map(|device| device.model.clone())
+            .unwrap_or_else(|| "4K Camera".to_string()),
+        firmware_version: device_information.map(|device| device.firmware_version),
+        streams: streams
+            .iter()
+            .flat_map(|stream| {
+                stream
+                    .stream_endpoints()
+                    .iter()
+                    .map(move |endpoint| MavlinkVideoStream {
+                        name: stream.name().to_string(),
+                        uri: endpoint.to_string(),
+                        running: stream.is_running(),
+                        resolution,
+                        framerate,
+                        bitrate,
+                    })
+            })
+            .collect(),
+    }
+}
+
+/// Main stream encoder settings, cached for [`VIDEO_PARAMETERS_MAX_AGE`].
+async fn video_parameters(camera_uuid: Uuid) -> Option<VideoParameterSettings> {
+    let cached = VIDEO_PARAMETERS.lock().unwrap().get(&camera_uuid).cloned();
+    if let Some((fetched_at, parameters)) = &cached
+        && fetched_at.elapsed() < VIDEO_PARAMETERS_MAX_AGE
+    {
+        return Some(parameters.clone());
+    }
+
+    let fetched = tokio::time::timeout(
+        CAMERA_FETCH_TIMEOUT,
+        br4kcam_commands::handle_control(CameraControl {
+            camera_uuid,
+            action: CameraAction::GetVideoParameterSettings(VideoParameterSettings {
+                channel: Some(VideoChannelValue::MainStream),
+                ..Default::default()
+            }),
+        }),
+    )
+    .await;
+
+    match fetched {
+        Ok(Ok(value)) => match serde_json::from_value::<VideoParameterSettings>(value) {
+            Ok(parameters) => {
+                VIDEO_PARAMETERS
+                    .lock()
+                    .unwrap()
+                    .insert(camera_uuid, (Instant::now(), parameters.clone()));
+                Some(parameters)
+            }
+            Err(error) => {
+                debug!(%camera_uuid, "Unexpected video parameters: {error:?}");
+                cached.map(|(_, parameters)| parameters)
+            }
+        },
+        Ok(Err(error)) => {
+            debug!(%camera_uuid, "Failed fetching video parameters: {error:?}");
+            cached.map(|(_, parameters)| parameters)
+        }
+        Err(_) => {
+            debug!(%camera_uuid, "Timed out fetching video parameters");
+            cached.map(|(_, parameters)| parameters)
+        }
+    }
+}
@@ -1,2 +1,6 @@
+#[cfg(all(feature = "autopilot", feature = "mcm"))]
+pub mod image_capture;
 pub mod logger;
+#[cfg(all(feature = "autopilot", feature = "mcm"))]
+pub mod mavlink_camera;
 pub mod web;
@@ -21,6 +21,7 @@ use tokio::sync::broadcast;
 use tracing::*;
 use uuid::Uuid;
 
+#[cfg(feature = "autopilot")]
 use autopilot::api::{Action as AutopilotAction, ActuatorsControl};
 
 use crate::web::camera_ui;
@@ -36,10 +37,17 @@ const SLOW_FETCH_CONCURRENCY: usize = 4;
 /// Cap distinct cameras one WebSocket may subscribe to.
 const MAX_CAMERAS_PER_CONNECTION: usize = 8;
 
+/// What the actuator fetches fail with in a build without the autopilot.
+#[cfg(not(feature = "autopilot"))]
+const NO_AUTOPILOT: &str = "This build has no autopilot support";
+
+#[cfg(feature = "autopilot")]
 static ACTUATORS_BRIDGE_STARTED: AtomicBool = AtomicBool::new(false);
 static SLOW_WATCHER_STARTED: AtomicBool = AtomicBool::new(false);
 static CAMERA_LIST_WATCHER_STARTED: AtomicBool = AtomicBool::new(false);
+#[cfg(feature = "autopilot")]
 static ACTUATORS_LAG_RESYNCING: AtomicBool = AtomicBool::new(false);
+#[cfg(feature = "autopilot")]
 static ACTUATORS_LAG_NEEDS_RESYNC: AtomicBool = AtomicBool::new(false);
 static REGISTRY: OnceCell<Mutex<Registry>> = OnceCell::new();
 static STATE_TX: OnceCell<broadcast::Sender<CameraStateEvent>> = OnceCell::new();
@@ -57,10 +65,12 @@ struct Registry {
 ///
 /// Call [`ActuatorsLagResyncGuard::disarm`] before a clean exit that already
 /// cleared `ACTUATORS_LAG_RESYNCING`, so Drop does not clobber a successor task.
+#[cfg(feature = "autopilot")]
 struct ActuatorsLagResyncGuard {
     armed: bool,
 }
 
+#[cfg(feature = "autopilot")]
 impl ActuatorsLagResyncGuard {
     fn new() -> Self {
         Self { armed: true }
@@ -79,8 +89,10 @@ struct CameraSnapshot {
     video_parameters: Option<serde_json::Value>,
     base_parameters: Option<serde_json::Value>,
     advanced_parameters: Option<serde_json::Value>,
+    calibration_session: Option<serde_json::Value>,
 }
 
+#[cfg(feature = "autopilot")]
 impl Drop for ActuatorsLagResyncGuard {
     fn drop(&mut self) {
         if self.armed {
@@ -179,10 +191,11 @@ pub(crate) fn subscribe(connection_id: ConnectionId, camera_uuid: Uuid) -> bool
         // Keep the autopilot interest handoff under the same lock as the registry so a
         // concurrent last-unsubscribe cannot disable SERVO after we just enabled it.
         if was_empty {
-            autopilot::add_actuators_state_interest();
+            add_actuators_state_interest();
         }
     }
 
+    #[cfg(feature = "autopilot")]
     ensure_actuators_bridge();
     ensure_slow_watcher_started();
     ensure_camera_list_watcher();
@@ -207,7 +220,7 @@ pub(crate) fn unsubscribe(connection_id: ConnectionId, camera_uuid: Uuid) {
     let had_interest = !registry.camera_interest.is_empty();
     release_interest(&mut registry, camera_uuid);
     if had_interest && registry.camera_interest.is_empty() {
-        autopilot::remove_actuators_state_interest();
+        remove_actuators_state_interest();
     }
 }
 
@@ -225,7 +238,7 @@ pub(crate) fn unsubscribe_connection(connection_id: ConnectionId) {
     }
 
     if had_interest && registry.camera_interest.is_empty() {
-        autopilot::remove_actuators_state_interest();
+        remove_actuators_state_interest();
     }
 }
 
@@ -254,15 +267,15 @@ pub(crate) fn cached_state_event(camera_uuid: Uuid) -> CameraStateEvent {
 /// `last_states` when the SERVO cache is stale for this camera.
 #[instrument(level = "debug", skip_all, fields(%camera_uuid))]
 pub(crate) async fn actuators_state_event(camera_uuid: Uuid) -> Option<CameraStateEvent> {
-    let actuators_state = match autopilot::handle_control(ActuatorsControl {
-        camera_uuid,
-        action: AutopilotAction::GetActuatorsState,
-    })
-    .await
-    {
+    // Nothing to push, and nothing worth a warning, without actuators.
+    if cfg!(not(feature = "autopilot")) {
+        return None;
+    }
+
+    let actuators_state = match fetch_actuators_state(camera_uuid).await {
         Ok(value) => value,
         Err(error) => {
-            warn!(%camera_uuid, "GetActuatorsState for actuators_state_event failed: {error:?}");
+            warn!(%camera_uuid, "GetActuatorsState for actuators_state_event failed: {error}");
             return None;
         }
     };
@@ -331,6 +344,7 @@ pub(crate) fn emit_camera_control_update(
 }
 
 /// Push autopilot-control side effects into the shared state stream.
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip_all, fields(%camera_uuid))]
 pub(crate) fn emit_autopilot_control_update(
     camera_uuid: Uuid,
@@ -343,13 +357,39 @@ pub(crate) fn emit_autopilot_control_update(
     };
 
     match action {
-        AutopilotAction::SetActuatorsState(_) => event.actuators_state = Some(result.clone()),
+        AutopilotAction::SetActuatorsState(_)
+        | AutopilotAction::StepActuators(_)
+        | AutopilotAction::RecallPreset(_) => event.actuators_state = Some(result.clone()),
         AutopilotAction::SetActuatorsConfig(_) | AutopilotAction::ResetActuatorsConfig => {
             event.actuators_config = Some(result.clone());
             event.actuators_configured = Some(true);
         }
         AutopilotAction::ForgetActuatorsConfig => {
             event.actuators_configured = Some(false);
+            event.calibration_session = Some(serde_json::Value::Null);
+        }
+        AutopilotAction::StartCalibrationSession(_)
+        | AutopilotAction::GoToCalibrationStep(_)
+        | AutopilotAction::NudgeCalibrationFocus(_)
+        | AutopilotAction::CaptureCalibrationPoint
+        | AutopilotAction::DiscardCalibrationSession => {
+            event.calibration_session = Some(result.clone());
+        }
+        AutopilotAction::ImportCalibrationCsv(import)
+            if import.target == Some(autopilot::api::CalibrationImportTarget::Calibration) =>
+        {
+            tokio::spawn(reconcile_snapshot(camera_uuid).instrument(Span::current()));
+            return;
+        }
+        AutopilotAction::SaveCalibrationSet(_)
+        | AutopilotAction::SwitchCalibrationSet(_)
+        | AutopilotAction::SetOpticsMapping(_) => {
+            tokio::spawn(reconcile_snapshot(camera_uuid).instrument(Span::current()));
+            return;
+        }
+        AutopilotAction::CommitCalibrationSession => {
+            event.actuators_config = Some(result.clone());
+            event.calibration_session = Some(serde_json::Value::Null);
         }
         _ => return,
     }
@@ -456,6 +496,11 @@ fn commit_fetched_snapshot(
         &baseline.advanced_parameters,
         fetched.advanced_parameters,
     );
+    take_if_unchanged(
+        &mut current.calibration_session,
+        &baseline.calibration_session,
+        fetched.calibration_session,
+    );
 
     Some(current.clone())
 }
@@ -555,8 +600,13 @@ fn record_partial(event: &mut CameraStateEvent) {
         snapshot.advanced_parameters = Some(value.clone());
         event.advanced_parameters = Some(value);
     }
+    if let Some(value) = event.calibration_session.take() {
+        snapshot.calibration_session = Some(value.clone());
+        event.calibration_session = Some(value);
+    }
 }
 
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip_all)]
 fn ensure_actuators_bridge() {
     if ACTUATORS_BRIDGE_STARTED
@@ -678,7 +728,7 @@ fn ensure_camera_list_watcher() {
     }
 
     tokio::spawn(async {
-        let mut receiver = mcm_client::subscribe_cameras();
+        let mut receiver = cameras::subscribe_cameras();
         let mut interval = tokio::time::interval(CAMERA_LIST_WATCHER_TICK);
         interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
         loop {
@@ -783,6 +833,7 @@ async fn slow_state_watcher() {
 /// Uses the autopilot manager (authoritative after the watcher wrote it), not
 /// this module's possibly-stale `last_states`, so EmitGate-deduped positions
 /// still reach clients.
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip_all)]
 async fn resync_actuators_from_manager(cameras: Vec<Uuid>) {
     for camera_uuid in cameras {
@@ -823,6 +874,7 @@ fn snapshot_to_event(
         video_parameters: snapshot.video_parameters,
         base_parameters: snapshot.base_parameters,
         advanced_parameters: snapshot.advanced_parameters,
+        calibration_session: snapshot.calibration_session,
         ui: None,
     }
 }
@@ -837,6 +889,7 @@ async fn fetch_snapshot(camera_uuid: Uuid, previous: &CameraSnapshot) -> CameraS
         camera_fetch_timeout(fetch_base_parameters(camera_uuid)),
         camera_fetch_timeout(fetch_advanced_parameters(camera_uuid)),
     );
+    let calibration_session = fetch_calibration_session(camera_uuid).await;
 
     CameraSnapshot {
         actuators_configured: actuators_configured(&actuators_config, previous),
@@ -855,6 +908,9 @@ async fn fetch_snapshot(camera_uuid: Uuid, previous: &CameraSnapshot) -> CameraS
         advanced_parameters: advanced_parameters
             .ok()
             .or_else(|| previous.advanced_parameters.clone()),
+        calibration_session: calibration_session
+            .ok()
+            .or_else(|| previous.calibration_session.clone()),
     }
 }
 
@@ -863,7 +919,7 @@ async fn fetch_snapshot(camera_uuid: Uuid, previous: &CameraSnapshot) -> CameraS
 /// Preserves `previous.actuators_state` so a merge does not wipe the live SERVO cache.
 #[instrument(level = "debug", skip_all, fields(%camera_uuid))]
 async fn fetch_slow_snapshot(camera_uuid: Uuid, previous: &CameraSnapshot) -> CameraSnapshot {
-    let camera = mcm_client::get_camera(&camera_uuid).await;
+    let camera = cameras::get_camera(&camera_uuid).await;
     if let Some(camera) = &camera {
         connectivity::observe_camera(camera_uuid, camera.hostname);
     }
@@ -950,6 +1006,7 @@ async fn fetch_slow_snapshot(camera_uuid: Uuid, previous: &CameraSnapshot) -> Ca
         advanced_parameters: advanced_parameters
             .ok()
             .or_else(|| previous.advanced_parameters.clone()),
+        calibration_session: previous.calibration_session.clone(),
     }
 }
 
@@ -960,6 +1017,7 @@ fn actuators_configured(
 ) -> Option<bool> {
     match actuators_config {
         Ok(_) => Some(true),
+        #[cfg(feature = "autopilot")]
         Err(error) if autopilot::error_indicates_actuators_not_configured(error) => Some(false),
         Err(error) => {
             debug!("Actuators config probe failed: {error}");
@@ -968,6 +1026,7 @@ fn actuators_configured(
     }
 }
 
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip_all)]
 async fn actuators_default_config() -> Option<serde_json::Value> {
     match ACTUATORS_DEFAULT_CONFIG
@@ -990,6 +1049,12 @@ async fn actuators_default_config() -> Option<serde_json::Value> {
     }
 }
 
+#[cfg(not(feature = "autopilot"))]
+async fn actuators_default_config() -> Option<serde_json::Value> {
+    None
+}
+
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip_all, fields(%camera_uuid))]
 async fn fetch_actuators_config(camera_uuid: Uuid) -> Result<serde_json::Value, String> {
     let value = autopilot::handle_control(ActuatorsControl {
@@ -1001,6 +1066,7 @@ async fn fetch_actuators_config(camera_uuid: Uuid) -> Result<serde_json::Value,
     serde_json::to_value(value).map_err(|error| error.to_string())
 }
 
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip_all, fields(%camera_uuid))]
 async fn fetch_actuators_state(camera_uuid: Uuid) -> Result<serde_json::Value, String> {
     let value = autopilot::handle_control(ActuatorsControl {
@@ -1012,6 +1078,43 @@ async fn fetch_actuators_state(camera_uuid: Uuid) -> Result<serde_json::Value, S
     serde_json::to_value(value).map_err(|error| error.to_string())
 }
 
+#[cfg(feature = "autopilot")]
+#[instrument(level = "debug", skip_all, fields(%camera_uuid))]
+async fn fetch_calibration_session(camera_uuid: Uuid) -> Result<serde_json::Value, String> {
+    autopilot::handle_control(ActuatorsControl {
+        camera_uuid,
+        action: AutopilotAction::GetCalibrationSession,
+    })
+    .await
+    .map_err(|error| format!("{error:?}"))
+}
+
+#[cfg(not(feature = "autopilot"))]
+async fn fetch_actuators_config(_camera_uuid: Uuid) -> Result<serde_json::Value, String> {
+    Err(NO_AUTOPILOT.to_string())
+}
+
+#[cfg(not(feature = "autopilot"))]
+async fn fetch_actuators_state(_camera_uuid: Uuid) -> Result<serde_json::Value, String> {
+    Err(NO_AUTOPILOT.to_string())
+}
+
+#[cfg(not(feature = "autopilot"))]
+async fn fetch_calibration_session(_camera_uuid: Uuid) -> Result<serde_json::Value, String> {
+    Err(NO_AUTOPILOT.to_string())
+}
+
+/// SERVO outputs are only streamed to us while some client watches a camera.
+fn add_actuators_state_interest() {
+    #[cfg(feature = "autopilot")]
+    autopilot::add_actuators_state_interest();
+}
+
+fn remove_actuators_state_interest() {
+    #[cfg(feature = "autopilot")]
+    autopilot::remove_actuators_state_interest();
+}
+
 #[instrument(level = "debug", skip_all, fields(%camera_uuid))]
 async fn fetch_video_parameters(camera_uuid: Uuid) -> Result<serde_json::Value, String> {
     let value = br4kcam_commands::handle_control(CameraControl {
@@ -1049,13 +1152,16 @@ async fn fetch_advanced_parameters(camera_uuid: Uuid) -> Result<serde_json::Valu
 }
 
 async fn refresh_cameras_missing_from_mcm() {
-    let discovered = mcm_client::cameras().await;
+    let discovered = cameras::cameras().await;
     let known: HashSet<Uuid> = discovered.keys().copied().collect();
     for (camera_uuid, camera) in &discovered {
         connectivity::observe_camera(*camera_uuid, camera.hostname);
     }
 
-    let expected: HashSet<Uuid> = autopilot::configured_cameras().await.into_iter().collect();
+    let expected: HashSet<Uuid> = connectivity::configured_cameras()
+        .await
+        .into_iter()
+        .collect();
     let missing: Vec<Uuid> = expected.difference(&known).copied().collect();
     let updates = stream::iter(missing)
         .map(|camera_uuid| async move {
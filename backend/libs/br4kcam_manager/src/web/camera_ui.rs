@@ -7,6 +7,7 @@ use std::{
     time::{Duration, Instant},
 };
 
+#[cfg(feature = "autopilot")]
 use autopilot::api::{Action as AutopilotAction, ActuatorsConfig};
 use br4kcam_api::{CameraConnectivity, CameraUiState, OnePushAwbStatus, UiDismissField};
 use br4kcam_commands::Action as CameraAction;
@@ -74,6 +75,14 @@ pub(crate) fn set_one_push_awb(camera_uuid: Uuid, status: Option<OnePushAwbStatu
 /// Set or clear the shared connectivity state for all clients.
 #[instrument(level = "debug")]
 pub(crate) fn set_connectivity(camera_uuid: Uuid, connectivity: CameraConnectivity) {
+    // Unknown is not offline: nothing has been probed yet.
+    #[cfg(feature = "autopilot")]
+    autopilot::set_camera_online(
+        camera_uuid,
+        (connectivity != CameraConnectivity::Unknown)
+            .then_some(connectivity == CameraConnectivity::Online),
+    );
+
     let state = {
         let mut lock = ui().lock().unwrap();
         let entry = lock.entry(camera_uuid).or_insert_with(new_entry);
@@ -87,8 +96,12 @@ pub(crate) fn set_connectivity(camera_uuid: Uuid, connectivity: CameraConnectivi
 }
 
 /// Set or clear the shared video stream error for all clients.
+#[cfg(feature = "mcm")]
 #[instrument(level = "debug")]
 pub(crate) fn set_stream_error(camera_uuid: Uuid, error: Option<String>) {
+    #[cfg(feature = "autopilot")]
+    autopilot::set_camera_video_healthy(camera_uuid, error.is_none());
+
     let state = {
         let mut lock = ui().lock().unwrap();
         let entry = lock.entry(camera_uuid).or_insert_with(new_entry);
@@ -102,6 +115,7 @@ pub(crate) fn set_stream_error(camera_uuid: Uuid, error: Option<String>) {
 }
 
 /// Set or clear the shared ONVIF authentication error for all clients.
+#[cfg(feature = "mcm")]
 #[instrument(level = "debug")]
 pub(crate) fn set_onvif_auth_error(camera_uuid: Uuid, error: Option<String>) {
     let state = {
@@ -158,6 +172,7 @@ pub(crate) fn fail_camera_action(camera_uuid: Uuid, action: &CameraAction, error
 }
 
 /// Handle a failed autopilot control: dialog for deliberate actions, toast otherwise.
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip(action))]
 pub(crate) fn fail_autopilot_action(camera_uuid: Uuid, action: &AutopilotAction, error: &str) {
     if loading_message_for_autopilot_action(action).is_some() {
@@ -199,6 +214,7 @@ pub(crate) fn finish_camera_action(camera_uuid: Uuid, action: &CameraAction) {
 }
 
 /// Start UI lifecycle for an autopilot action, if deliberate.
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip(action))]
 pub(crate) fn start_autopilot_action(camera_uuid: Uuid, action: &AutopilotAction) {
     let Some(message) = loading_message_for_autopilot_action(action) else {
@@ -208,6 +224,7 @@ pub(crate) fn start_autopilot_action(camera_uuid: Uuid, action: &AutopilotAction
 }
 
 /// Finish UI lifecycle for a successful autopilot action.
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip(action))]
 pub(crate) fn finish_autopilot_action(camera_uuid: Uuid, action: &AutopilotAction) {
     if loading_message_for_autopilot_action(action).is_some() {
@@ -373,6 +390,7 @@ fn end_reboot(camera_uuid: Uuid) {
 /// Show a modal error dialog without clearing an in-progress reboot.
 #[instrument(level = "debug")]
 fn set_error(camera_uuid: Uuid, message: String) {
+    #[cfg(feature = "autopilot")]
     if autopilot::error_indicates_actuators_not_configured(&message) {
         return;
     }
@@ -397,6 +415,7 @@ fn set_error(camera_uuid: Uuid, message: String) {
 /// Show a transient warning toast; suppressed while rebooting.
 #[instrument(level = "debug")]
 pub(crate) fn set_warning(camera_uuid: Uuid, message: String) {
+    #[cfg(feature = "autopilot")]
     if autopilot::error_indicates_actuators_not_configured(&message) {
         return;
     }
@@ -425,6 +444,7 @@ fn loading_message_for_camera_action(action: &CameraAction) -> Option<&'static s
     }
 }
 
+#[cfg(feature = "autopilot")]
 fn loading_message_for_autopilot_action(action: &AutopilotAction) -> Option<&'static str> {
     match action {
         AutopilotAction::ExportLuaScript => Some("Updating Lua script…"),
@@ -433,10 +453,14 @@ fn loading_message_for_autopilot_action(action: &AutopilotAction) -> Option<&'st
             Some("Applying custom hardware setup…")
         }
         AutopilotAction::ForgetActuatorsConfig => Some("Forgetting camera…"),
+        AutopilotAction::CommitCalibrationSession => Some("Saving focus calibration…"),
+        AutopilotAction::ImportCalibrationCsv(_) => Some("Importing focus calibration…"),
+        AutopilotAction::SwitchCalibrationSet(_) => Some("Switching focus calibration…"),
         _ => None,
     }
 }
 
+#[cfg(feature = "autopilot")]
 fn is_full_hardware_setup(config: &ActuatorsConfig) -> bool {
     let Some(parameters) = &config.parameters else {
         return false;
@@ -455,11 +479,15 @@ fn camera_action_label(action: &CameraAction) -> &'static str {
     }
 }
 
+#[cfg(feature = "autopilot")]
 fn autopilot_action_label(action: &AutopilotAction) -> &'static str {
     match action {
         AutopilotAction::ExportLuaScript => "Failed to update Lua script",
         AutopilotAction::ResetActuatorsConfig => "Failed to apply default hardware setup",
         AutopilotAction::SetActuatorsConfig(_) => "Error saving hardware setup",
+        AutopilotAction::CommitCalibrationSession => "Failed to save focus calibration",
+        AutopilotAction::ImportCalibrationCsv(_) => "Failed to import focus calibration",
+        AutopilotAction::SwitchCalibrationSet(_) => "Failed to switch focus calibration",
         _ => "Autopilot control failed",
     }
 }
@@ -508,7 +536,7 @@ fn arm_loading_timeout(entry: &mut Entry, camera_uuid: Uuid) {
     ));
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "autopilot"))]
 mod tests {
     use super::*;
     use autopilot::api::Action as AutopilotAction;
@@ -10,7 +10,10 @@ use std::{
     time::Duration,
 };
 
-use br4kcam_api::{CameraConnectivity, ExpectedCamera, McmHealth, SystemHealth};
+use br4kcam_api::{
+    AutopilotHealth, CameraConnectivity, Diagnostics, ExpectedCamera, LuaScriptStatus, McmHealth,
+    ParameterDrift, SystemHealth,
+};
 use once_cell::sync::OnceCell;
 use tokio::net::TcpStream;
 use tokio::sync::{Notify, broadcast};
@@ -42,16 +45,32 @@ struct StitchHealthParams<'a> {
     mcm_consecutive_failures: u32,
     cameras_discovered: usize,
     configured: &'a [Uuid],
-    autopilot: br4kcam_api::AutopilotHealth,
+    standalone: bool,
+    autopilot: AutopilotHealth,
     autopilot_detail: Option<String>,
     lua_scripting_disabled: bool,
-    lua_script: br4kcam_api::LuaScriptStatus,
+    lua_script: LuaScriptStatus,
     lua_script_detail: Option<String>,
-    parameter_drifts: Option<Vec<br4kcam_api::ParameterDrift>>,
-    diagnostics: br4kcam_api::Diagnostics,
+    parameter_drifts: Option<Vec<ParameterDrift>>,
+    unknown_vehicle: Option<String>,
+    diagnostics: Diagnostics,
     state_events_lagged: u64,
 }
 
+/// The autopilot's share of [`StitchHealthParams`].
+#[derive(Default)]
+struct AutopilotReport {
+    standalone: bool,
+    autopilot: AutopilotHealth,
+    autopilot_detail: Option<String>,
+    lua_scripting_disabled: bool,
+    lua_script: LuaScriptStatus,
+    lua_script_detail: Option<String>,
+    parameter_drifts: Option<Vec<ParameterDrift>>,
+    unknown_vehicle: Option<String>,
+    diagnostics: Diagnostics,
+}
+
 pub(crate) struct ClassifyEvidence {
     pub in_mcm_list: bool,
     pub expected: bool,
@@ -100,7 +119,7 @@ pub(crate) fn classify_table(evidence: ClassifyEvidence) -> CameraConnectivity {
 
 /// Remember the last hostname MCM reported for `camera_uuid`.
 pub(crate) fn observe_camera(camera_uuid: Uuid, hostname: Ipv4Addr) {
-    mcm_client::remember_hostname(camera_uuid, hostname);
+    cameras::remember_hostname(camera_uuid, hostname);
     let mut guard = cameras().lock().expect("connectivity lock");
     guard
         .entry(camera_uuid)
@@ -112,7 +131,7 @@ pub(crate) fn publish(camera_uuid: Uuid, next: CameraConnectivity, reason: &str)
     // While MCM is down, list membership and HTTP probes are inconclusive — do not
     // latch Unreachable/Unresponsive that would flash as "camera not responding"
     // the moment MCM returns.
-    let next = if mcm_client::health().state == McmHealth::Down
+    let next = if mcm_health().0 == McmHealth::Down
         && !matches!(
             next,
             CameraConnectivity::Online | CameraConnectivity::Unknown
@@ -136,7 +155,7 @@ pub(crate) fn publish(camera_uuid: Uuid, next: CameraConnectivity, reason: &str)
             .entry(camera_uuid)
             .or_insert_with(|| Entry::new(CameraConnectivity::Unknown));
         let from = entry.connectivity;
-        let hostname = mcm_client::cached_hostname(&camera_uuid)
+        let hostname = cameras::cached_hostname(&camera_uuid)
             .map(|ip| ip.to_string())
             .unwrap_or_else(|| "?".to_string());
 
@@ -226,6 +245,7 @@ fn get(camera_uuid: Uuid) -> CameraConnectivity {
 }
 
 /// Drop connectivity state after the user forgets a configured camera and push health.
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug", skip_all, fields(%camera_uuid))]
 pub(crate) fn forget_camera(camera_uuid: Uuid) {
     cameras()
@@ -238,12 +258,24 @@ pub(crate) fn forget_camera(camera_uuid: Uuid) {
 
 /// True when `camera_uuid` is configured in persisted actuator settings.
 pub(crate) async fn is_expected(camera_uuid: Uuid) -> bool {
-    autopilot::configured_cameras().await.contains(&camera_uuid)
+    configured_cameras().await.contains(&camera_uuid)
+}
+
+/// Cameras with persisted actuator settings.
+#[cfg(feature = "autopilot")]
+pub(crate) async fn configured_cameras() -> Vec<Uuid> {
+    autopilot::configured_cameras().await
+}
+
+/// Without autopilot support no actuator settings are kept, so no camera is expected.
+#[cfg(not(feature = "autopilot"))]
+pub(crate) async fn configured_cameras() -> Vec<Uuid> {
+    Vec::new()
 }
 
 /// True when a WebSocket may subscribe to `camera_uuid` even if MCM does not list it.
 pub(crate) async fn subscribe_allowed(camera_uuid: Uuid, connection_id: ConnectionId) -> bool {
-    mcm_client::get_camera(&camera_uuid).await.is_some()
+    cameras::get_camera(&camera_uuid).await.is_some()
         || is_expected(camera_uuid).await
         || camera_state::has_interest(camera_uuid)
         || camera_state::connection_subscribed(connection_id, camera_uuid)
@@ -251,7 +283,7 @@ pub(crate) async fn subscribe_allowed(camera_uuid: Uuid, connection_id: Connecti
 }
 
 pub(crate) async fn last_hostname(camera_uuid: Uuid) -> Option<Ipv4Addr> {
-    mcm_client::camera_address(&camera_uuid).await
+    cameras::camera_address(&camera_uuid).await
 }
 
 fn stitch_system_health(
@@ -280,14 +312,16 @@ fn stitch_system_health(
             .flatten(),
         cameras_discovered: params.cameras_discovered,
         expected_missing,
+        standalone: params.standalone,
         autopilot: params.autopilot,
-        autopilot_detail: (params.autopilot != br4kcam_api::AutopilotHealth::Online)
+        autopilot_detail: (params.autopilot != AutopilotHealth::Online)
             .then_some(params.autopilot_detail)
             .flatten(),
         lua_scripting_disabled: params.lua_scripting_disabled,
         lua_script: params.lua_script,
         lua_script_detail: params.lua_script_detail,
         parameter_drifts: params.parameter_drifts,
+        unknown_vehicle: params.unknown_vehicle,
         diagnostics,
     }
 }
@@ -295,44 +329,44 @@ fn stitch_system_health(
 /// Backend-wide health snapshot for WebSocket push and `GET /v1/health`.
 #[instrument(level = "debug", skip_all)]
 pub(crate) async fn system_health() -> SystemHealth {
-    let mcm = mcm_client::health();
-    let discovered = mcm_client::cameras().await;
-    let configured = autopilot::configured_cameras().await;
-    let (autopilot, autopilot_detail) = autopilot::health();
-    let (lua_script, lua_script_detail) = autopilot::lua_script_status();
-    let parameter_drifts = {
-        let drifts: Vec<_> = autopilot::parameter_drifts()
-            .into_iter()
-            .map(|drift| br4kcam_api::ParameterDrift {
-                name: drift.name,
-                expected: drift.expected,
-                actual: drift.actual,
-            })
-            .collect();
-        (!drifts.is_empty()).then_some(drifts)
-    };
+    let (mcm_state, mcm_detail, mcm_consecutive_failures) = mcm_health();
+    let discovered = cameras::cameras().await;
+    let configured = configured_cameras().await;
+    let AutopilotReport {
+        standalone,
+        autopilot,
+        autopilot_detail,
+        lua_scripting_disabled,
+        lua_script,
+        lua_script_detail,
+        parameter_drifts,
+        unknown_vehicle,
+        diagnostics,
+    } = autopilot_report();
 
     let mut hostnames = HashMap::new();
     for uuid in &configured {
-        if let Some(hostname) = mcm_client::camera_address(uuid).await {
+        if let Some(hostname) = cameras::camera_address(uuid).await {
             hostnames.insert(*uuid, hostname.to_string());
         }
     }
 
     stitch_system_health(
         StitchHealthParams {
-            mcm_state: mcm.state,
-            mcm_detail: mcm.detail,
-            mcm_consecutive_failures: mcm.consecutive_failures,
+            mcm_state,
+            mcm_detail,
+            mcm_consecutive_failures,
             cameras_discovered: discovered.len(),
             configured: &configured,
+            standalone,
             autopilot,
             autopilot_detail,
-            lua_scripting_disabled: autopilot::lua_scripting_disabled(),
+            lua_scripting_disabled,
             lua_script,
             lua_script_detail,
             parameter_drifts,
-            diagnostics: autopilot::diagnostics(),
+            unknown_vehicle,
+            diagnostics,
             state_events_lagged: camera_state::state_events_lagged(),
         },
         |uuid| discovered.contains_key(&uuid),
@@ -340,6 +374,56 @@ pub(crate) async fn system_health() -> SystemHealth {
     )
 }
 
+/// MCM state, detail and consecutive failing polls.
+#[cfg(feature = "mcm")]
+fn mcm_health() -> (McmHealth, Option<String>, u32) {
+    let mcm = mcm_client::health();
+    (mcm.state, mcm.detail, mcm.consecutive_failures)
+}
+
+#[cfg(not(feature = "mcm"))]
+fn mcm_health() -> (McmHealth, Option<String>, u32) {
+    (McmHealth::Disabled, None, 0)
+}
+
+#[cfg(feature = "autopilot")]
+fn autopilot_report() -> AutopilotReport {
+    let (autopilot, autopilot_detail) = autopilot::health();
+    let (lua_script, lua_script_detail) = autopilot::lua_script_status();
+    let drifts: Vec<_> = autopilot::parameter_drifts()
+        .into_iter()
+        .map(|drift| ParameterDrift {
+            name: drift.name,
+            expected: drift.expected,
+            actual: drift.actual,
+        })
+        .collect();
+
+    AutopilotReport {
+        standalone: autopilot::platform() == autopilot::Platform::Standalone,
+        autopilot,
+        autopilot_detail,
+        lua_scripting_disabled: autopilot::lua_scripting_disabled(),
+        lua_script,
+        lua_script_detail,
+        parameter_drifts: (!drifts.is_empty()).then_some(drifts),
+        unknown_vehicle: autopilot::unknown_vehicle(),
+        diagnostics: autopilot::diagnostics(),
+    }
+}
+
+#[cfg(not(feature = "autopilot"))]
+fn autopilot_report() -> AutopilotReport {
+    AutopilotReport {
+        autopilot: AutopilotHealth::Disabled,
+        diagnostics: Diagnostics {
+            backend_version: env!("CARGO_PKG_VERSION").to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
 /// Health with the fields that move on every sample blanked, so equality means
 /// "nothing a client would render has changed".
 pub(crate) fn health_comparable(mut health: SystemHealth) -> SystemHealth {
@@ -417,9 +501,10 @@ pub(crate) fn has_pending_debounce() -> bool {
         .any(|entry| entry.pending.is_some())
 }
 
+#[cfg(feature = "mcm")]
 async fn sync_mcm_camera_errors() {
-    let discovered = mcm_client::cameras().await;
-    let configured = autopilot::configured_cameras().await;
+    let discovered = cameras::cameras().await;
+    let configured = configured_cameras().await;
     let uuids: HashSet<Uuid> = discovered
         .keys()
         .chain(configured.iter())
@@ -442,20 +527,21 @@ fn ensure_fan_in() {
     }
 
     tokio::spawn(async {
-        let mut mcm_rx = mcm_client::subscribe_health();
-        let mut autopilot_rx = autopilot::subscribe_health();
-        let mut cameras_rx = mcm_client::subscribe_cameras();
+        let mut mcm_rx = subscribe_mcm_health();
+        let mut autopilot_rx = subscribe_autopilot_health();
+        let mut cameras_rx = cameras::subscribe_cameras();
 
         loop {
             tokio::select! {
                 _ = HEALTH_DIRTY.notified() => {}
-                _ = mcm_rx.recv() => {
-                    if mcm_client::health().state == McmHealth::Online {
+                _ = changed(&mut mcm_rx) => {
+                    if mcm_health().0 == McmHealth::Online {
                         reset_after_mcm_recovery();
                     }
                 }
-                _ = autopilot_rx.recv() => {}
+                _ = changed(&mut autopilot_rx) => {}
                 _ = cameras_rx.recv() => {
+                    #[cfg(feature = "mcm")]
                     sync_mcm_camera_errors().await;
                 }
             }
@@ -464,6 +550,36 @@ fn ensure_fan_in() {
     });
 }
 
+#[cfg(feature = "mcm")]
+fn subscribe_mcm_health() -> Option<broadcast::Receiver<()>> {
+    Some(mcm_client::subscribe_health())
+}
+
+#[cfg(not(feature = "mcm"))]
+fn subscribe_mcm_health() -> Option<broadcast::Receiver<()>> {
+    None
+}
+
+#[cfg(feature = "autopilot")]
+fn subscribe_autopilot_health() -> Option<broadcast::Receiver<()>> {
+    Some(autopilot::subscribe_health())
+}
+
+#[cfg(not(feature = "autopilot"))]
+fn subscribe_autopilot_health() -> Option<broadcast::Receiver<()>> {
+    None
+}
+
+/// Next notification on `receiver`; never resolves for a subsystem this build lacks.
+async fn changed(receiver: &mut Option<broadcast::Receiver<()>>) {
+    match receiver {
+        Some(receiver) => {
+            let _ = receiver.recv().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
 #[cfg(test)]
 fn connectivity_test_lock() -> std::sync::MutexGuard<'static, ()> {
     static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
@@ -472,8 +588,6 @@ fn connectivity_test_lock() -> std::sync::MutexGuard<'static, ()> {
 
 #[cfg(test)]
 mod tests {
-    use br4kcam_api::{AutopilotHealth, Diagnostics};
-
     use super::*;
 
     // Process-lifetime OnceCell globals are not reset between tests; use unique
@@ -494,6 +608,7 @@ mod tests {
                 mcm_consecutive_failures: 0,
                 cameras_discovered: 1,
                 configured: &configured,
+                standalone: false,
                 autopilot: AutopilotHealth::Online,
                 autopilot_detail: Some("suppressed".into()),
                 lua_scripting_disabled: false,
@@ -504,6 +619,7 @@ mod tests {
                     expected: 33.0,
                     actual: 0.0,
                 }]),
+                unknown_vehicle: None,
                 diagnostics: Diagnostics::default(),
                 state_events_lagged: 0,
             },
@@ -538,12 +654,14 @@ mod tests {
                 mcm_consecutive_failures: 3,
                 cameras_discovered: 0,
                 configured: &[],
+                standalone: false,
                 autopilot: AutopilotHealth::MavlinkDown,
                 autopilot_detail: Some("no frames".into()),
                 lua_scripting_disabled: false,
                 lua_script: br4kcam_api::LuaScriptStatus::Ok,
                 lua_script_detail: None,
                 parameter_drifts: None,
+                unknown_vehicle: None,
                 diagnostics: Diagnostics::default(),
                 state_events_lagged: 0,
             },
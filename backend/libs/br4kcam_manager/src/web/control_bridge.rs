@@ -1,6 +1,7 @@
 //! Single entry point for camera and autopilot mutations, so every caller
 //! (REST and WebSocket alike) produces the same UI overlay and state updates.
 
+#[cfg(feature = "autopilot")]
 use autopilot::api::{Action as AutopilotAction, ActuatorsControl};
 use br4kcam_commands::{Action as CameraAction, CameraControl};
 use serde_json::Value;
@@ -39,7 +40,7 @@ pub(crate) async fn camera_control(camera_control: CameraControl) -> Result<Valu
     let camera_uuid = camera_control.camera_uuid;
     // Addressability, not discovery: a camera absent from the MCM list while ONVIF
     // rediscovers still answers its HTTP API at the hostname we last saw it on.
-    if mcm_client::camera_address(&camera_uuid).await.is_none() {
+    if cameras::camera_address(&camera_uuid).await.is_none() {
         return Err(ControlError::unknown_camera());
     }
     let action = camera_control.action.clone();
@@ -71,12 +72,13 @@ pub(crate) async fn camera_control(camera_control: CameraControl) -> Result<Valu
 }
 
 /// Run an autopilot control, driving the shared UI overlay and state stream.
+#[cfg(feature = "autopilot")]
 #[tracing::instrument(level = "debug", skip_all, fields(%actuators_control.camera_uuid))]
 pub(crate) async fn autopilot_control(
     actuators_control: ActuatorsControl,
 ) -> Result<Value, ControlError> {
     let camera_uuid = actuators_control.camera_uuid;
-    if mcm_client::camera_address(&camera_uuid).await.is_none()
+    if cameras::camera_address(&camera_uuid).await.is_none()
         && !connectivity::is_expected(camera_uuid).await
         && !matches!(
             actuators_control.action,
@@ -93,7 +95,7 @@ pub(crate) async fn autopilot_control(
             camera_ui::finish_autopilot_action(camera_uuid, &action);
             camera_state::emit_autopilot_control_update(camera_uuid, &action, &value);
             if matches!(action, AutopilotAction::ForgetActuatorsConfig) {
-                mcm_client::forget_hostname(camera_uuid);
+                cameras::forget_hostname(camera_uuid);
                 connectivity::forget_camera(camera_uuid);
             }
             Ok(value)
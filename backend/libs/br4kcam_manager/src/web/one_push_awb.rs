@@ -215,7 +215,7 @@ pub(crate) fn on_control_success(camera_uuid: Uuid, action: &CameraAction) {
             let span = Span::current();
             tokio::spawn(
                 async move {
-                    let cameras = mcm_client::cameras().await;
+                    let cameras = cameras::cameras().await;
                     for uuid in cameras.keys().copied() {
                         begin_after_trigger(uuid);
                     }
@@ -1,7 +1,8 @@
 use axum::response::IntoResponse;
+use schemars::JsonSchema;
 use serde::Serialize;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 /// https://blueos.cloud/docs/latest/development/extensions/#web-interface-http-server
 pub struct ServerMetadata {
     pub name: &'static str,
@@ -25,7 +26,7 @@ pub struct ServerMetadata {
     pub extras: Option<Extras>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, JsonSchema)]
 pub struct Extras {
     pub cockpit: &'static str,
 }
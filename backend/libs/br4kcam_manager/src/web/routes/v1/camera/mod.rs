@@ -1,28 +1,75 @@
-use axum::{
-    Json, Router,
-    http::StatusCode,
-    response::IntoResponse,
-    routing::{get, post},
-};
-use br4kcam_commands::CameraControl;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+#[cfg(feature = "mcm")]
+use axum::extract::Path;
+use axum::{Json, http::StatusCode, response::IntoResponse};
+use br4kcam_commands::{CameraControl, CameraControlResult};
+use cameras::Cameras;
+#[cfg(feature = "mcm")]
+use mcm_client::StreamPreferences;
 use tracing::*;
+#[cfg(feature = "mcm")]
+use uuid::Uuid;
 
+use super::docs::{ApiRouter, Operation};
 use crate::web::control_bridge;
 
 #[instrument(level = "trace")]
-pub fn router() -> Router {
-    Router::new()
-        .route("/list", get(br4kcam_commands::list))
-        .route("/control", post(control))
-        .layer(TraceLayer::new_for_http())
-        .layer(CorsLayer::permissive())
+pub fn router() -> ApiRouter {
+    let api = ApiRouter::new()
+        .get(
+            "/list",
+            br4kcam_commands::list,
+            Operation::new(
+                "camera",
+                "List the cameras discovered through MCM or registered by hand, by UUID",
+            )
+            .json::<Cameras>("200", "Cameras"),
+        )
+        .post(
+            "/control",
+            control,
+            Operation::new("camera", "Send a command to a camera")
+                .request::<CameraControl>()
+                .json::<CameraControlResult>(
+                    "200",
+                    "Command result, shaped after the requested action",
+                )
+                .text("default", "Why the action failed"),
+        );
+
+    #[cfg(feature = "mcm")]
+    let api = api
+        .get(
+            "/{uuid}/stream",
+            stream,
+            Operation::new(
+                "camera",
+                "How MCM streams the camera: channel, format and endpoints",
+            )
+            .path_parameter::<Uuid>("uuid")
+            .json::<StreamPreferences>("200", "Stream preferences")
+            .text("404", "Unknown camera"),
+        )
+        .post(
+            "/{uuid}/stream",
+            set_stream,
+            Operation::new(
+                "camera",
+                "Change how MCM streams the camera; its stream is recreated to match",
+            )
+            .path_parameter::<Uuid>("uuid")
+            .request::<StreamPreferences>()
+            .json::<StreamPreferences>("200", "Saved stream preferences")
+            .text("400", "Why the preferences cannot be streamed")
+            .text("404", "Unknown camera"),
+        );
+
+    api
 }
 
 #[instrument(level = "debug")]
 async fn control(Json(camera_control): Json<CameraControl>) -> impl IntoResponse {
     match control_bridge::camera_control(camera_control).await {
-        Ok(value) => (StatusCode::OK, value.to_string()).into_response(),
+        Ok(value) => Json(value).into_response(),
         Err(error) => {
             let status =
                 StatusCode::from_u16(error.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
@@ -30,3 +77,39 @@ async fn control(Json(camera_control): Json<CameraControl>) -> impl IntoResponse
         }
     }
 }
+
+#[cfg(feature = "mcm")]
+#[instrument(level = "debug")]
+async fn stream(Path(camera_uuid): Path<Uuid>) -> impl IntoResponse {
+    if cameras::get_camera(&camera_uuid).await.is_none() {
+        return (StatusCode::NOT_FOUND, "Unknown camera").into_response();
+    }
+
+    match mcm_client::stream_preferences(&camera_uuid).await {
+        Ok(preferences) => Json(preferences).into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{error:#}")).into_response(),
+    }
+}
+
+#[cfg(feature = "mcm")]
+#[instrument(level = "debug")]
+async fn set_stream(
+    Path(camera_uuid): Path<Uuid>,
+    Json(preferences): Json<StreamPreferences>,
+) -> impl IntoResponse {
+    if cameras::get_camera(&camera_uuid).await.is_none() {
+        return (StatusCode::NOT_FOUND, "Unknown camera").into_response();
+    }
+
+    if let Err(error) = preferences.validate() {
+        return (StatusCode::BAD_REQUEST, format!("{error:#}")).into_response();
+    }
+
+    match mcm_client::set_stream_preferences(camera_uuid, preferences.clone()).await {
+        Ok(()) => Json(preferences).into_response(),
+        Err(error) => {
+            warn!("Failed to save the stream preferences of {camera_uuid}: {error:#?}");
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("{error:#}")).into_response()
+        }
+    }
+}
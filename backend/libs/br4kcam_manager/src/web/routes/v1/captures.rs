@@ -0,0 +1,46 @@
+use axum::{
+    Json,
+    extract::Path,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use tracing::*;
+
+use super::docs::{ApiRouter, Operation};
+use crate::image_capture::{self, StoredCapture};
+
+#[instrument(level = "trace")]
+pub fn router() -> ApiRouter {
+    ApiRouter::new()
+        .get(
+            "/",
+            list,
+            Operation::new("captures", "List the stills captured on MAVLink request")
+                .json::<Vec<StoredCapture>>("200", "Stored captures")
+                .text("500", "Why the captures could not be listed"),
+        )
+        .get(
+            "/{id}",
+            download,
+            Operation::new("captures", "Download a capture")
+                .path_parameter::<String>("id")
+                .binary("200", "image/jpeg", "The captured image")
+                .text("404", "Unknown capture"),
+        )
+}
+
+#[instrument(level = "debug")]
+async fn list() -> impl IntoResponse {
+    match image_capture::list().await {
+        Ok(captures) => Json(captures).into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{error:?}")).into_response(),
+    }
+}
+
+#[instrument(level = "debug")]
+async fn download(Path(id): Path<String>) -> impl IntoResponse {
+    match image_capture::read(&id).await {
+        Ok(image) => ([(header::CONTENT_TYPE, "image/jpeg")], image).into_response(),
+        Err(error) => (StatusCode::NOT_FOUND, format!("{error:?}")).into_response(),
+    }
+}
@@ -1,12 +1,26 @@
+use std::collections::HashSet;
+
+#[cfg(feature = "autopilot")]
+use autopilot::{
+    api::{Action as AutopilotAction, ActuatorsControl, ActuatorsState, ActuatorsStep, PresetName},
+    parameters::ActuatorsParameters,
+};
 use axum::response::IntoResponse;
 use br4kcam_commands::{
-    CameraControl, protocol::display::advanced_display::AdvancedParameterSetting,
+    CameraControl,
+    protocol::display::advanced_display::{
+        AdvancedDisplayColorBlackValue, AdvancedDisplayInfrDetectModeValue,
+        AdvancedParameterSetting,
+    },
 };
-use mcm_client::Cameras;
+use cameras::Cameras;
+use indexmap::IndexMap;
+use schemars::JsonSchema;
 use serde::Serialize;
 use serde_json::json;
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CockpitExtras {
     pub target_system: String,
@@ -14,10 +28,11 @@ pub struct CockpitExtras {
     pub widgets: Vec<CockpitIframeWidget>,
     pub actions: Vec<CockpitAction>,
     pub joystick_suggestions: Vec<JoystickMapSuggestion>,
+    pub data_lake_variables: Vec<CockpitDataLakeVariable>,
 }
 
 /// Widget configuration object as received from BlueOS or anany external source
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CockpitIframeWidget {
     /// Name of the widget, this is displayed on edit mode widget browser
@@ -36,7 +51,31 @@ pub struct CockpitIframeWidget {
     pub use_extension_path_as_base_url: bool,
 }
 
-#[derive(Debug, Serialize, Clone)]
+/// Data-lake variable fed by this extension. Its id is the name of the MAVLink
+/// `NAMED_VALUE_FLOAT` carrying its value, which Cockpit stores under that name.
+#[derive(Debug, Serialize, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CockpitDataLakeVariable {
+    /// ID for this variable
+    pub id: String,
+    /// Name for this variable
+    pub name: String,
+    /// Type of the variable's values
+    #[serde(rename = "type")]
+    pub kind: CockpitDataLakeVariableType,
+    /// Description showing to the user
+    pub description: String,
+}
+
+/// `NAMED_VALUE_FLOAT`s only carry numbers.
+#[derive(Debug, Serialize, Clone, Copy, JsonSchema)]
+#[cfg_attr(not(feature = "autopilot"), allow(dead_code))]
+#[serde(rename_all = "lowercase")]
+pub enum CockpitDataLakeVariableType {
+    Number,
+}
+
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CockpitAction {
     /// ID for this Action
@@ -52,13 +91,13 @@ pub struct CockpitAction {
     pub description: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 #[serde(tag = "type", content = "config", rename_all = "kebab-case")]
 pub enum CockpitActionType {
     HttpRequest(HttpRequestAction),
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct HttpRequestAction {
     name: String,
@@ -69,7 +108,7 @@ pub struct HttpRequestAction {
     body: String,
 }
 
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Clone, Copy, JsonSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum HttpRequestMethod {
     GET,
@@ -81,7 +120,7 @@ pub enum HttpRequestMethod {
 
 /// Joystick map suggestion from BlueOS extensions
 /// Example of use: https://github.com/rafaellehmkuhl/MockBlueOsExtension/blob/913eb0a978159bdb2c4e4b610044f1c8082755ab/src/backend/main.py#L147
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct JoystickMapSuggestion {
     /// ID for this suggestion
@@ -99,13 +138,13 @@ pub struct JoystickMapSuggestion {
 }
 
 /// Vehicle type, from mavlink. Should match mavlink::ardupilotmega::MavType
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Clone, Copy, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TargetVehicleType {
     MavTypeSubmarine,
 }
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ButtonMappingSuggestion {
     /// ID for this button suggestion
@@ -126,7 +165,7 @@ pub struct ButtonMappingSuggestion {
 
 /// Available joystick protocols.
 /// Each protocol is expected to have it's own way of doing thing, including mapping, limiting, communicating, etc.
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Clone, Copy, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum JoystickProtocol {
     CockpitModifierKey,
@@ -137,7 +176,7 @@ pub enum JoystickProtocol {
 }
 
 /// Modifier keys
-#[derive(Debug, Serialize, Clone, Copy)]
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, Hash, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub enum CockpitModifierKeyOption {
     Regular,
@@ -145,14 +184,16 @@ pub enum CockpitModifierKeyOption {
 }
 
 pub async fn cockpit_extras() -> impl IntoResponse {
-    let cameras = mcm_client::cameras().await;
+    let cameras = cameras::cameras().await;
+    let actuators = configured_actuators(&cameras).await;
 
     let cockpit_extras = CockpitExtras {
         target_system: "Cockpit".to_string(),
         target_cockpit_api_version: "1.0.0".to_string(),
         widgets: widgets(&cameras),
-        actions: actions(&cameras),
-        joystick_suggestions: joystick_suggestions(&cameras),
+        actions: actions(&cameras, &actuators),
+        joystick_suggestions: joystick_suggestions(&actuators),
+        data_lake_variables: data_lake_variables(&cameras),
     };
 
     let json = serde_json::to_string_pretty(&cockpit_extras).unwrap();
@@ -160,6 +201,31 @@ pub async fn cockpit_extras() -> impl IntoResponse {
     json.into_response()
 }
 
+#[cfg(feature = "autopilot")]
+fn data_lake_variables(cameras: &Cameras) -> Vec<CockpitDataLakeVariable> {
+    cameras
+        .iter()
+        .flat_map(|(camera_uuid, camera)| {
+            autopilot::DataLakeValue::ALL
+                .into_iter()
+                .filter_map(|value| {
+                    Some(CockpitDataLakeVariable {
+                        id: autopilot::data_lake_name(*camera_uuid, value)?,
+                        name: format!("4K Cam {} ({})", value.label(), camera.hostname),
+                        kind: CockpitDataLakeVariableType::Number,
+                        description: format!("{} of {}", value.description(), camera.hostname),
+                    })
+                })
+        })
+        .collect()
+}
+
+/// The values are published by the autopilot's MAVLink component.
+#[cfg(not(feature = "autopilot"))]
+fn data_lake_variables(_cameras: &Cameras) -> Vec<CockpitDataLakeVariable> {
+    Vec::new()
+}
+
 fn widgets(cameras: &Cameras) -> Vec<CockpitIframeWidget> {
     cameras
         .iter()
@@ -175,73 +241,347 @@ fn widgets(cameras: &Cameras) -> Vec<CockpitIframeWidget> {
         .collect()
 }
 
-fn actions(cameras: &Cameras) -> Vec<CockpitAction> {
-    let mut actions = cameras
-        .iter()
-        .flat_map(|(camera_uuid, camera)| {
-            let name: String = format!("4K Cam One-Push White Balance ({})", camera.hostname);
-
-            vec![CockpitAction {
-                id: format!("br4kcam_white_balance_{camera_uuid}"),
-                name: name.clone(),
-                action_type: CockpitActionType::HttpRequest(HttpRequestAction {
-                    name,
-                    url:
-                        "http://{{ vehicle-address }}/extensionv2/br4kcammanager/v1/camera/control"
-                            .to_string(),
-                    method: HttpRequestMethod::POST,
-                    headers: json!({
-                        "Content-Type": "application/json",
-                    }),
-                    url_params: json!({}),
-                    body: json!(CameraControl {
-                        camera_uuid: *camera_uuid,
-                        action: br4kcam_commands::Action::SetImageAdjustmentEx(
-                            AdvancedParameterSetting {
-                                once_awb: Some(1),
-                                ..Default::default()
-                            }
-                        ),
-                    })
-                    .to_string(),
-                }),
-                description: format!(
-                    "Triggers one-push white balance on {} only.",
-                    camera.hostname
-                ),
-                version: env!("CARGO_PKG_VERSION").to_string(), // TODO: freeze this once we settle with a button layout
-            }]
-        })
-        .collect::<Vec<CockpitAction>>();
+/// Version of every action's request. Action ids and requests are what Cockpit bindings
+/// point at: bump this only when a request changes meaning, never on a regular release.
+const ACTIONS_VERSION: &str = "1.0.0";
+const CAMERA_CONTROL_URL: &str =
+    "http://{{ vehicle-address }}/extensionv2/br4kcammanager/v1/camera/control";
+#[cfg(feature = "autopilot")]
+const AUTOPILOT_CONTROL_URL: &str =
+    "http://{{ vehicle-address }}/extensionv2/br4kcammanager/v1/autopilot/control";
+
+/// Zoom step per button press, in percent.
+#[cfg(feature = "autopilot")]
+const ZOOM_STEP: f32 = 10.0;
+/// Focus step per button press, in percent.
+#[cfg(feature = "autopilot")]
+const FOCUS_STEP: f32 = 5.0;
+/// Tilt step per button press, in degrees.
+#[cfg(feature = "autopilot")]
+const TILT_STEP: f32 = 10.0;
+
+/// A camera with actuators configured, as far as Cockpit is concerned. Only built with
+/// autopilot support; without it no camera has actuators.
+#[cfg_attr(not(feature = "autopilot"), allow(dead_code))]
+struct ConfiguredActuators {
+    hostname: String,
+    axes: ActuatorAxes,
+    /// Level when the mount can reach it, otherwise the middle of its travel, in degrees.
+    tilt_centre: f32,
+    presets: Vec<String>,
+}
+
+/// Axes a camera's actuators can move, from its channel and mount ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ActuatorAxes {
+    zoom: bool,
+    focus: bool,
+    tilt: bool,
+}
+
+#[cfg(feature = "autopilot")]
+impl From<&ActuatorsParameters> for ActuatorAxes {
+    fn from(parameters: &ActuatorsParameters) -> Self {
+        Self {
+            zoom: parameters.zoom_channel_min != parameters.zoom_channel_max,
+            focus: parameters.focus_channel_min != parameters.focus_channel_max,
+            tilt: parameters.tilt_mnt_pitch_min != parameters.tilt_mnt_pitch_max,
+        }
+    }
+}
+
+/// Cameras with actuators configured, in `cameras` order.
+#[cfg(feature = "autopilot")]
+async fn configured_actuators(cameras: &Cameras) -> IndexMap<Uuid, ConfiguredActuators> {
+    let mut configured = IndexMap::new();
+
+    for (camera_uuid, camera) in cameras.iter() {
+        let Some(parameters) = autopilot::actuators_parameters(*camera_uuid).await else {
+            continue;
+        };
+        let presets = autopilot::actuators_presets(*camera_uuid)
+            .await
+            .map(|presets| presets.into_keys().collect())
+            .unwrap_or_default();
+
+        configured.insert(
+            *camera_uuid,
+            ConfiguredActuators {
+                hostname: camera.hostname.to_string(),
+                axes: ActuatorAxes::from(&parameters),
+                tilt_centre: tilt_centre(&parameters),
+                presets,
+            },
+        );
+    }
+
+    configured
+}
+
+#[cfg(not(feature = "autopilot"))]
+async fn configured_actuators(_cameras: &Cameras) -> IndexMap<Uuid, ConfiguredActuators> {
+    IndexMap::new()
+}
+
+#[cfg(feature = "autopilot")]
+fn tilt_centre(parameters: &ActuatorsParameters) -> f32 {
+    let (pitch_min, pitch_max) = (
+        parameters
+            .tilt_mnt_pitch_min
+            .min(parameters.tilt_mnt_pitch_max),
+        parameters
+            .tilt_mnt_pitch_min
+            .max(parameters.tilt_mnt_pitch_max),
+    );
+    if (pitch_min..=pitch_max).contains(&0) {
+        0.0
+    } else {
+        (pitch_min + pitch_max) as f32 / 2.0
+    }
+}
+
+fn action_id(kind: &str, camera_uuid: Uuid) -> String {
+    format!("br4kcam_{kind}_{camera_uuid}")
+}
+
+#[cfg_attr(not(feature = "autopilot"), allow(unused_variables))]
+fn actions(
+    cameras: &Cameras,
+    actuators: &IndexMap<Uuid, ConfiguredActuators>,
+) -> Vec<CockpitAction> {
+    let mut actions = Vec::new();
+
+    for (camera_uuid, camera) in cameras.iter() {
+        actions.extend(camera_actions(*camera_uuid, &camera.hostname.to_string()));
+
+        #[cfg(feature = "autopilot")]
+        if let Some(configured) = actuators.get(camera_uuid) {
+            actions.extend(actuator_actions(*camera_uuid, configured));
+        }
+    }
 
     actions.insert(
         0,
-        CockpitAction {
-            id: "br4kcam_white_balance_all".to_string(),
-            name: "4K Cam One-Push White Balance (All)".to_string(),
-            action_type: CockpitActionType::HttpRequest(HttpRequestAction {
-                name: "4K Cam One-Push White Balance (All)".to_string(),
-                url: "http://{{ vehicle-address }}/extensionv2/br4kcammanager/v1/camera/control"
-                    .to_string(),
-                method: HttpRequestMethod::POST,
-                headers: json!({
-                    "Content-Type": "application/json",
-                }),
-                url_params: json!({}),
-                body: json!({
-                    "action": "setImageAdjustmentExAll",
-                    "json": { "onceAWB": 1 },
-                })
-                .to_string(),
+        http_post_action(
+            "br4kcam_white_balance_all".to_string(),
+            "4K Cam One-Push White Balance (All)".to_string(),
+            CAMERA_CONTROL_URL,
+            json!({
+                "action": "setImageAdjustmentExAll",
+                "json": { "onceAWB": 1 },
             }),
-            description: "Triggers one-push white balance on every configured 4K Cam.".to_string(),
-            version: env!("CARGO_PKG_VERSION").to_string(),
-        },
+            "Triggers one-push white balance on every configured 4K Cam.".to_string(),
+        ),
     );
+    #[cfg(feature = "autopilot")]
+    actions.extend(preset_actions(actuators));
     actions
 }
 
-fn joystick_suggestions(_cameras: &Cameras) -> Vec<JoystickMapSuggestion> {
+fn http_post_action(
+    id: String,
+    name: String,
+    url: &str,
+    body: serde_json::Value,
+    description: String,
+) -> CockpitAction {
+    CockpitAction {
+        id,
+        name: name.clone(),
+        action_type: CockpitActionType::HttpRequest(HttpRequestAction {
+            name,
+            url: url.to_string(),
+            method: HttpRequestMethod::POST,
+            headers: json!({
+                "Content-Type": "application/json",
+            }),
+            url_params: json!({}),
+            body: body.to_string(),
+        }),
+        version: ACTIONS_VERSION.to_string(),
+        description,
+    }
+}
+
+/// Actions every camera supports, whatever its actuators.
+fn camera_actions(camera_uuid: Uuid, hostname: &str) -> Vec<CockpitAction> {
+    let action = |id: &str, name: &str, action: br4kcam_commands::Action, description: &str| {
+        http_post_action(
+            action_id(id, camera_uuid),
+            format!("4K Cam {name} ({hostname})"),
+            CAMERA_CONTROL_URL,
+            json!(CameraControl {
+                camera_uuid,
+                action,
+            }),
+            format!("{description} on {hostname} only."),
+        )
+    };
+
+    vec![
+        action(
+            "white_balance",
+            "One-Push White Balance",
+            br4kcam_commands::Action::SetImageAdjustmentEx(AdvancedParameterSetting {
+                once_awb: Some(1),
+                ..Default::default()
+            }),
+            "Triggers one-push white balance",
+        ),
+        action(
+            "recommended_settings",
+            "Recommended Settings",
+            br4kcam_commands::Action::SetRecommendedCameraSettings,
+            "Applies the recommended image and stream settings",
+        ),
+        action(
+            "day_mode",
+            "Day Mode",
+            br4kcam_commands::Action::SetImageAdjustmentEx(AdvancedParameterSetting {
+                color_black: Some(AdvancedDisplayColorBlackValue::Color),
+                ..Default::default()
+            }),
+            "Keeps the image in color",
+        ),
+        action(
+            "night_mode",
+            "Night Mode",
+            br4kcam_commands::Action::SetImageAdjustmentEx(AdvancedParameterSetting {
+                color_black: Some(AdvancedDisplayColorBlackValue::Auto),
+                infr_detect_mode: Some(AdvancedDisplayInfrDetectModeValue::VideoDetection),
+                ..Default::default()
+            }),
+            "Lets the image turn black and white in low light",
+        ),
+        action(
+            "restart",
+            "Restart",
+            br4kcam_commands::Action::Restart,
+            "Restarts the camera",
+        ),
+    ]
+}
+
+/// Focus, zoom and tilt actions, only for the axes the camera can actually move.
+#[cfg(feature = "autopilot")]
+fn actuator_actions(camera_uuid: Uuid, configured: &ConfiguredActuators) -> Vec<CockpitAction> {
+    let hostname = &configured.hostname;
+    let action = |id: &str, name: &str, action: AutopilotAction, description: &str| {
+        http_post_action(
+            action_id(id, camera_uuid),
+            format!("4K Cam {name} ({hostname})"),
+            AUTOPILOT_CONTROL_URL,
+            json!(ActuatorsControl {
+                camera_uuid,
+                action,
+            }),
+            format!("{description} on {hostname}."),
+        )
+    };
+    let step = |focus, zoom, tilt_degrees| {
+        AutopilotAction::StepActuators(ActuatorsStep {
+            focus,
+            zoom,
+            tilt_degrees,
+        })
+    };
+
+    let axes = configured.axes;
+    let mut actions = Vec::new();
+
+    if axes.zoom {
+        actions.push(action(
+            "zoom_in",
+            "Zoom In",
+            step(None, Some(ZOOM_STEP), None),
+            &format!("Zooms in by {ZOOM_STEP}%"),
+        ));
+        actions.push(action(
+            "zoom_out",
+            "Zoom Out",
+            step(None, Some(-ZOOM_STEP), None),
+            &format!("Zooms out by {ZOOM_STEP}%"),
+        ));
+    }
+
+    if axes.focus {
+        actions.push(action(
+            "focus_near",
+            "Focus Near",
+            step(Some(-FOCUS_STEP), None, None),
+            &format!("Moves the focus {FOCUS_STEP}% nearer"),
+        ));
+        actions.push(action(
+            "focus_far",
+            "Focus Far",
+            step(Some(FOCUS_STEP), None, None),
+            &format!("Moves the focus {FOCUS_STEP}% farther"),
+        ));
+    }
+
+    if axes.tilt {
+        let centre = configured.tilt_centre;
+
+        actions.push(action(
+            "tilt_up",
+            "Tilt Up",
+            step(None, None, Some(TILT_STEP)),
+            &format!("Tilts the camera up by {TILT_STEP}°"),
+        ));
+        actions.push(action(
+            "tilt_down",
+            "Tilt Down",
+            step(None, None, Some(-TILT_STEP)),
+            &format!("Tilts the camera down by {TILT_STEP}°"),
+        ));
+        actions.push(action(
+            "tilt_centre",
+            "Tilt Centre",
+            AutopilotAction::SetActuatorsState(ActuatorsState {
+                tilt_degrees: Some(centre),
+                ..Default::default()
+            }),
+            &format!("Tilts the camera to {centre}°"),
+        ));
+    }
+
+    actions
+}
+
+/// One action per saved actuator preset, recalling it on its camera.
+#[cfg(feature = "autopilot")]
+fn preset_actions(actuators: &IndexMap<Uuid, ConfiguredActuators>) -> Vec<CockpitAction> {
+    actuators
+        .iter()
+        .flat_map(|(camera_uuid, configured)| {
+            configured.presets.iter().map(|preset| {
+                http_post_action(
+                    preset_action_id(*camera_uuid, preset),
+                    format!("4K Cam Preset {preset} ({})", configured.hostname),
+                    AUTOPILOT_CONTROL_URL,
+                    json!(ActuatorsControl {
+                        camera_uuid: *camera_uuid,
+                        action: AutopilotAction::RecallPreset(PresetName {
+                            name: preset.clone(),
+                        }),
+                    }),
+                    format!(
+                        "Moves focus, zoom and tilt of {} to the {preset:?} preset.",
+                        configured.hostname
+                    ),
+                )
+            })
+        })
+        .collect()
+}
+
+fn preset_action_id(camera_uuid: Uuid, preset: &str) -> String {
+    format!("br4kcam_preset_{camera_uuid}_{preset}")
+}
+
+fn joystick_suggestions(
+    actuators: &IndexMap<Uuid, ConfiguredActuators>,
+) -> Vec<JoystickMapSuggestion> {
     let mut mappings_rov = vec![
         // === Regular modifier ===
         ButtonMappingSuggestion {
@@ -449,43 +789,7 @@ fn joystick_suggestions(_cameras: &Cameras) -> Vec<JoystickMapSuggestion> {
         },
     ]);
 
-    let mappings_for_anys = vec![
-        ButtonMappingSuggestion {
-            id: "camera-zoom-decrease".to_string(),
-            action_protocol: JoystickProtocol::DataLakeVariable,
-            action_name: "Camera zoom decrease".to_string(),
-            action_id: "camera-zoom-decrease".to_string(),
-            button: 6,
-            modifier_key: CockpitModifierKeyOption::Shift,
-            description: Some("Decrease camera zoom level".to_string()),
-        },
-        ButtonMappingSuggestion {
-            id: "camera-zoom-increase".to_string(),
-            action_protocol: JoystickProtocol::DataLakeVariable,
-            action_name: "Camera zoom increase".to_string(),
-            action_id: "camera-zoom-increase".to_string(),
-            button: 7,
-            modifier_key: CockpitModifierKeyOption::Shift,
-            description: Some("Increase camera zoom level".to_string()),
-        },
-        ButtonMappingSuggestion {
-            id: "camera-focus-decrease".to_string(),
-            action_protocol: JoystickProtocol::DataLakeVariable,
-            action_name: "Camera focus decrease".to_string(),
-            action_id: "camera-focus-decrease".to_string(),
-            button: 6,
-            modifier_key: CockpitModifierKeyOption::Regular,
-            description: Some("Decrease camera focus distance".to_string()),
-        },
-        ButtonMappingSuggestion {
-            id: "camera-focus-increase".to_string(),
-            action_protocol: JoystickProtocol::DataLakeVariable,
-            action_name: "Camera focus increase".to_string(),
-            action_id: "camera-focus-increase".to_string(),
-            button: 7,
-            modifier_key: CockpitModifierKeyOption::Regular,
-            description: Some("Increase camera focus distance".to_string()),
-        },
+    let common_mappings = vec![
         ButtonMappingSuggestion {
             id: "toggle_recording_all_streams".to_string(),
             action_protocol: JoystickProtocol::CockpitAction,
@@ -506,8 +810,17 @@ fn joystick_suggestions(_cameras: &Cameras) -> Vec<JoystickMapSuggestion> {
         },
     ];
 
-    mappings_rov.extend_from_slice(&mappings_for_anys);
-    mappings_rov_with_gripper.extend_from_slice(&mappings_for_anys);
+    let mut mappings_for_anys = actuator_mappings(actuators, ButtonLayout::Any);
+    mappings_for_anys.extend_from_slice(&common_mappings);
+
+    let mut rov_camera_mappings = actuator_mappings(actuators, ButtonLayout::Rov);
+    rov_camera_mappings.extend_from_slice(&common_mappings);
+    mappings_rov.extend_from_slice(&rov_camera_mappings);
+    mappings_rov_with_gripper.extend_from_slice(&rov_camera_mappings);
+
+    let mappings_rov = deduplicated(mappings_rov);
+    let mappings_rov_with_gripper = deduplicated(mappings_rov_with_gripper);
+    let mappings_for_anys = deduplicated(mappings_for_anys);
 
     vec![
         JoystickMapSuggestion {
@@ -530,9 +843,289 @@ fn joystick_suggestions(_cameras: &Cameras) -> Vec<JoystickMapSuggestion> {
             id: "br4kcam-for-any-vehicle-types".to_string(),
             name: "4K Cam on any vehicle types".to_string(),
             target_vehicle_types: vec![],
-            description: "Minimal 4K Cam buttons mapping for any vehicle types".to_string(),
+            description: "4K Cam buttons mapping for any vehicle types".to_string(),
             button_mapping_suggestions: mappings_for_anys,
             version: env!("CARGO_PKG_VERSION").to_string(), // TODO: freeze this once we settle with a button layout
         },
     ]
 }
+
+/// Where camera buttons go in a suggested layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ButtonLayout {
+    /// BlueROV: ArduSub already tilts the mount from the bumpers, and the D-pad is taken.
+    Rov,
+    /// Any vehicle: zoom on the bumpers, tilt on the D-pad, presets on shift + D-pad.
+    Any,
+}
+
+/// Focus, zoom, tilt and preset buttons for the configured actuators.
+///
+/// A button can only trigger one camera, so each axis goes to the first camera that can move
+/// it. Without such a camera, zoom and focus fall back to Cockpit's own camera variables.
+fn actuator_mappings(
+    actuators: &IndexMap<Uuid, ConfiguredActuators>,
+    layout: ButtonLayout,
+) -> Vec<ButtonMappingSuggestion> {
+    use CockpitModifierKeyOption::{Regular, Shift};
+
+    let first_with = |axis: fn(ActuatorAxes) -> bool| {
+        actuators
+            .iter()
+            .find(|(_, configured)| axis(configured.axes))
+    };
+    let action =
+        |kind: &str, name: &str, camera_uuid: Uuid, hostname: &str, button, modifier_key| {
+            let id = action_id(kind, camera_uuid);
+            ButtonMappingSuggestion {
+                id: id.clone(),
+                action_protocol: JoystickProtocol::CockpitAction,
+                action_name: format!("4K Cam {name} ({hostname})"),
+                action_id: id,
+                button,
+                modifier_key,
+                description: Some(format!("{name} on {hostname}")),
+            }
+        };
+    let variable =
+        |id: &str, name: &str, button, modifier_key, description: &str| ButtonMappingSuggestion {
+            id: id.to_string(),
+            action_protocol: JoystickProtocol::DataLakeVariable,
+            action_name: name.to_string(),
+            action_id: id.to_string(),
+            button,
+            modifier_key,
+            description: Some(description.to_string()),
+        };
+
+    let mut mappings = Vec::new();
+
+    let (zoom_out_button, zoom_in_button, zoom_modifier) = match layout {
+        ButtonLayout::Rov => (6, 7, Shift),
+        ButtonLayout::Any => (4, 5, Regular),
+    };
+    match first_with(|axes| axes.zoom) {
+        Some((camera_uuid, configured)) => mappings.extend([
+            action(
+                "zoom_out",
+                "Zoom Out",
+                *camera_uuid,
+                &configured.hostname,
+                zoom_out_button,
+                zoom_modifier,
+            ),
+            action(
+                "zoom_in",
+                "Zoom In",
+                *camera_uuid,
+                &configured.hostname,
+                zoom_in_button,
+                zoom_modifier,
+            ),
+        ]),
+        None => mappings.extend([
+            variable(
+                "camera-zoom-decrease",
+                "Camera zoom decrease",
+                6,
+                Shift,
+                "Decrease camera zoom level",
+            ),
+            variable(
+                "camera-zoom-increase",
+                "Camera zoom increase",
+                7,
+                Shift,
+                "Increase camera zoom level",
+            ),
+        ]),
+    }
+
+    match first_with(|axes| axes.focus) {
+        Some((camera_uuid, configured)) => mappings.extend([
+            action(
+                "focus_near",
+                "Focus Near",
+                *camera_uuid,
+                &configured.hostname,
+                6,
+                Regular,
+            ),
+            action(
+                "focus_far",
+                "Focus Far",
+                *camera_uuid,
+                &configured.hostname,
+                7,
+                Regular,
+            ),
+        ]),
+        None => mappings.extend([
+            variable(
+                "camera-focus-decrease",
+                "Camera focus decrease",
+                6,
+                Regular,
+                "Decrease camera focus distance",
+            ),
+            variable(
+                "camera-focus-increase",
+                "Camera focus increase",
+                7,
+                Regular,
+                "Increase camera focus distance",
+            ),
+        ]),
+    }
+
+    if layout == ButtonLayout::Rov {
+        return mappings;
+    }
+
+    if let Some((camera_uuid, configured)) = first_with(|axes| axes.tilt) {
+        mappings.extend([
+            action(
+                "tilt_up",
+                "Tilt Up",
+                *camera_uuid,
+                &configured.hostname,
+                12,
+                Regular,
+            ),
+            action(
+                "tilt_down",
+                "Tilt Down",
+                *camera_uuid,
+                &configured.hostname,
+                13,
+                Regular,
+            ),
+            action(
+                "tilt_centre",
+                "Tilt Centre",
+                *camera_uuid,
+                &configured.hostname,
+                14,
+                Regular,
+            ),
+        ]);
+    }
+
+    if let Some((camera_uuid, configured)) = actuators
+        .iter()
+        .find(|(_, configured)| !configured.presets.is_empty())
+    {
+        mappings.extend(
+            configured
+                .presets
+                .iter()
+                .zip(12..=15)
+                .map(|(preset, button)| {
+                    let id = preset_action_id(*camera_uuid, preset);
+                    ButtonMappingSuggestion {
+                        id: id.clone(),
+                        action_protocol: JoystickProtocol::CockpitAction,
+                        action_name: format!("4K Cam Preset {preset} ({})", configured.hostname),
+                        action_id: id,
+                        button,
+                        modifier_key: Shift,
+                        description: Some(format!(
+                            "Recall the {preset:?} preset on {}",
+                            configured.hostname
+                        )),
+                    }
+                }),
+        );
+    }
+
+    mappings
+}
+
+/// Keep the first suggestion for each button and each id, so adopting a layout never maps a
+/// button twice.
+fn deduplicated(mappings: Vec<ButtonMappingSuggestion>) -> Vec<ButtonMappingSuggestion> {
+    let mut buttons = HashSet::new();
+    let mut ids = HashSet::new();
+
+    mappings
+        .into_iter()
+        .filter(|mapping| {
+            buttons.insert((mapping.button, mapping.modifier_key)) && ids.insert(mapping.id.clone())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn configured(hostname: &str, presets: &[&str]) -> ConfiguredActuators {
+        ConfiguredActuators {
+            hostname: hostname.to_string(),
+            axes: ActuatorAxes {
+                zoom: true,
+                focus: true,
+                tilt: true,
+            },
+            tilt_centre: 0.0,
+            presets: presets.iter().map(|preset| preset.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn camera_buttons_go_to_the_first_capable_camera_only() {
+        let (first, second) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let actuators = IndexMap::from([
+            (first, configured("first", &[])),
+            (second, configured("second", &["wide", "gauge"])),
+        ]);
+
+        let mappings = deduplicated(actuator_mappings(&actuators, ButtonLayout::Any));
+
+        let zoom_in = mappings.iter().find(|mapping| mapping.button == 5).unwrap();
+        assert_eq!(zoom_in.action_id, action_id("zoom_in", first));
+        assert!(
+            !mappings
+                .iter()
+                .any(|mapping| mapping.action_id == action_id("zoom_in", second))
+        );
+        assert_eq!(
+            mappings
+                .iter()
+                .filter(|mapping| mapping.action_id.starts_with("br4kcam_preset_"))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn zoom_and_focus_fall_back_to_cockpit_variables() {
+        let mappings = actuator_mappings(&IndexMap::new(), ButtonLayout::Rov);
+
+        assert_eq!(mappings.len(), 4);
+        assert!(
+            mappings.iter().all(|mapping| matches!(
+                mapping.action_protocol,
+                JoystickProtocol::DataLakeVariable
+            ))
+        );
+    }
+
+    #[test]
+    fn duplicated_buttons_keep_the_first_suggestion() {
+        let mapping = |id: &str, button| ButtonMappingSuggestion {
+            id: id.to_string(),
+            action_protocol: JoystickProtocol::CockpitAction,
+            action_name: id.to_string(),
+            action_id: id.to_string(),
+            button,
+            modifier_key: CockpitModifierKeyOption::Regular,
+            description: None,
+        };
+
+        let mappings = deduplicated(vec![mapping("a", 4), mapping("b", 4), mapping("a", 5)]);
+
+        assert_eq!(mappings.len(), 1);
+        assert_eq!(mappings[0].id, "a");
+    }
+}
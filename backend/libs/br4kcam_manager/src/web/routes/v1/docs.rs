@@ -0,0 +1,539 @@
+//! OpenAPI description of the REST API, browsable at `/docs` and served raw at
+//! `/docs/openapi.json`.
+//!
+//! Routes are registered through an [`ApiRouter`], which keeps the [`Operation`] of every
+//! route next to its handler, so the document lists exactly the routes served. Schemas are
+//! derived from the same types the routes (de)serialize. The WebSocket at `/ws` carries the
+//! `br4kcam_api` envelopes, which are listed as components even though OpenAPI cannot
+//! describe the socket itself.
+
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::Path,
+    handler::Handler,
+    http::{StatusCode, header},
+    response::{Html, IntoResponse, Response},
+    routing::{self, MethodRouter},
+};
+use once_cell::sync::Lazy;
+use schemars::{JsonSchema, Schema, generate::SchemaSettings};
+use serde_json::{Map, Value, json};
+use tracing::*;
+use utoipa_swagger_ui::Config;
+
+static SPEC: Lazy<Value> = Lazy::new(|| super::api().openapi());
+static SWAGGER_CONFIG: Lazy<Arc<Config<'static>>> =
+    Lazy::new(|| Arc::new(Config::from("openapi.json")));
+
+/// Swagger UI's own page loads its assets relative to the document, which breaks when the
+/// page is reached as `/docs` instead of `/docs/` (trailing slashes are trimmed before
+/// routing). The `<base>` below points every relative URL inside `docs/`, wherever the
+/// extension is proxied from.
+const INDEX_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="UTF-8">
+  <title>4K Cam Manager API</title>
+  <script>document.write('<base href="' + location.pathname.replace(/\/?$/, '/') + '">');</script>
+  <link rel="stylesheet" type="text/css" href="swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
+
+#[instrument(level = "debug")]
+pub async fn ui(file: Option<Path<String>>) -> Response {
+    let Some(Path(file)) = file.filter(|Path(file)| !file.is_empty() && file != "index.html")
+    else {
+        return Html(INDEX_HTML).into_response();
+    };
+
+    match utoipa_swagger_ui::serve(&file, SWAGGER_CONFIG.clone()) {
+        Ok(Some(file)) => (
+            [(header::CONTENT_TYPE, file.content_type)],
+            file.bytes.into_owned(),
+        )
+            .into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "404 Not Found").into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{error:?}")).into_response(),
+    }
+}
+
+#[instrument(level = "debug")]
+pub async fn spec() -> Json<Value> {
+    Json(SPEC.clone())
+}
+
+/// A [`Router`] that also records the [`Operation`] of each of its routes.
+#[derive(Default)]
+pub struct ApiRouter {
+    router: Router,
+    operations: Vec<(String, &'static str, Operation)>,
+}
+
+impl ApiRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get<H, T>(self, path: &str, handler: H, operation: Operation) -> Self
+    where
+        H: Handler<T, ()>,
+        T: 'static,
+    {
+        self.route(path, "get", routing::get(handler), operation)
+    }
+
+    pub fn post<H, T>(self, path: &str, handler: H, operation: Operation) -> Self
+    where
+        H: Handler<T, ()>,
+        T: 'static,
+    {
+        self.route(path, "post", routing::post(handler), operation)
+    }
+
+    pub fn delete<H, T>(self, path: &str, handler: H, operation: Operation) -> Self
+    where
+        H: Handler<T, ()>,
+        T: 'static,
+    {
+        self.route(path, "delete", routing::delete(handler), operation)
+    }
+
+    pub fn nest(mut self, prefix: &str, api: ApiRouter) -> Self {
+        self.router = self.router.nest(prefix, api.router);
+        self.operations.extend(
+            api.operations
+                .into_iter()
+                .map(|(path, method, operation)| (join(prefix, &path), method, operation)),
+        );
+        self
+    }
+
+    pub fn into_router(self) -> Router {
+        self.router
+    }
+
+    /// Builds the OpenAPI 3 document of the recorded routes.
+    pub fn openapi(&self) -> Value {
+        let mut schemas = Schemas::new();
+        let mut paths = Map::new();
+
+        for (path, method, operation) in &self.operations {
+            let item = paths.entry(path.clone()).or_insert_with(|| json!({}));
+            item[*method] = operation.to_value(&mut schemas);
+        }
+
+        json!({
+            "openapi": "3.0.3",
+            "info": {
+                "title": "4K Cam Manager",
+                "description": "The official management interface for 4K Cam",
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            // Relative to this document, so it holds behind the BlueOS extension proxy and
+            // under `/v1` alike.
+            "servers": [{ "url": ".." }],
+            "paths": paths,
+            "components": { "schemas": schemas.into_definitions() },
+        })
+    }
+
+    fn route(
+        mut self,
+        path: &str,
+        method: &'static str,
+        method_router: MethodRouter,
+        operation: Operation,
+    ) -> Self {
+        // Routes sharing a path merge their methods.
+        self.router = self.router.route(path, method_router);
+        self.operations.push((path.to_string(), method, operation));
+        self
+    }
+}
+
+/// Path of a nested route as the client sees it: trailing slashes are trimmed before
+/// routing.
+fn join(prefix: &str, path: &str) -> String {
+    match format!("{prefix}{path}").trim_end_matches('/') {
+        "" => "/".to_string(),
+        path => path.to_string(),
+    }
+}
+
+type SchemaOf = fn(&mut Schemas) -> Value;
+type ParametersOf = fn(&mut Schemas) -> Vec<Value>;
+
+/// OpenAPI description of one route, built alongside the route itself.
+pub struct Operation {
+    tag: &'static str,
+    summary: &'static str,
+    description: Option<&'static str>,
+    path_parameters: Vec<(&'static str, SchemaOf)>,
+    query: Option<ParametersOf>,
+    request: Option<SchemaOf>,
+    responses: Vec<(&'static str, &'static str, Content)>,
+    components: Vec<SchemaOf>,
+}
+
+enum Content {
+    Empty,
+    Json(SchemaOf),
+    Text,
+    Binary(&'static str),
+}
+
+impl Operation {
+    pub fn new(tag: &'static str, summary: &'static str) -> Self {
+        Self {
+            tag,
+            summary,
+            description: None,
+            path_parameters: Vec::new(),
+            query: None,
+            request: None,
+            responses: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+
+    /// A route upgrading to a WebSocket, whose messages `description` explains.
+    pub fn websocket(tag: &'static str, summary: &'static str, description: &'static str) -> Self {
+        Self {
+            description: Some(description),
+            ..Self::new(tag, summary)
+        }
+        .empty("101", "Switching to the WebSocket protocol")
+    }
+
+    /// The `{name}` segment of the path, extracted as `T`.
+    pub fn path_parameter<T: JsonSchema>(mut self, name: &'static str) -> Self {
+        self.path_parameters.push((name, Schemas::of::<T>));
+        self
+    }
+
+    /// The query string, extracted as the struct `T`.
+    pub fn query<T: JsonSchema>(mut self) -> Self {
+        self.query = Some(Schemas::query_parameters::<T>);
+        self
+    }
+
+    /// A JSON request body, extracted as `T`.
+    pub fn request<T: JsonSchema>(mut self) -> Self {
+        self.request = Some(Schemas::of::<T>);
+        self
+    }
+
+    pub fn json<T: JsonSchema>(mut self, status: &'static str, description: &'static str) -> Self {
+        self.responses
+            .push((status, description, Content::Json(Schemas::of::<T>)));
+        self
+    }
+
+    pub fn text(mut self, status: &'static str, description: &'static str) -> Self {
+        self.responses.push((status, description, Content::Text));
+        self
+    }
+
+    pub fn binary(
+        mut self,
+        status: &'static str,
+        media_type: &'static str,
+        description: &'static str,
+    ) -> Self {
+        self.responses
+            .push((status, description, Content::Binary(media_type)));
+        self
+    }
+
+    pub fn empty(mut self, status: &'static str, description: &'static str) -> Self {
+        self.responses.push((status, description, Content::Empty));
+        self
+    }
+
+    /// A schema the operation refers to without carrying it in a body, like the messages of
+    /// a WebSocket.
+    pub fn component<T: JsonSchema>(mut self) -> Self {
+        self.components.push(Schemas::of::<T>);
+        self
+    }
+
+    fn to_value(&self, schemas: &mut Schemas) -> Value {
+        let mut operation = json!({
+            "tags": [self.tag],
+            "summary": self.summary,
+            "responses": {},
+        });
+
+        if let Some(description) = self.description {
+            operation["description"] = description.into();
+        }
+
+        let mut parameters = self
+            .path_parameters
+            .iter()
+            .map(|(name, schema)| {
+                json!({ "name": name, "in": "path", "required": true, "schema": schema(schemas) })
+            })
+            .collect::<Vec<_>>();
+        if let Some(query) = self.query {
+            parameters.extend(query(schemas));
+        }
+        if !parameters.is_empty() {
+            operation["parameters"] = parameters.into();
+        }
+
+        if let Some(request) = self.request {
+            operation["requestBody"] = json!({
+                "required": true,
+                "content": { "application/json": { "schema": request(schemas) } },
+            });
+        }
+
+        for (status, description, content) in &self.responses {
+            let mut response = json!({ "description": description });
+            match content {
+                Content::Empty => {}
+                Content::Json(schema) => {
+                    response["content"] =
+                        json!({ "application/json": { "schema": schema(schemas) } })
+                }
+                Content::Text => {
+                    response["content"] =
+                        json!({ "text/plain": { "schema": { "type": "string" } } })
+                }
+                Content::Binary(media_type) => {
+                    response["content"] = json!({
+                        *media_type: { "schema": { "type": "string", "format": "binary" } },
+                    })
+                }
+            }
+            operation["responses"][*status] = response;
+        }
+
+        for component in &self.components {
+            component(schemas);
+        }
+
+        operation
+    }
+}
+
+/// Collects the schema components while handing out references to them.
+struct Schemas(schemars::SchemaGenerator);
+
+impl Schemas {
+    fn new() -> Self {
+        Self(SchemaSettings::openapi3().into_generator())
+    }
+
+    fn of<T: JsonSchema>(&mut self) -> Value {
+        match Schema::to_value(self.0.subschema_for::<T>()) {
+            // OpenAPI 3.0 has no boolean schemas; any value is the empty schema.
+            Value::Bool(true) => json!({}),
+            schema => schema,
+        }
+    }
+
+    /// One query parameter per property of the struct `T`.
+    fn query_parameters<T: JsonSchema>(&mut self) -> Vec<Value> {
+        let schema = Schema::to_value(self.0.root_schema_for::<T>());
+        let required = schema["required"].as_array().cloned().unwrap_or_default();
+        let Some(properties) = schema["properties"].as_object() else {
+            return Vec::new();
+        };
+
+        properties
+            .iter()
+            .map(|(name, property)| {
+                let mut property = property.clone();
+                let description = property
+                    .as_object_mut()
+                    .and_then(|property| property.remove("description"));
+                let mut parameter = json!({
+                    "name": name,
+                    "in": "query",
+                    "required": required.contains(&Value::from(name.as_str())),
+                    "schema": property,
+                });
+                if let Some(description) = description {
+                    parameter["description"] = description;
+                }
+                parameter
+            })
+            .collect()
+    }
+
+    fn into_definitions(mut self) -> Map<String, Value> {
+        self.0.take_definitions(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn openapi() -> Value {
+        super::super::api().openapi()
+    }
+
+    #[test]
+    fn every_reference_resolves() {
+        let spec = openapi();
+        let schemas = spec["components"]["schemas"].as_object().unwrap();
+
+        let mut references = Vec::new();
+        collect_references(&spec, &mut references);
+
+        assert!(!references.is_empty());
+        for reference in references {
+            let name = reference
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("Unexpected reference {reference}"));
+            assert!(schemas.contains_key(name), "Missing schema {name}");
+        }
+        for name in [
+            "CameraControl",
+            "CameraControlResult",
+            #[cfg(feature = "autopilot")]
+            "ActuatorsControl",
+            #[cfg(feature = "autopilot")]
+            "ActuatorsControlResult",
+            #[cfg(feature = "mcm")]
+            "StreamPreferences",
+            "SystemHealth",
+            "WsEvent",
+        ] {
+            assert!(schemas.contains_key(name), "Missing schema {name}");
+        }
+    }
+
+    #[test]
+    fn nested_routes_are_documented_under_their_prefix() {
+        let spec = openapi();
+        let paths = spec["paths"].as_object().unwrap();
+
+        for (path, method) in [
+            ("/camera/list", "get"),
+            ("/camera/control", "post"),
+            ("/health", "get"),
+            ("/info/full", "get"),
+            ("/log", "get"),
+            ("/ws", "get"),
+            #[cfg(feature = "mcm")]
+            ("/camera/{uuid}/stream", "post"),
+            #[cfg(feature = "autopilot")]
+            ("/settings", "delete"),
+            #[cfg(feature = "autopilot")]
+            ("/autopilot/control", "post"),
+        ] {
+            assert!(
+                paths.get(path).and_then(|path| path.get(method)).is_some(),
+                "{} {path} is missing from the OpenAPI document",
+                method.to_uppercase()
+            );
+        }
+        assert!(!paths.keys().any(|path| path.starts_with("/docs")));
+    }
+
+    #[test]
+    fn every_path_parameter_is_described() {
+        let spec = openapi();
+
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            let names = path
+                .split('/')
+                .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'));
+            for name in names {
+                for (method, operation) in item.as_object().unwrap() {
+                    let described = operation["parameters"]
+                        .as_array()
+                        .is_some_and(|parameters| {
+                            parameters.iter().any(|parameter| {
+                                parameter["name"] == name && parameter["in"] == "path"
+                            })
+                        });
+                    assert!(described, "{method} {path} does not describe {{{name}}}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn control_responses_are_typed() {
+        let spec = openapi();
+        let result = |path: &str| {
+            spec["paths"][path]["post"]["responses"]["200"]["content"]["application/json"]["schema"]
+                ["$ref"]
+                .clone()
+        };
+
+        assert_eq!(
+            result("/camera/control"),
+            "#/components/schemas/CameraControlResult"
+        );
+        #[cfg(feature = "autopilot")]
+        assert_eq!(
+            result("/autopilot/control"),
+            "#/components/schemas/ActuatorsControlResult"
+        );
+    }
+
+    #[test]
+    fn query_parameters_follow_their_struct() {
+        #[derive(JsonSchema)]
+        #[allow(dead_code)]
+        struct Query {
+            /// Only pretend.
+            #[serde(default)]
+            dry_run: bool,
+            name: String,
+        }
+
+        let parameters = Schemas::new().query_parameters::<Query>();
+
+        assert_eq!(
+            parameters,
+            vec![
+                json!({
+                    "name": "dry_run",
+                    "in": "query",
+                    "required": false,
+                    "description": "Only pretend.",
+                    "schema": { "type": "boolean", "default": false },
+                }),
+                json!({
+                    "name": "name",
+                    "in": "query",
+                    "required": true,
+                    "schema": { "type": "string" },
+                }),
+            ]
+        );
+    }
+
+    fn collect_references(value: &Value, references: &mut Vec<String>) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get("$ref") {
+                    references.push(reference.clone());
+                }
+                map.values()
+                    .for_each(|value| collect_references(value, references));
+            }
+            Value::Array(values) => values
+                .iter()
+                .for_each(|value| collect_references(value, references)),
+            _ => {}
+        }
+    }
+}
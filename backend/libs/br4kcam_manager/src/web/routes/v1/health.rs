@@ -1,16 +1,21 @@
-use axum::{Json, Router, routing::get};
+use axum::Json;
 use br4kcam_api::SystemHealth;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing::*;
 
+use super::docs::{ApiRouter, Operation};
 use crate::web::connectivity;
 
 #[instrument(level = "trace")]
-pub fn router() -> Router {
-    Router::new()
-        .route("/", get(health))
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
+pub fn router() -> ApiRouter {
+    ApiRouter::new().get(
+        "/",
+        health,
+        Operation::new(
+            "system",
+            "Aggregate health of the cameras, MCM, autopilot and Lua script",
+        )
+        .json::<SystemHealth>("200", "System health"),
+    )
 }
 
 #[instrument(level = "debug")]
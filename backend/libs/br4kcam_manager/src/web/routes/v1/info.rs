@@ -1,9 +1,15 @@
-use axum::{Json, Router, response::IntoResponse, routing::get};
+use axum::{Json, response::IntoResponse};
+use once_cell::sync::OnceCell;
+use schemars::JsonSchema;
 use serde::Serialize;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use serde_json::Value;
 use tracing::*;
 
-#[derive(Serialize, Debug, Default)]
+use super::docs::{ApiRouter, Operation};
+
+static CONFIGURATION: OnceCell<Value> = OnceCell::new();
+
+#[derive(Serialize, Debug, Default, JsonSchema)]
 pub struct InfoContent {
     /// Name of the program
     name: String,
@@ -16,21 +22,41 @@ pub struct InfoContent {
     authors: String,
 }
 
-#[derive(Serialize, Debug, Default)]
+#[derive(Serialize, Debug, Default, JsonSchema)]
 pub struct Info {
     /// Version of the REST API
     version: u32,
     /// Service information
     service: InfoContent,
+    /// Effective value of each startup option, and where it was set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    configuration: Option<Value>,
+}
+
+/// Publish the effective startup configuration, for troubleshooting.
+pub fn set_configuration(configuration: Value) {
+    // The configuration is read once per process, so a soft restart sets the same value.
+    let _ = CONFIGURATION.set(configuration);
 }
 
 #[instrument(level = "trace")]
-pub fn router() -> Router {
-    Router::new()
-        .route("/", get(info))
-        .route("/full", get(info_full))
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
+pub fn router() -> ApiRouter {
+    ApiRouter::new()
+        .get(
+            "/",
+            info,
+            Operation::new(
+                "system",
+                "Service name, version and effective startup configuration",
+            )
+            .json::<Info>("200", "Service information"),
+        )
+        .get(
+            "/full",
+            info_full,
+            Operation::new("system", "The service's Cargo manifest, as JSON")
+                .json::<Value>("200", "Cargo manifest"),
+        )
 }
 
 #[instrument(level = "trace")]
@@ -44,6 +70,7 @@ async fn info() -> Json<Info> {
             build_date: env!("VERGEN_BUILD_TIMESTAMP").into(), // TODO: WE NEED TO HAVE VERGEN-GIX HERE EMITTING IT HERE TOO!!!
             authors: env!("CARGO_PKG_AUTHORS").into(),
         },
+        configuration: CONFIGURATION.get().cloned(),
     })
 }
 
@@ -1,24 +1,27 @@
 use axum::{
-    Router,
     extract::{
         WebSocketUpgrade,
         ws::{Message, WebSocket},
     },
     response::Response,
-    routing::get,
 };
 use futures::{SinkExt, StreamExt};
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing::*;
 
+use super::docs::{ApiRouter, Operation};
 use crate::logger;
 
 #[instrument(level = "trace")]
-pub fn router() -> Router {
-    Router::new()
-        .route("/", get(log_websocket_handler))
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
+pub fn router() -> ApiRouter {
+    ApiRouter::new().get(
+        "/",
+        log_websocket_handler,
+        Operation::websocket(
+            "system",
+            "Stream the service log",
+            "WebSocket of text frames, one per log line, starting with the recent history.",
+        ),
+    )
 }
 
 #[instrument(level = "debug")]
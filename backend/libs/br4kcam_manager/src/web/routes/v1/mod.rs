@@ -1,48 +1,124 @@
-use autopilot::api::ActuatorsControl;
-use axum::{
-    Json, Router,
-    http::StatusCode,
-    response::IntoResponse,
-    routing::{get, post},
+#[cfg(feature = "autopilot")]
+use autopilot::api::{ActuatorsControl, ActuatorsControlResult};
+#[cfg(feature = "autopilot")]
+use axum::{Json, response::IntoResponse};
+use axum::{Router, http::StatusCode, routing::get};
+use br4kcam_api::{
+    CameraStateEvent, ConnectionStats, WsClientMessage, WsEvent, WsRequest, WsResponse,
 };
 use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use tracing::*;
 
-use crate::web::{control_bridge, request_restart};
+use self::{
+    blueos::ServerMetadata,
+    cockpit::CockpitExtras,
+    docs::{ApiRouter, Operation},
+};
+#[cfg(feature = "autopilot")]
+use crate::web::control_bridge;
+use crate::web::request_restart;
 
 pub mod blueos;
 pub mod camera;
+#[cfg(all(feature = "autopilot", feature = "mcm"))]
+pub mod captures;
 pub mod cockpit;
+pub mod docs;
 pub mod health;
 pub mod info;
 pub mod log;
+#[cfg(feature = "autopilot")]
 pub mod settings;
+#[cfg(feature = "autopilot")]
+pub mod vehicles;
 pub mod ws;
 
 #[instrument(level = "trace")]
 pub fn router() -> Router {
-    Router::new()
+    api()
+        .into_router()
+        .route("/docs", get(docs::ui))
+        .route("/docs/openapi.json", get(docs::spec))
+        .route("/docs/{*file}", get(docs::ui))
+        .layer(TraceLayer::new_for_http())
+        .layer(CorsLayer::permissive())
+}
+
+/// Every route but the documentation itself, which is generated from them.
+pub fn api() -> ApiRouter {
+    let api = ApiRouter::new()
         .nest("/camera", camera::router())
         .nest("/log", log::router())
         .nest("/info", info::router())
-        .nest("/health", health::router())
+        .nest("/health", health::router());
+
+    #[cfg(all(feature = "autopilot", feature = "mcm"))]
+    let api = api.nest("/captures", captures::router());
+
+    #[cfg(feature = "autopilot")]
+    let api = api
         .nest("/settings", settings::router())
-        .nest(
-            "/autopilot",
-            Router::new().route("/control", post(autopilot_control)),
+        .nest("/vehicles", vehicles::router())
+        .post(
+            "/autopilot/control",
+            autopilot_control,
+            Operation::new(
+                "autopilot",
+                "Configure or move a camera's focus, zoom and tilt actuators",
+            )
+            .request::<ActuatorsControl>()
+            .json::<ActuatorsControlResult>(
+                "200",
+                "Action result, shaped after the requested action",
+            )
+            .text("default", "Why the action failed"),
+        );
+
+    api.get(
+        "/ws",
+        ws::websocket_handler,
+        Operation::websocket(
+            "system",
+            "Request/response calls and camera state events over one socket",
+            "WebSocket of JSON messages. Clients send `WsRequest` calls, answered by \
+             `WsResponse` with the same `id`, and `WsClientMessage`s to subscribe to \
+             cameras. The server pushes `WsEvent`s, such as `camera/state` carrying a \
+             `CameraStateEvent`, and `connection/stats` carrying `ConnectionStats`.",
         )
-        .route("/ws", get(ws::websocket_handler))
-        .route("/service/restart", post(restart))
-        .route("/register_service", get(blueos::server_metadata))
-        .route("/cockpit_extras.json", get(cockpit::cockpit_extras))
-        .layer(TraceLayer::new_for_http())
-        .layer(CorsLayer::permissive())
+        .component::<WsRequest>()
+        .component::<WsResponse>()
+        .component::<WsClientMessage>()
+        .component::<WsEvent>()
+        .component::<CameraStateEvent>()
+        .component::<ConnectionStats>(),
+    )
+    .post(
+        "/service/restart",
+        restart,
+        Operation::new("system", "Restart the service").empty("200", "Restart requested"),
+    )
+    .get(
+        "/register_service",
+        blueos::server_metadata,
+        Operation::new("blueos", "BlueOS extension metadata")
+            .json::<ServerMetadata>("200", "Service metadata"),
+    )
+    .get(
+        "/cockpit_extras.json",
+        cockpit::cockpit_extras,
+        Operation::new(
+            "cockpit",
+            "Widgets, actions, joystick suggestions and data-lake variables for Cockpit",
+        )
+        .json::<CockpitExtras>("200", "Cockpit extras"),
+    )
 }
 
+#[cfg(feature = "autopilot")]
 #[instrument(level = "debug")]
 async fn autopilot_control(Json(actuators_control): Json<ActuatorsControl>) -> impl IntoResponse {
     match control_bridge::autopilot_control(actuators_control).await {
-        Ok(value) => (StatusCode::OK, value.to_string()).into_response(),
+        Ok(value) => Json(value).into_response(),
         Err(error) => {
             let status =
                 StatusCode::from_u16(error.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
@@ -1,13 +1,56 @@
-use axum::{Router, http::StatusCode, routing::delete};
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use autopilot::api::SettingsImportSummary;
+use axum::{
+    Json,
+    extract::Query,
+    http::{StatusCode, header},
+    response::IntoResponse,
+};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
 use tracing::*;
 
+use super::docs::{ApiRouter, Operation};
+
+const EXPORT_FILENAME: &str = "br4kcam-settings.json";
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ImportQuery {
+    /// Only report which cameras the import would affect.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
 #[instrument(level = "trace")]
-pub fn router() -> Router {
-    Router::new()
-        .route("/", delete(clear))
-        .layer(TraceLayer::new_for_http())
-        .layer(CorsLayer::permissive())
+pub fn router() -> ApiRouter {
+    ApiRouter::new()
+        .delete(
+            "/",
+            clear,
+            Operation::new("system", "Clear the saved settings")
+                .empty("200", "Settings cleared")
+                .empty("500", "Settings could not be cleared"),
+        )
+        .get(
+            "/export",
+            export,
+            Operation::new("system", "Download the settings document, for backup")
+                .json::<Value>("200", "Settings document")
+                .text("500", "Why the settings could not be read"),
+        )
+        .post(
+            "/import",
+            import,
+            Operation::new(
+                "system",
+                "Restore a settings document and apply it to the affected cameras",
+            )
+            .query::<ImportQuery>()
+            .request::<Value>()
+            .json::<SettingsImportSummary>("200", "Affected cameras")
+            .text("400", "Not a valid settings document")
+            .text("500", "Why applying the settings failed"),
+        )
 }
 
 #[instrument(level = "debug")]
@@ -20,3 +63,41 @@ async fn clear() -> StatusCode {
         }
     }
 }
+
+#[instrument(level = "debug")]
+async fn export() -> impl IntoResponse {
+    match autopilot::export_settings().await {
+        Ok(document) => (
+            [(
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{EXPORT_FILENAME}\""),
+            )],
+            Json(document),
+        )
+            .into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{error:?}")).into_response(),
+    }
+}
+
+#[instrument(level = "debug", skip(document))]
+async fn import(
+    Query(query): Query<ImportQuery>,
+    Json(document): Json<Value>,
+) -> impl IntoResponse {
+    let plan = match autopilot::plan_settings_import(document).await {
+        Ok(plan) => plan,
+        Err(error) => return (StatusCode::BAD_REQUEST, format!("{error:#}")).into_response(),
+    };
+
+    if query.dry_run {
+        return Json(plan.summary).into_response();
+    }
+
+    match autopilot::apply_settings_import(plan).await {
+        Ok(summary) => Json(summary).into_response(),
+        Err(error) => {
+            warn!("Failed to import settings: {error:#?}");
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("{error:#}")).into_response()
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use autopilot::api::{AdoptVehicle, VehicleProfiles};
+use axum::{Json, http::StatusCode, response::IntoResponse};
+use tracing::*;
+
+use super::docs::{ApiRouter, Operation};
+
+#[instrument(level = "trace")]
+pub fn router() -> ApiRouter {
+    ApiRouter::new()
+        .get(
+            "/",
+            vehicles,
+            Operation::new(
+                "system",
+                "Vehicles with stored actuator profiles, and any unknown connected autopilot",
+            )
+            .json::<VehicleProfiles>("200", "Vehicle profiles")
+            .text("500", "Why the profiles could not be read"),
+        )
+        .post(
+            "/adopt",
+            adopt,
+            Operation::new(
+                "system",
+                "Start a profile for the connected, unknown autopilot",
+            )
+            .request::<AdoptVehicle>()
+            .json::<VehicleProfiles>("200", "Vehicle profiles")
+            .text("409", "The connected autopilot is already known"),
+        )
+}
+
+#[instrument(level = "debug")]
+async fn vehicles() -> impl IntoResponse {
+    match autopilot::vehicles().await {
+        Ok(vehicles) => Json(vehicles).into_response(),
+        Err(error) => (StatusCode::INTERNAL_SERVER_ERROR, format!("{error:#}")).into_response(),
+    }
+}
+
+#[instrument(level = "debug")]
+async fn adopt(Json(request): Json<AdoptVehicle>) -> impl IntoResponse {
+    match autopilot::adopt_vehicle(request).await {
+        Ok(vehicles) => Json(vehicles).into_response(),
+        Err(error) => {
+            warn!("Failed to adopt the autopilot: {error:#?}");
+            (StatusCode::CONFLICT, format!("{error:#}")).into_response()
+        }
+    }
+}
@@ -92,7 +92,7 @@ async fn websocket_connection(socket: WebSocket) {
     Span::current().record("connection_id", connection_id);
     let _guard = ConnectionGuard(connection_id);
     let (mut sender, mut receiver) = socket.split();
-    let mut cameras_receiver = mcm_client::subscribe_cameras();
+    let mut cameras_receiver = cameras::subscribe_cameras();
     let mut state_receiver = camera_state::subscribe_state();
     let mut health_receiver = connectivity::subscribe();
     let (response_tx, mut response_rx) = mpsc::channel::<String>(RESPONSE_QUEUE);
@@ -582,7 +582,7 @@ async fn send_camera_list_event(
     sender: &mut futures::stream::SplitSink<WebSocket, Message>,
     connection_id: ConnectionId,
 ) -> Result<(), SendFailure> {
-    let cameras = mcm_client::cameras().await;
+    let cameras = cameras::cameras().await;
     let body = match serde_json::to_value(cameras) {
         Ok(body) => body,
         Err(error) => {
@@ -730,7 +730,7 @@ async fn handle_request(request: WsRequest) -> WsResponse {
     let id = request.id;
 
     match (request.method.as_str(), request.path.as_str()) {
-        ("GET", "/camera/list") => match serde_json::to_value(mcm_client::cameras().await) {
+        ("GET", "/camera/list") => match serde_json::to_value(cameras::cameras().await) {
             Ok(body) => WsResponse::new(id, 200, body),
             Err(error) => WsResponse::new(id, 500, Value::String(format!("{error:?}"))),
         },
@@ -751,6 +751,7 @@ async fn handle_request(request: WsRequest) -> WsResponse {
                 Err(error) => WsResponse::new(id, error.status, Value::String(error.message)),
             }
         }
+        #[cfg(feature = "autopilot")]
         ("POST", "/autopilot/control") => {
             let Some(body) = request.body else {
                 return WsResponse::new(id, 400, Value::String("missing body".to_string()));
@@ -769,6 +770,27 @@ async fn handle_request(request: WsRequest) -> WsResponse {
                 Err(error) => WsResponse::new(id, error.status, Value::String(error.message)),
             }
         }
+        #[cfg(feature = "autopilot")]
+        ("POST", "/vehicles/adopt") => {
+            let request = match serde_json::from_value::<autopilot::api::AdoptVehicle>(
+                request
+                    .body
+                    .unwrap_or_else(|| Value::Object(Default::default())),
+            ) {
+                Ok(request) => request,
+                Err(error) => {
+                    return WsResponse::new(id, 400, Value::String(format!("{error:?}")));
+                }
+            };
+
+            match autopilot::adopt_vehicle(request).await {
+                Ok(vehicles) => match serde_json::to_value(vehicles) {
+                    Ok(body) => WsResponse::new(id, 200, body),
+                    Err(error) => WsResponse::new(id, 500, Value::String(format!("{error:?}"))),
+                },
+                Err(error) => WsResponse::new(id, 409, Value::String(format!("{error:#}"))),
+            }
+        }
         _ => WsResponse::new(id, 404, Value::String("not found".to_string())),
     }
 }
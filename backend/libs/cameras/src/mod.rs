@@ -0,0 +1,309 @@
+//! Registry of the 4K Cams this manager controls, by UUID.
+//!
+//! Cameras are either discovered through MCM or registered by hand with their address,
+//! for setups without MCM. Every camera consumer reads from here, so it does not matter
+//! where a camera came from.
+
+use std::{
+    collections::{HashMap, HashSet},
+    net::Ipv4Addr,
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use once_cell::sync::{Lazy, OnceCell};
+use schemars::JsonSchema;
+use serde_derive::{Deserialize, Serialize};
+use tokio::sync::{RwLock, broadcast};
+use tracing::*;
+use ts_rs::TS;
+use url::Url;
+use uuid::Uuid;
+
+static REGISTRY: Lazy<RwLock<Registry>> = Lazy::new(|| RwLock::new(Registry::default()));
+static CAMERAS_TX: OnceCell<broadcast::Sender<()>> = OnceCell::new();
+/// Per-camera hostname retained after the camera drops out of MCM discovery so its HTTP
+/// API stays addressable; entries are only removed on an explicit Forget, not on
+/// `remove_camera`.
+static LAST_HOSTNAMES: OnceCell<Mutex<HashMap<Uuid, Ipv4Addr>>> = OnceCell::new();
+
+#[derive(Debug, Default)]
+struct Registry {
+    cameras: Cameras,
+    /// Cameras registered by hand, which discovery must not drop.
+    manual: HashSet<Uuid>,
+}
+
+pub type Cameras = IndexMap<Uuid, Camera>;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema)]
+pub struct Camera {
+    pub uuid: Uuid,
+    pub hostname: Ipv4Addr,
+    /// Device login credentials; never serialized to API clients.
+    #[serde(skip)]
+    #[ts(skip)]
+    pub credentials: Option<Credentials>,
+    pub streams: Streams,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+/// Video streams MCM serves from a camera, by MCM stream id.
+pub type Streams = IndexMap<Uuid, Stream>;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema)]
+pub struct Stream {
+    pub name: String,
+    pub source_endpoint: Url,
+    pub stream_endpoints: Vec<Url>,
+    pub state: StreamStatusState,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamStatusState {
+    Running,
+    Idle,
+    Stopped,
+    #[default]
+    #[serde(other)]
+    Unknown,
+}
+
+impl Credentials {
+    /// The 4K Cam factory login.
+    pub fn factory() -> Self {
+        Self {
+            username: "admin".to_string(),
+            password: "blue".to_string(),
+        }
+    }
+}
+
+#[instrument(level = "debug")]
+pub async fn cameras() -> Cameras {
+    REGISTRY.read().await.cameras.clone()
+}
+
+pub fn subscribe_cameras() -> broadcast::Receiver<()> {
+    cameras_sender().subscribe()
+}
+
+/// Wake camera-list subscribers, e.g. after per-camera status they display changed.
+pub fn notify_cameras() {
+    if let Err(error) = cameras_sender().send(()) {
+        debug!("No camera-list subscribers: {error}");
+    }
+}
+
+/// Add or update a camera. The streams it already has are kept; those are reported
+/// through [`set_streams`].
+#[instrument(level = "debug")]
+pub async fn add_camera(camera: &Camera) -> Result<()> {
+    let mut lock = REGISTRY.write().await;
+
+    let mut camera = camera.clone();
+    if let Some(known_camera) = lock.cameras.get(&camera.uuid) {
+        camera.streams = known_camera.streams.clone();
+        if *known_camera == camera {
+            return Ok(());
+        }
+    }
+
+    remember_hostname(camera.uuid, camera.hostname);
+
+    if let Some(old_camera) = lock.cameras.insert(camera.uuid, camera) {
+        debug!("Camera updated: old: {old_camera:?}");
+    }
+
+    notify_cameras();
+
+    Ok(())
+}
+
+/// Register the camera at `hostname` by hand. Its UUID is derived from the address, so
+/// registering it again on every start keeps the same UUID, and with it its settings.
+#[instrument(level = "debug")]
+pub async fn register_camera(hostname: Ipv4Addr) -> Result<Camera> {
+    let camera = Camera {
+        uuid: manual_uuid(hostname),
+        hostname,
+        credentials: Some(Credentials::factory()),
+        streams: Streams::new(),
+    };
+
+    REGISTRY.write().await.manual.insert(camera.uuid);
+    add_camera(&camera).await?;
+    info!("Registered camera {} at {hostname}", camera.uuid);
+
+    Ok(camera)
+}
+
+/// True for cameras registered with [`register_camera`].
+pub async fn is_manual(uuid: &Uuid) -> bool {
+    REGISTRY.read().await.manual.contains(uuid)
+}
+
+#[instrument(level = "debug")]
+pub async fn get_camera(uuid: &Uuid) -> Option<Camera> {
+    REGISTRY.read().await.cameras.get(uuid).cloned()
+}
+
+/// Address to reach `uuid` over HTTP, falling back to the last hostname it was seen at.
+///
+/// ONVIF rediscovery drops cameras from the MCM list for a while after the video service
+/// restarts; the camera itself keeps answering, so control must not wait for discovery.
+pub async fn camera_address(uuid: &Uuid) -> Option<Ipv4Addr> {
+    if let Some(camera) = get_camera(uuid).await {
+        return Some(camera.hostname);
+    }
+    cached_hostname(uuid)
+}
+
+#[instrument(level = "debug")]
+pub async fn remove_camera(uuid: &Uuid) -> Result<Camera> {
+    let mut lock = REGISTRY.write().await;
+
+    let camera = lock
+        .cameras
+        .swap_remove(uuid)
+        .context("Camera not registered")?;
+    lock.manual.remove(uuid);
+    notify_cameras();
+
+    Ok(camera)
+}
+
+/// Record the streams MCM serves from `uuid`. Unknown cameras are ignored.
+#[instrument(level = "debug")]
+pub async fn set_streams(uuid: &Uuid, streams: Streams) {
+    let mut lock = REGISTRY.write().await;
+
+    let Some(camera) = lock.cameras.get_mut(uuid) else {
+        return;
+    };
+    if camera.streams != streams {
+        camera.streams = streams;
+        notify_cameras();
+    }
+}
+
+/// Drop every discovered camera, keeping the ones registered by hand without the streams
+/// discovery reported for them.
+#[instrument(level = "debug")]
+pub async fn clear_discovered() {
+    let mut lock = REGISTRY.write().await;
+
+    let Registry { cameras, manual } = &mut *lock;
+    cameras.retain(|uuid, _| manual.contains(uuid));
+    cameras
+        .values_mut()
+        .for_each(|camera| camera.streams.clear());
+    notify_cameras();
+}
+
+pub fn remember_hostname(uuid: Uuid, hostname: Ipv4Addr) {
+    last_hostnames()
+        .lock()
+        .expect("last hostnames lock")
+        .insert(uuid, hostname);
+}
+
+pub fn forget_hostname(uuid: Uuid) {
+    last_hostnames()
+        .lock()
+        .expect("last hostnames lock")
+        .remove(&uuid);
+}
+
+pub fn cached_hostname(uuid: &Uuid) -> Option<Ipv4Addr> {
+    last_hostnames()
+        .lock()
+        .expect("last hostnames lock")
+        .get(uuid)
+        .copied()
+}
+
+fn last_hostnames() -> &'static Mutex<HashMap<Uuid, Ipv4Addr>> {
+    LAST_HOSTNAMES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn cameras_sender() -> &'static broadcast::Sender<()> {
+    CAMERAS_TX.get_or_init(|| {
+        let (sender, _) = broadcast::channel(16);
+        sender
+    })
+}
+
+fn manual_uuid(hostname: Ipv4Addr) -> Uuid {
+    Uuid::new_v5(
+        &Uuid::NAMESPACE_URL,
+        format!("http://{hostname}/").as_bytes(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn registered_cameras_survive_discovery_cleanup() {
+        let hostname = Ipv4Addr::new(192, 168, 2, 10);
+        let manual = register_camera(hostname).await.unwrap();
+        assert_eq!(manual.uuid, manual_uuid(hostname));
+
+        let discovered = Camera {
+            uuid: Uuid::from_u128(1),
+            hostname: Ipv4Addr::new(192, 168, 2, 11),
+            credentials: None,
+            streams: Streams::new(),
+        };
+        add_camera(&discovered).await.unwrap();
+
+        clear_discovered().await;
+
+        assert!(is_manual(&manual.uuid).await);
+        assert_eq!(get_camera(&manual.uuid).await, Some(manual));
+        assert_eq!(get_camera(&discovered.uuid).await, None);
+        assert_eq!(
+            camera_address(&discovered.uuid).await,
+            Some(discovered.hostname)
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_outlive_discovery_updates() {
+        let uuid = Uuid::from_u128(2);
+        let mut camera = Camera {
+            uuid,
+            hostname: Ipv4Addr::new(192, 168, 2, 12),
+            credentials: None,
+            streams: Streams::new(),
+        };
+        add_camera(&camera).await.unwrap();
+
+        let stream = Stream {
+            name: "Main".to_string(),
+            source_endpoint: "rtsp://192.168.2.12:554/stream_0".parse().unwrap(),
+            stream_endpoints: Vec::new(),
+            state: StreamStatusState::Running,
+            error: None,
+        };
+        let streams = Streams::from([(Uuid::from_u128(3), stream)]);
+        set_streams(&uuid, streams.clone()).await;
+
+        camera.hostname = Ipv4Addr::new(192, 168, 2, 13);
+        add_camera(&camera).await.unwrap();
+
+        let known_camera = get_camera(&uuid).await.unwrap();
+        assert_eq!(known_camera.hostname, camera.hostname);
+        assert_eq!(known_camera.streams, streams);
+    }
+}
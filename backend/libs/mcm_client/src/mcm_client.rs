@@ -1,17 +1,17 @@
 use std::net::SocketAddr;
 
 use anyhow::{Context, Result, anyhow};
-use indexmap::IndexMap;
+use cameras::{Camera, Credentials, Streams};
 use tracing::*;
 
 use crate::mcm_types::{
-    ApiVideoSource, AuthenticateOnvifDeviceRequest, CaptureConfiguration, Format, Info,
-    OnvifDevice, OnvifDeviceInformation, PostStream, RemoveStream, StreamInformation, StreamStatus,
+    ApiVideoSource, AuthenticateOnvifDeviceRequest, CaptureConfiguration, Info, OnvifDevice,
+    OnvifDeviceInformation, PostStream, RemoveStream, StreamInformation, StreamStatus,
     VideoCaptureConfiguration, VideoEncodeType, VideoSourceOnvif, VideoSourceOnvifType,
     VideoSourceType,
 };
 
-use super::{Camera, Credentials, Stream};
+use super::{Stream, StreamPreferences};
 
 const KNOWN_BR4KCAM_HARDWARE: &[&str] = &["HW0100302", "HW20200610"];
 
@@ -99,7 +99,7 @@ impl MCMClient {
                     name,
                     source: VideoSourceOnvifType::Onvif(endpoint),
                     device_information:
-                        OnvifDeviceInformation {
+                        device_information @ OnvifDeviceInformation {
                             manufacturer,
                             model,
                             ..
@@ -126,14 +126,17 @@ impl MCMClient {
                     return None;
                 };
 
-                let stream_endpoints = device.video_and_stream.stream_information.endpoints;
+                let stream_information = device.video_and_stream.stream_information;
 
                 Some(Stream {
+                    id: device.id,
                     name: device.video_and_stream.name.clone(),
                     source_endpoint,
-                    stream_endpoints,
+                    stream_endpoints: stream_information.endpoints.clone(),
                     state: device.state,
                     error: device.error.clone(),
+                    device_information: Some(device_information.clone()),
+                    stream_information: Some(stream_information),
                 })
             })
             .collect::<Vec<Stream>>();
@@ -180,50 +183,104 @@ impl MCMClient {
     }
 
     #[instrument(level = "debug", skip(self))]
-    pub async fn create_stream(&self, source: ApiVideoSource) -> Result<Vec<StreamStatus>> {
-        let formats = source
-            .formats
-            .into_iter()
-            .filter(|format| matches!(format.encode, VideoEncodeType::H264 | VideoEncodeType::H265))
-            .collect::<Vec<Format>>();
-
-        // TODO: Decide a way to get the format
-        let format = formats.first().context("Empty formats")?;
-        let encode = format.encode.to_owned();
-        let size = format.sizes.first().context("Empty sizes")?.to_owned();
-        let frame_interval = size
-            .intervals
-            .first()
-            .context("Empty intervals")?
-            .to_owned();
-
-        // TODO: When using DHCP, there's no guarantee that the IP of the camera is kept the same, so we NEED to find a more robust ID
-        let id = {
-            let source: url::Url = source.source.clone().parse()?;
-
-            let host = source.host_str().context("No host")?;
-            let stream_id = source.path().chars().last().context("No path")?;
-
-            format!("{host}/{stream_id}")
-        };
+    pub async fn create_stream(&self, stream: &PostStream) -> Result<Vec<StreamStatus>> {
+        web_client::post(&self.address, "streams", stream, ()).await
+    }
+}
 
-        let data = PostStream {
-            name: format!("4K Cam {id}"),
-            source: source.source,
-            stream_information: StreamInformation {
-                endpoints: vec![format!("rtsp://0.0.0.0:8554/br4kcam_{id}").parse()?],
-                configuration: CaptureConfiguration::Video(VideoCaptureConfiguration {
-                    encode,
-                    height: size.height,
-                    width: size.width,
-                    frame_interval,
-                }),
-                extended_configuration: None,
-            },
-        };
+/// The MCM stream a camera source gets under `preferences`.
+pub(crate) fn stream_request(
+    source: &ApiVideoSource,
+    preferences: &StreamPreferences,
+) -> Result<PostStream> {
+    let format = source
+        .formats
+        .iter()
+        .filter(|format| matches!(format.encode, VideoEncodeType::H264 | VideoEncodeType::H265))
+        .find(|format| {
+            preferences
+                .encode
+                .as_ref()
+                .is_none_or(|encode| encode == &format.encode)
+        })
+        .with_context(|| match &preferences.encode {
+            Some(encode) => format!("Source offers no {encode:?} format"),
+            None => "Source offers no H264 or H265 format".to_string(),
+        })?;
+    let size = format
+        .sizes
+        .iter()
+        .find(|size| {
+            preferences
+                .size
+                .is_none_or(|wanted| wanted.width == size.width && wanted.height == size.height)
+        })
+        .with_context(|| match &preferences.size {
+            Some(size) => format!(
+                "{:?} is not offered at {}x{}",
+                format.encode, size.width, size.height
+            ),
+            None => format!("{:?} offers no sizes", format.encode),
+        })?;
+    let frame_interval = size
+        .intervals
+        .iter()
+        .find(|interval| {
+            preferences
+                .frame_interval
+                .as_ref()
+                .is_none_or(|wanted| wanted == *interval)
+        })
+        .with_context(|| match &preferences.frame_interval {
+            Some(interval) => format!(
+                "{}x{} is not offered at a {}/{}s frame interval",
+                size.width, size.height, interval.numerator, interval.denominator
+            ),
+            None => format!("{}x{} offers no frame intervals", size.width, size.height),
+        })?
+        .to_owned();
+
+    let id = stream_id(&source.source)?;
+
+    let endpoints = if preferences.endpoints.is_empty() {
+        vec![format!("rtsp://0.0.0.0:8554/br4kcam_{id}").parse()?]
+    } else {
+        preferences.endpoints.clone()
+    };
+
+    Ok(PostStream {
+        name: stream_name(&id),
+        source: source.source.clone(),
+        stream_information: StreamInformation {
+            endpoints,
+            configuration: CaptureConfiguration::Video(VideoCaptureConfiguration {
+                encode: format.encode.to_owned(),
+                height: size.height,
+                width: size.width,
+                frame_interval,
+            }),
+            extended_configuration: None,
+        },
+    })
+}
 
-        web_client::post(&self.address, "streams", data, ()).await
-    }
+/// Name of the stream we create for a camera source.
+pub(crate) fn source_stream_name(source: &str) -> Result<String> {
+    Ok(stream_name(&stream_id(source)?))
+}
+
+// TODO: When using DHCP, there's no guarantee that the IP of the camera is kept the same, so we NEED to find a more robust ID
+fn stream_id(source: &str) -> Result<String> {
+    let source: url::Url = source.parse()?;
+
+    let host = source.host_str().context("No host")?;
+    let stream_id = source.path().chars().last().context("No path")?;
+
+    Ok(format!("{host}/{stream_id}"))
+}
+
+fn stream_name(id: &str) -> String {
+    format!("4K Cam {id}")
 }
 
 fn br4kcams_from_onvif_devices(
@@ -250,11 +307,8 @@ fn br4kcams_from_onvif_devices(
             Some(Camera {
                 hostname: device.ip,
                 uuid: device.uuid,
-                credentials: Some(Credentials {
-                    username: "admin".to_string(),
-                    password: "blue".to_string(),
-                }),
-                streams: IndexMap::new(),
+                credentials: Some(Credentials::factory()),
+                streams: Streams::new(),
             })
         })
         .collect()
@@ -263,6 +317,55 @@ fn br4kcams_from_onvif_devices(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        StreamChannel, StreamSize,
+        mcm_types::{Format, FrameInterval, Size},
+    };
+    use url::Url;
+
+    fn source() -> ApiVideoSource {
+        let size = |width, height| Size {
+            width,
+            height,
+            intervals: vec![
+                FrameInterval {
+                    numerator: 1,
+                    denominator: 30,
+                },
+                FrameInterval {
+                    numerator: 1,
+                    denominator: 25,
+                },
+            ],
+        };
+
+        ApiVideoSource {
+            name: "UnderwaterCam - IPCamera (UnderwaterCam)".to_string(),
+            source: "rtsp://192.168.2.10:554/stream_1".to_string(),
+            formats: vec![
+                Format {
+                    encode: VideoEncodeType::Mjpg,
+                    sizes: vec![size(640, 480)],
+                },
+                Format {
+                    encode: VideoEncodeType::H264,
+                    sizes: vec![size(1920, 1080), size(1280, 720)],
+                },
+                Format {
+                    encode: VideoEncodeType::H265,
+                    sizes: vec![size(3840, 2160)],
+                },
+            ],
+        }
+    }
+
+    fn video_configuration(stream: &PostStream) -> &VideoCaptureConfiguration {
+        let CaptureConfiguration::Video(configuration) = &stream.stream_information.configuration
+        else {
+            panic!("Not a video stream: {stream:?}");
+        };
+        configuration
+    }
 
     #[test]
     fn supported_versions_are_not_capped_at_the_next_minor() {
@@ -273,4 +376,87 @@ mod tests {
         assert!(supported.matches(&semver::Version::parse("0.3.0").unwrap()));
         assert!(supported.matches(&semver::Version::parse("1.0.0").unwrap()));
     }
+
+    #[test]
+    fn unset_preferences_take_the_first_h26x_format() {
+        let stream = stream_request(&source(), &StreamPreferences::default()).unwrap();
+        let configuration = video_configuration(&stream);
+
+        assert_eq!(stream.name, "4K Cam 192.168.2.10/1");
+        assert_eq!(configuration.encode, VideoEncodeType::H264);
+        assert_eq!((configuration.width, configuration.height), (1920, 1080));
+        assert_eq!(configuration.frame_interval.denominator, 30);
+        assert_eq!(
+            stream.stream_information.endpoints,
+            vec![Url::parse("rtsp://0.0.0.0:8554/br4kcam_192.168.2.10/1").unwrap()]
+        );
+    }
+
+    #[test]
+    fn preferences_pick_the_format_and_endpoints() {
+        let endpoints = vec![
+            Url::parse("udp://192.168.2.1:5600").unwrap(),
+            Url::parse("rtsp://0.0.0.0:8554/pilot").unwrap(),
+            Url::parse("rtsp://0.0.0.0:8554/recorder").unwrap(),
+        ];
+        let preferences = StreamPreferences {
+            channel: StreamChannel::Aux,
+            encode: Some(VideoEncodeType::H264),
+            size: Some(StreamSize {
+                width: 1280,
+                height: 720,
+            }),
+            frame_interval: Some(FrameInterval {
+                numerator: 1,
+                denominator: 25,
+            }),
+            endpoints: endpoints.clone(),
+        };
+        preferences.validate().unwrap();
+
+        let stream = stream_request(&source(), &preferences).unwrap();
+        let configuration = video_configuration(&stream);
+
+        assert_eq!((configuration.width, configuration.height), (1280, 720));
+        assert_eq!(configuration.frame_interval.denominator, 25);
+        assert_eq!(stream.stream_information.endpoints, endpoints);
+
+        let h265 = StreamPreferences {
+            encode: Some(VideoEncodeType::H265),
+            ..Default::default()
+        };
+        let stream = stream_request(&source(), &h265).unwrap();
+        assert_eq!(video_configuration(&stream).width, 3840);
+
+        let unavailable = StreamPreferences {
+            encode: Some(VideoEncodeType::H265),
+            size: Some(StreamSize {
+                width: 1280,
+                height: 720,
+            }),
+            ..Default::default()
+        };
+        assert!(stream_request(&source(), &unavailable).is_err());
+    }
+
+    #[test]
+    fn only_h26x_over_rtsp_or_udp_is_accepted() {
+        let mjpg = StreamPreferences {
+            encode: Some(VideoEncodeType::Mjpg),
+            ..Default::default()
+        };
+        assert!(mjpg.validate().is_err());
+
+        for endpoint in [
+            "http://0.0.0.0:8554/pilot",
+            "rtsp://0.0.0.0:8554/",
+            "udp://192.168.2.1",
+        ] {
+            let preferences = StreamPreferences {
+                endpoints: vec![Url::parse(endpoint).unwrap()],
+                ..Default::default()
+            };
+            assert!(preferences.validate().is_err(), "{endpoint}");
+        }
+    }
 }
@@ -1,10 +1,11 @@
 use std::net::Ipv4Addr;
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use url::Url;
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub struct Info {
     /// Name of the program
     pub name: String,
@@ -17,7 +18,7 @@ pub struct Info {
     pub authors: String,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub struct ApiVideoSource {
     pub name: String,
     pub source: String,
@@ -25,13 +26,13 @@ pub struct ApiVideoSource {
     // pub controls: Vec<Control>, # We don't need this
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 pub struct Format {
     pub encode: VideoEncodeType,
     pub sizes: Vec<Size>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum VideoEncodeType {
     H264,
@@ -42,40 +43,40 @@ pub enum VideoEncodeType {
     Yuyv,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 pub struct Size {
     pub width: u32,
     pub height: u32,
     pub intervals: Vec<FrameInterval>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 pub struct FrameInterval {
     pub numerator: u32,
     pub denominator: u32,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 pub struct StreamInformation {
     pub endpoints: Vec<Url>,
     pub configuration: CaptureConfiguration,
     pub extended_configuration: Option<ExtendedConfiguration>,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum CaptureConfiguration {
     Video(VideoCaptureConfiguration),
     Redirect(RedirectCaptureConfiguration),
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 pub struct ExtendedConfiguration {
     pub thermal: bool,
     pub disable_mavlink: bool,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 pub struct VideoCaptureConfiguration {
     pub encode: VideoEncodeType,
     pub height: u32,
@@ -83,21 +84,12 @@ pub struct VideoCaptureConfiguration {
     pub frame_interval: FrameInterval,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 pub struct RedirectCaptureConfiguration {}
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize, TS)]
-#[serde(rename_all = "lowercase")]
-pub enum StreamStatusState {
-    Running,
-    Idle,
-    Stopped,
-    #[default]
-    #[serde(other)]
-    Unknown,
-}
+pub use cameras::StreamStatusState;
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub struct StreamStatus {
     pub id: uuid::Uuid,
     pub running: bool,
@@ -108,20 +100,20 @@ pub struct StreamStatus {
     pub mavlink: Option<MavlinkComponent>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub struct VideoAndStreamInformation {
     pub name: String,
     pub stream_information: StreamInformation,
     pub video_source: VideoSourceType,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
 pub struct MavlinkComponent {
     pub system_id: u8,
     pub component_id: u8,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub enum VideoSourceType {
     Gst(VideoSourceGst),
     Local(VideoSourceLocal),
@@ -129,13 +121,13 @@ pub enum VideoSourceType {
     Redirect(VideoSourceRedirect),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub struct VideoSourceGst {
     pub name: String,
     pub source: VideoSourceGstType,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub enum VideoSourceGstType {
     // TODO: local should have a pipeline also
     Local(VideoSourceLocal),
@@ -143,7 +135,7 @@ pub enum VideoSourceGstType {
     QR(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub struct VideoSourceLocal {
     pub name: String,
     pub device_path: String,
@@ -151,25 +143,25 @@ pub struct VideoSourceLocal {
     pub typ: VideoSourceLocalType,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub enum VideoSourceLocalType {
     Unknown(String),
     Usb(String),
     LegacyRpiCam(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub struct VideoSourceRedirect {
     pub name: String,
     pub source: VideoSourceRedirectType,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub enum VideoSourceRedirectType {
     Redirect(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub struct VideoSourceOnvif {
     pub name: String,
     pub source: VideoSourceOnvifType,
@@ -177,12 +169,12 @@ pub struct VideoSourceOnvif {
     pub device_information: OnvifDeviceInformation,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 pub enum VideoSourceOnvifType {
     Onvif(String),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, TS, JsonSchema)]
 pub struct OnvifDeviceInformation {
     pub manufacturer: String,
     pub model: String,
@@ -191,19 +183,27 @@ pub struct OnvifDeviceInformation {
     pub hardware_id: String,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS, JsonSchema)]
 pub struct PostStream {
     pub name: String,
     pub source: String,
     pub stream_information: StreamInformation,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS, JsonSchema)]
 pub struct RemoveStream {
     pub name: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, TS, JsonSchema)]
+pub struct ThumbnailRequest {
+    /// Video source the thumbnail is taken from.
+    pub source: String,
+    /// JPEG quality, from 1 to 100.
+    pub quality: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TS, JsonSchema)]
 pub struct OnvifDevice {
     pub uuid: uuid::Uuid,
     pub ip: Ipv4Addr,
@@ -213,7 +213,7 @@ pub struct OnvifDevice {
     pub urls: Vec<Url>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS, JsonSchema)]
 pub struct AuthenticateOnvifDeviceRequest {
     /// Onvif Device UUID, obtained via `/onvif/devices` get request
     pub device_uuid: uuid::Uuid,
@@ -223,7 +223,7 @@ pub struct AuthenticateOnvifDeviceRequest {
     pub password: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, TS, JsonSchema)]
 pub struct UnauthenticateOnvifDeviceRequest {
     /// Onvif Device UUID, obtained via `/onvif/devices` get request
     pub device_uuid: uuid::Uuid,
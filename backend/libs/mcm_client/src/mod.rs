@@ -7,24 +7,29 @@ use std::{
 
 use anyhow::{Context, Result};
 use br4kcam_api::McmHealth;
-use indexmap::IndexMap;
-use mcm_client::MCMClient;
+use cameras::{Camera, notify_cameras};
+use mcm_client::{MCMClient, source_stream_name, stream_request};
 use once_cell::sync::OnceCell;
-use serde::{Deserialize, Serialize};
 use tokio::{
     sync::{RwLock, broadcast},
     task::JoinHandle,
 };
 use tracing::*;
-use ts_rs::TS;
 use url::Url;
 use uuid::Uuid;
 
-use mcm_types::StreamStatusState;
+use mcm_types::{
+    OnvifDeviceInformation, PostStream, StreamInformation, StreamStatusState, ThumbnailRequest,
+};
 
 // note: keep this private to isolate MCM API from the rest of the code
 pub(crate) mod mcm_client;
 pub mod mcm_types;
+mod stream_preferences;
+
+pub use stream_preferences::{
+    StreamChannel, StreamPreferences, StreamSize, set_stream_preferences, stream_preferences,
+};
 
 const MCM_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
 const MCM_MUTATION_TIMEOUT: Duration = Duration::from_secs(60);
@@ -33,13 +38,8 @@ const MCM_FAILURES_TO_DOWN: u32 = 3;
 const STREAM_FAILURES_TO_RECREATE: u32 = 60;
 
 static MANAGER: OnceCell<RwLock<Manager>> = OnceCell::new();
-static CAMERAS_TX: OnceCell<broadcast::Sender<()>> = OnceCell::new();
 static HEALTH: OnceCell<Mutex<HealthState>> = OnceCell::new();
 static HEALTH_TX: OnceCell<broadcast::Sender<()>> = OnceCell::new();
-/// Per-camera hostname retained after the camera drops out of MCM discovery so its HTTP
-/// API stays addressable; entries are only removed on an explicit Forget, not on
-/// `remove_camera`.
-static LAST_HOSTNAMES: OnceCell<Mutex<HashMap<Uuid, Ipv4Addr>>> = OnceCell::new();
 
 struct HealthState {
     state: McmHealth,
@@ -60,41 +60,64 @@ pub struct McmHealthSnapshot {
 
 #[derive(Debug)]
 struct Manager {
-    cameras: Cameras,
     auth_failures: HashMap<Uuid, String>,
     stream_failures: HashMap<Uuid, String>,
+    /// Hardware serial reported by ONVIF for each discovered camera.
+    serials: HashMap<Uuid, String>,
+    /// MCM streams served from each discovered camera, as of the last poll.
+    streams: HashMap<Uuid, Vec<Stream>>,
+    mcm_address: SocketAddr,
     _authentication_task_handler: JoinHandle<()>,
     _start_br4kcams_task_handler: JoinHandle<()>,
 }
 
-pub type Cameras = IndexMap<Uuid, Camera>;
-
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TS)]
-pub struct Camera {
-    pub uuid: Uuid,
-    pub hostname: Ipv4Addr,
-    /// Device login credentials; never serialized to API clients.
-    #[serde(skip)]
-    #[ts(skip)]
-    pub credentials: Option<Credentials>,
-    pub streams: Streams,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TS)]
-pub struct Credentials {
-    pub username: String,
-    pub password: String,
-}
-
-pub type Streams = IndexMap<Uuid, Stream>;
-
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, TS)]
+/// An MCM stream, with what the API shows of it in [`cameras::Stream`].
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Stream {
+    id: Uuid,
     name: String,
     source_endpoint: Url,
     stream_endpoints: Vec<Url>,
     state: StreamStatusState,
     error: Option<String>,
+    /// ONVIF identity of the source device.
+    device_information: Option<OnvifDeviceInformation>,
+    /// What MCM was asked to stream, to tell when the preferences changed.
+    stream_information: Option<StreamInformation>,
+}
+
+impl Stream {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn stream_endpoints(&self) -> &[Url] {
+        &self.stream_endpoints
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.state == StreamStatusState::Running
+    }
+
+    pub fn device_information(&self) -> Option<&OnvifDeviceInformation> {
+        self.device_information.as_ref()
+    }
+
+    fn to_camera_stream(&self) -> cameras::Stream {
+        cameras::Stream {
+            name: self.name.clone(),
+            source_endpoint: self.source_endpoint.clone(),
+            stream_endpoints: self.stream_endpoints.clone(),
+            state: self.state,
+            error: self.error.clone(),
+        }
+    }
+
+    /// ONVIF serial number of the source device, when it reports one.
+    fn serial_number(&self) -> Option<&str> {
+        let serial = self.device_information.as_ref()?.serial_number.trim();
+        (!serial.is_empty()).then_some(serial)
+    }
 }
 
 /// Constructs our manager, Should be done inside main
@@ -104,6 +127,7 @@ pub async fn init(mcm_address: SocketAddr, skip_hardware_check: bool) {
         let mut lock = manager.write().await;
         lock._authentication_task_handler.abort();
         lock._start_br4kcams_task_handler.abort();
+        lock.mcm_address = mcm_address;
         lock._authentication_task_handler =
             tokio::spawn(
                 async move { authenticate_br4kcams(&mcm_address, skip_hardware_check).await },
@@ -115,7 +139,6 @@ pub async fn init(mcm_address: SocketAddr, skip_hardware_check: bool) {
         return;
     }
 
-    let cameras = IndexMap::new();
     let _authentication_task_handler =
         tokio::spawn(async move { authenticate_br4kcams(&mcm_address, skip_hardware_check).await });
     let _start_br4kcams_task_handler =
@@ -125,9 +148,11 @@ pub async fn init(mcm_address: SocketAddr, skip_hardware_check: bool) {
 
     MANAGER.get_or_init(|| {
         RwLock::new(Manager {
-            cameras,
             auth_failures: HashMap::new(),
             stream_failures: HashMap::new(),
+            serials: HashMap::new(),
+            streams: HashMap::new(),
+            mcm_address,
             _authentication_task_handler,
             _start_br4kcams_task_handler,
         })
@@ -140,10 +165,13 @@ pub async fn shutdown() {
         let mut lock = manager.write().await;
         lock._authentication_task_handler.abort();
         lock._start_br4kcams_task_handler.abort();
-        lock.cameras.clear();
         lock.auth_failures.clear();
         lock.stream_failures.clear();
+        lock.serials.clear();
+        lock.streams.clear();
     }
+
+    cameras::clear_discovered().await;
 }
 
 impl Drop for Manager {
@@ -179,11 +207,12 @@ async fn authenticate_br4kcams(mcm_address: &SocketAddr, skip_hardware_check: bo
             };
             report_success(mcm.address);
 
-            let known_cameras = cameras().await;
+            let known_cameras = cameras::cameras().await;
             let br4kcam_uuids: HashSet<Uuid> = br4kcams.iter().map(|camera| camera.uuid).collect();
             prune_auth_failures(&br4kcam_uuids).await;
             for uuid in known_cameras.keys() {
                 if !br4kcam_uuids.contains(uuid)
+                    && !cameras::is_manual(uuid).await
                     && let Err(error) = remove_camera(uuid).await
                 {
                     debug!("Failed removing stale camera {uuid}: {error:?}");
@@ -193,7 +222,7 @@ async fn authenticate_br4kcams(mcm_address: &SocketAddr, skip_hardware_check: bo
             for camera in &br4kcams {
                 if let Some(known_camera) = known_cameras.get(&camera.uuid) {
                     if known_camera != camera
-                        && let Err(error) = add_camera(camera).await
+                        && let Err(error) = cameras::add_camera(camera).await
                     {
                         debug!("Failed updating camera {camera:?}: {error:?}");
                         continue;
@@ -220,7 +249,7 @@ async fn authenticate_br4kcams(mcm_address: &SocketAddr, skip_hardware_check: bo
 
                 clear_auth_failure(&camera.uuid).await;
 
-                if let Err(error) = add_camera(camera).await {
+                if let Err(error) = cameras::add_camera(camera).await {
                     debug!("Failed adding camera {camera:?}: {error:?}");
                     continue;
                 }
@@ -254,6 +283,7 @@ async fn start_br4kcams_streams(mcm_address: &SocketAddr, skip_hardware_check: b
             else {
                 break;
             };
+            record_streams(&existing_br4kcam_streams).await;
 
             let Some(available_br4kcam_sources) =
                 await_mcm_probe(mcm.address, "v4l", || mcm.get_br4kcam_video_sources()).await
@@ -264,9 +294,9 @@ async fn start_br4kcams_streams(mcm_address: &SocketAddr, skip_hardware_check: b
             let mut observed_sources = HashSet::new();
 
             for source in available_br4kcam_sources {
-                if !source.source.ends_with("stream_0") {
-                    continue; // We only want the main stream
-                }
+                let Some(channel) = StreamChannel::of_source(&source.source) else {
+                    continue;
+                };
 
                 let Ok(mut available_source) = source.source.parse::<Url>() else {
                     warn!(
@@ -291,8 +321,57 @@ async fn start_br4kcams_streams(mcm_address: &SocketAddr, skip_hardware_check: b
                     existing_source.eq(&available_source)
                 });
 
+                let preferences = source_stream_preferences(&available_source).await;
+
+                if channel != preferences.channel {
+                    // Only the stream we made for a channel the camera no longer streams goes.
+                    if let Some(stream) = matching_stream
+                        && source_stream_name(&source.source).is_ok_and(|name| name == stream.name)
+                    {
+                        stream_failure_counts.remove(&available_source);
+                        if let Err(error) = await_mcm_mutation(mcm.address, "delete_stream", || {
+                            mcm.delete_stream(&stream.name)
+                        })
+                        .await
+                        {
+                            warn!(
+                                "Failed deleting unwanted stream {:?}: {error:?}",
+                                stream.name
+                            );
+                        }
+                    }
+
+                    continue;
+                }
+
+                let request = match stream_request(&source, &preferences) {
+                    Ok(request) => request,
+                    Err(error) => {
+                        debug!("Cannot stream {available_source} as preferred: {error:?}");
+                        let detail = format!("Stream preferences: {error:#}");
+                        set_stream_failure_for_source(
+                            &available_source,
+                            StreamStatusState::Stopped,
+                            Some(&detail),
+                        )
+                        .await;
+                        continue;
+                    }
+                };
+
                 if let Some(stream) = matching_stream {
-                    if stream_needs_recreation(stream.state) {
+                    let outdated = stream.stream_information.as_ref().is_some_and(|current| {
+                        !streams_as_requested(current, &request.stream_information)
+                    });
+
+                    if outdated {
+                        info!(
+                            "Recreating stream {:?} from the camera's stream preferences",
+                            stream.name
+                        );
+                        stream_failure_counts.remove(&available_source);
+                        recreate_stream(&mcm, &stream.name, &request).await;
+                    } else if stream_needs_recreation(stream.state) {
                         let count = stream_failure_counts
                             .entry(available_source.clone())
                             .or_insert(0);
@@ -305,23 +384,8 @@ async fn start_br4kcams_streams(mcm_address: &SocketAddr, skip_hardware_check: b
                                 stream.error.as_deref(),
                             )
                             .await;
-                            if let Err(error) =
-                                await_mcm_mutation(mcm.address, "delete_stream", || {
-                                    mcm.delete_stream(&stream.name)
-                                })
-                                .await
-                            {
-                                warn!("Failed deleting failed stream {:?}: {error:?}", stream.name);
-                            } else {
+                            if recreate_stream(&mcm, &stream.name, &request).await {
                                 *count = 0;
-                                if let Err(error) =
-                                    await_mcm_mutation(mcm.address, "streams", || {
-                                        mcm.create_stream(source)
-                                    })
-                                    .await
-                                {
-                                    warn!("Failed recreating stream: {error:?}");
-                                }
                             }
                         }
                     } else {
@@ -335,7 +399,7 @@ async fn start_br4kcams_streams(mcm_address: &SocketAddr, skip_hardware_check: b
                 stream_failure_counts.remove(&available_source);
 
                 if let Err(error) =
-                    await_mcm_mutation(mcm.address, "streams", || mcm.create_stream(source)).await
+                    await_mcm_mutation(mcm.address, "streams", || mcm.create_stream(&request)).await
                 {
                     warn!("Failed creating stream: {error:?}");
                     continue;
@@ -359,16 +423,54 @@ pub async fn stream_failure(uuid: &Uuid) -> Option<String> {
     manager.read().await.stream_failures.get(uuid).cloned()
 }
 
-#[instrument(level = "debug")]
-pub async fn cameras() -> Cameras {
+/// Hardware serials of every discovered camera that reported one.
+pub async fn camera_serials() -> HashMap<Uuid, String> {
     let Some(manager) = MANAGER.get() else {
-        return IndexMap::new();
+        return HashMap::new();
     };
-    manager.read().await.cameras.clone()
+    manager.read().await.serials.clone()
 }
 
-pub fn subscribe_cameras() -> broadcast::Receiver<()> {
-    cameras_sender().subscribe()
+/// MCM streams served from a discovered camera, as of the last poll.
+pub async fn camera_streams(uuid: &Uuid) -> Vec<Stream> {
+    let Some(manager) = MANAGER.get() else {
+        return Vec::new();
+    };
+    manager
+        .read()
+        .await
+        .streams
+        .get(uuid)
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Grab a JPEG frame from the MCM stream of a discovered camera.
+#[instrument(level = "debug")]
+pub async fn grab_frame(uuid: &Uuid) -> Result<Vec<u8>> {
+    let (mcm_address, source) = {
+        let manager = MANAGER.get().context("Not available")?.read().await;
+        let streams = manager
+            .streams
+            .get(uuid)
+            .context("Camera has no MCM stream")?;
+        let stream = streams
+            .iter()
+            .find(|stream| stream.is_running())
+            .or(streams.first())
+            .context("Camera has no MCM stream")?;
+        (manager.mcm_address, stream.source_endpoint.to_string())
+    };
+
+    web_client::get_bytes(
+        &mcm_address,
+        "thumbnail",
+        ThumbnailRequest {
+            source,
+            quality: 95,
+        },
+    )
+    .await
 }
 
 pub fn health() -> McmHealthSnapshot {
@@ -476,93 +578,60 @@ fn report_success(address: SocketAddr) {
     }
 }
 
-fn last_hostnames() -> &'static Mutex<HashMap<Uuid, Ipv4Addr>> {
-    LAST_HOSTNAMES.get_or_init(|| Mutex::new(HashMap::new()))
-}
-
-pub fn remember_hostname(uuid: Uuid, hostname: Ipv4Addr) {
-    last_hostnames()
-        .lock()
-        .expect("last hostnames lock")
-        .insert(uuid, hostname);
-}
-
-pub fn forget_hostname(uuid: Uuid) {
-    last_hostnames()
-        .lock()
-        .expect("last hostnames lock")
-        .remove(&uuid);
-}
-
-pub fn cached_hostname(uuid: &Uuid) -> Option<Ipv4Addr> {
-    last_hostnames()
-        .lock()
-        .expect("last hostnames lock")
-        .get(uuid)
-        .copied()
-}
+/// Drop a camera that left discovery, along with its stream failure.
+#[instrument(level = "debug")]
+async fn remove_camera(uuid: &Uuid) -> Result<Camera> {
+    let camera = cameras::remove_camera(uuid).await?;
+    if let Some(manager) = MANAGER.get() {
+        manager.write().await.stream_failures.remove(uuid);
+    }
 
-fn cameras_sender() -> &'static broadcast::Sender<()> {
-    CAMERAS_TX.get_or_init(|| {
-        let (sender, _) = broadcast::channel(16);
-        sender
-    })
+    Ok(camera)
 }
 
-fn notify_cameras() {
-    if let Err(error) = cameras_sender().send(()) {
-        debug!("No camera-list subscribers: {error}");
+/// Replace a stream with `request`, returning whether the old one could be deleted.
+async fn recreate_stream(mcm: &MCMClient, name: &str, request: &PostStream) -> bool {
+    if let Err(error) =
+        await_mcm_mutation(mcm.address, "delete_stream", || mcm.delete_stream(name)).await
+    {
+        warn!("Failed deleting stream {name:?}: {error:?}");
+        return false;
     }
-}
-
-#[instrument(level = "debug")]
-pub async fn add_camera(camera: &Camera) -> Result<()> {
-    let mut lock = MANAGER.get().unwrap().write().await;
 
-    last_hostnames()
-        .lock()
-        .expect("last hostnames lock")
-        .insert(camera.uuid, camera.hostname);
-
-    if let Some(old_camera) = lock.cameras.insert(camera.uuid, camera.clone()) {
-        debug!("Camera updated: old: {old_camera:?}");
+    if let Err(error) =
+        await_mcm_mutation(mcm.address, "streams", || mcm.create_stream(request)).await
+    {
+        warn!("Failed recreating stream: {error:?}");
     }
 
-    notify_cameras();
-
-    Ok(())
+    true
 }
 
-#[instrument(level = "debug")]
-pub async fn get_camera(uuid: &Uuid) -> Option<Camera> {
-    let manager = MANAGER.get()?.read().await;
-    manager.cameras.get(uuid).cloned()
-}
-
-/// Address to reach `uuid` over HTTP, falling back to the last hostname it was seen at.
-///
-/// ONVIF rediscovery drops cameras from the MCM list for a while after the video service
-/// restarts; the camera itself keeps answering, so control must not wait for discovery.
-pub async fn camera_address(uuid: &Uuid) -> Option<Ipv4Addr> {
-    if let Some(camera) = get_camera(uuid).await {
-        return Some(camera.hostname);
-    }
-    last_hostnames()
-        .lock()
-        .expect("last hostnames lock")
-        .get(uuid)
-        .copied()
-}
+/// Whether MCM streams what was requested, in whatever order it lists the endpoints.
+fn streams_as_requested(current: &StreamInformation, requested: &StreamInformation) -> bool {
+    let sorted = |endpoints: &[Url]| {
+        let mut endpoints = endpoints.to_vec();
+        endpoints.sort();
+        endpoints
+    };
 
-#[instrument(level = "debug")]
-pub async fn remove_camera(uuid: &Uuid) -> Result<Camera> {
-    let mut lock = MANAGER.get().unwrap().write().await;
+    current.configuration == requested.configuration
+        && sorted(&current.endpoints) == sorted(&requested.endpoints)
+}
 
-    let camera = lock.cameras.swap_remove(uuid).context("context")?;
-    lock.stream_failures.remove(uuid);
-    notify_cameras();
+/// Stream preferences of the camera serving `source`; the defaults for cameras not yet
+/// known or when the settings cannot be read.
+async fn source_stream_preferences(source: &Url) -> StreamPreferences {
+    let Some(camera_uuid) = camera_uuid_for_stream_source(source).await else {
+        return StreamPreferences::default();
+    };
 
-    Ok(camera)
+    stream_preferences(&camera_uuid)
+        .await
+        .unwrap_or_else(|error| {
+            debug!("Failed reading stream preferences of {camera_uuid}: {error:?}");
+            StreamPreferences::default()
+        })
 }
 
 fn stream_needs_recreation(state: StreamStatusState) -> bool {
@@ -660,6 +729,56 @@ async fn clear_stream_failure_for_source(source: &Url) {
     }
 }
 
+/// Group streams by the camera serving them and record each camera's ONVIF serial.
+async fn record_streams(streams: &[Stream]) {
+    let Some(manager) = MANAGER.get() else {
+        return;
+    };
+    let mut streams_by_camera: HashMap<Uuid, Vec<Stream>> = HashMap::new();
+    for stream in streams {
+        if let Some(uuid) = camera_uuid_for_stream_source(&stream.source_endpoint).await {
+            streams_by_camera
+                .entry(uuid)
+                .or_default()
+                .push(stream.clone());
+        }
+    }
+    let mut serials: HashMap<Uuid, String> = streams_by_camera
+        .iter()
+        .filter_map(|(uuid, streams)| {
+            let serial = streams.iter().find_map(Stream::serial_number)?;
+            Some((*uuid, serial.to_string()))
+        })
+        .collect();
+
+    let visible: HashSet<Uuid> = cameras::cameras().await.keys().copied().collect();
+    for uuid in &visible {
+        let camera_streams = streams_by_camera
+            .get(uuid)
+            .into_iter()
+            .flatten()
+            .map(|stream| (stream.id, stream.to_camera_stream()))
+            .collect();
+        cameras::set_streams(uuid, camera_streams).await;
+    }
+
+    let changed = {
+        let mut lock = manager.write().await;
+        serials.retain(|uuid, _| visible.contains(uuid));
+        streams_by_camera.retain(|uuid, _| visible.contains(uuid));
+        lock.streams = streams_by_camera;
+        if lock.serials == serials {
+            false
+        } else {
+            lock.serials = serials;
+            true
+        }
+    };
+    if changed {
+        notify_cameras();
+    }
+}
+
 fn stream_failure_detail(error: Option<&str>) -> String {
     error
         .map(str::to_string)
@@ -669,11 +788,8 @@ fn stream_failure_detail(error: Option<&str>) -> String {
 async fn camera_uuid_for_stream_source(source: &Url) -> Option<Uuid> {
     let host = source.host_str()?;
     let ip = host.parse::<Ipv4Addr>().ok()?;
-    let manager = MANAGER.get()?;
-    manager
-        .read()
+    cameras::cameras()
         .await
-        .cameras
         .iter()
         .find(|(_, camera)| camera.hostname == ip)
         .map(|(uuid, _)| *uuid)
@@ -0,0 +1,192 @@
+//! How MCM streams each camera: which channel, in which format, and where to.
+//!
+//! Preferences are persisted in the settings document. Anything left unset falls back to
+//! the first H264 or H265 format the channel offers, and to one RTSP endpoint named after
+//! the camera.
+
+use anyhow::{Result, bail};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tracing::*;
+use ts_rs::TS;
+use url::Url;
+use uuid::Uuid;
+
+use crate::mcm_types::{FrameInterval, VideoEncodeType};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, TS, JsonSchema)]
+pub struct StreamPreferences {
+    /// Camera channel the stream is taken from.
+    #[serde(default)]
+    pub channel: StreamChannel,
+    /// H264 or H265; the first of the two the channel offers when unset.
+    #[serde(default)]
+    pub encode: Option<VideoEncodeType>,
+    /// The first size the format offers when unset.
+    #[serde(default)]
+    pub size: Option<StreamSize>,
+    /// The first frame interval the size offers when unset.
+    #[serde(default)]
+    pub frame_interval: Option<FrameInterval>,
+    /// `rtsp://` or `udp://` URLs MCM publishes the stream to; one RTSP endpoint named
+    /// after the camera when empty.
+    #[serde(default)]
+    pub endpoints: Vec<Url>,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, TS, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamChannel {
+    #[default]
+    Main,
+    Aux,
+    Third,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, TS, JsonSchema)]
+pub struct StreamSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl StreamChannel {
+    /// The channel an ONVIF source URL of the camera serves.
+    pub(crate) fn of_source(source: &str) -> Option<Self> {
+        [Self::Main, Self::Aux, Self::Third]
+            .into_iter()
+            .find(|channel| source.ends_with(channel.source_suffix()))
+    }
+
+    fn source_suffix(self) -> &'static str {
+        match self {
+            Self::Main => "stream_0",
+            Self::Aux => "stream_1",
+            Self::Third => "stream_2",
+        }
+    }
+}
+
+impl StreamPreferences {
+    /// Reject preferences MCM could never stream.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(encode) = &self.encode
+            && !matches!(encode, VideoEncodeType::H264 | VideoEncodeType::H265)
+        {
+            bail!("Only H264 and H265 can be streamed, not {encode:?}");
+        }
+
+        if let Some(frame_interval) = &self.frame_interval
+            && (frame_interval.numerator == 0 || frame_interval.denominator == 0)
+        {
+            bail!("Frame interval must not be zero");
+        }
+
+        for endpoint in &self.endpoints {
+            match endpoint.scheme() {
+                "rtsp" if endpoint.path().trim_matches('/').is_empty() => {
+                    bail!("RTSP endpoint {endpoint} needs a path")
+                }
+                "udp" if endpoint.host().is_none() || endpoint.port().is_none() => {
+                    bail!("UDP endpoint {endpoint} needs a host and a port")
+                }
+                "rtsp" | "udp" => {}
+                scheme => bail!("Unsupported endpoint scheme {scheme:?}, use rtsp or udp"),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// How MCM streams the camera, or the defaults when nothing was set.
+#[instrument(level = "debug")]
+pub async fn stream_preferences(camera_uuid: &Uuid) -> Result<StreamPreferences> {
+    Ok(settings::stream_settings(camera_uuid)
+        .await?
+        .as_ref()
+        .map(StreamPreferences::from)
+        .unwrap_or_default())
+}
+
+/// Persist how MCM streams the camera; its stream is recreated on the next poll.
+#[instrument(level = "debug")]
+pub async fn set_stream_preferences(
+    camera_uuid: Uuid,
+    preferences: StreamPreferences,
+) -> Result<()> {
+    preferences.validate()?;
+
+    let stream = (preferences != StreamPreferences::default())
+        .then(|| settings::CameraStreamSettings::from(&preferences));
+
+    settings::set_stream_settings(camera_uuid, stream).await
+}
+
+impl From<&settings::CameraStreamSettings> for StreamPreferences {
+    fn from(value: &settings::CameraStreamSettings) -> Self {
+        Self {
+            channel: match value.channel {
+                settings::StreamChannel::Main => StreamChannel::Main,
+                settings::StreamChannel::Aux => StreamChannel::Aux,
+                settings::StreamChannel::Third => StreamChannel::Third,
+            },
+            encode: value.encode.map(|encode| match encode {
+                settings::StreamEncode::H264 => VideoEncodeType::H264,
+                settings::StreamEncode::H265 => VideoEncodeType::H265,
+            }),
+            size: value.size.map(|size| StreamSize {
+                width: size.width,
+                height: size.height,
+            }),
+            frame_interval: value.frame_interval.map(|frame_interval| FrameInterval {
+                numerator: frame_interval.numerator,
+                denominator: frame_interval.denominator,
+            }),
+            endpoints: value
+                .endpoints
+                .iter()
+                .filter_map(|endpoint| match endpoint.parse() {
+                    Ok(endpoint) => Some(endpoint),
+                    Err(error) => {
+                        warn!("Ignoring unparsable stream endpoint {endpoint:?}: {error}");
+                        None
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&StreamPreferences> for settings::CameraStreamSettings {
+    fn from(value: &StreamPreferences) -> Self {
+        Self {
+            channel: match value.channel {
+                StreamChannel::Main => settings::StreamChannel::Main,
+                StreamChannel::Aux => settings::StreamChannel::Aux,
+                StreamChannel::Third => settings::StreamChannel::Third,
+            },
+            encode: value.encode.as_ref().and_then(|encode| match encode {
+                VideoEncodeType::H264 => Some(settings::StreamEncode::H264),
+                VideoEncodeType::H265 => Some(settings::StreamEncode::H265),
+                _ => None,
+            }),
+            size: value.size.map(|size| settings::StreamSize {
+                width: size.width,
+                height: size.height,
+            }),
+            frame_interval: value.frame_interval.as_ref().map(|frame_interval| {
+                settings::StreamFrameInterval {
+                    numerator: frame_interval.numerator,
+                    denominator: frame_interval.denominator,
+                }
+            }),
+            endpoints: value
+                .endpoints
+                .iter()
+                .map(|endpoint| endpoint.to_string())
+                .collect(),
+        }
+    }
+}
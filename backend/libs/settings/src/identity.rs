@@ -0,0 +1,139 @@
+//! Which physical camera each `Uuid` MCM hands out belongs to.
+//!
+//! Settings are filed under the hardware serial of the unit (see [`CameraKey`]), while
+//! everything else addresses cameras by `Uuid`. Once the serial of a `Uuid` is bound, that
+//! `Uuid` reads and writes the settings of its unit, whichever `Uuid` they were written
+//! under before. The bindings are saved with the settings and restored when they load.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use uuid::Uuid;
+
+use crate::CameraKey;
+
+static BINDINGS: Lazy<Mutex<Bindings>> = Lazy::new(|| Mutex::new(Bindings::default()));
+
+#[derive(Debug, Default)]
+struct Bindings {
+    serials: HashMap<Uuid, String>,
+}
+
+impl Bindings {
+    fn key(&self, camera_uuid: &Uuid) -> CameraKey {
+        match self.serials.get(camera_uuid) {
+            Some(serial) => CameraKey::Serial(serial.clone()),
+            None => CameraKey::Uuid(*camera_uuid),
+        }
+    }
+
+    fn uuid(&mut self, key: &CameraKey) -> Uuid {
+        let serial = match key {
+            CameraKey::Uuid(uuid) => return *uuid,
+            CameraKey::Serial(serial) => serial,
+        };
+
+        if let Some(camera_uuid) = self.bound_to(serial) {
+            return camera_uuid;
+        }
+
+        // A unit that has not shown up yet still needs a `Uuid` to be addressed by, until it
+        // does and takes over the one MCM assigns it.
+        let placeholder = Uuid::new_v5(&Uuid::NAMESPACE_OID, serial.as_bytes());
+        self.serials.insert(placeholder, serial.clone());
+        placeholder
+    }
+
+    fn bind(&mut self, camera_uuid: Uuid, serial: &str) -> bool {
+        if self.serials.get(&camera_uuid).map(String::as_str) == Some(serial) {
+            return false;
+        }
+
+        // A serial belongs to one unit: whichever `Uuid` it was bound to loses it.
+        self.serials.retain(|_, bound| bound != serial);
+        self.serials.insert(camera_uuid, serial.to_string());
+        true
+    }
+
+    fn bound_to(&self, serial: &str) -> Option<Uuid> {
+        self.serials
+            .iter()
+            .find(|(_, bound)| bound.as_str() == serial)
+            .map(|(camera_uuid, _)| *camera_uuid)
+    }
+}
+
+/// What the settings of `camera_uuid` are filed under.
+pub fn camera_key(camera_uuid: &Uuid) -> CameraKey {
+    BINDINGS.lock().unwrap().key(camera_uuid)
+}
+
+/// The `Uuid` the camera whose settings are filed under `key` is addressed by.
+pub fn camera_uuid(key: &CameraKey) -> Uuid {
+    BINDINGS.lock().unwrap().uuid(key)
+}
+
+/// Record that `camera_uuid` is the unit with `serial`. Returns whether that is news.
+pub(crate) fn bind_serial(camera_uuid: Uuid, serial: &str) -> bool {
+    BINDINGS.lock().unwrap().bind(camera_uuid, serial)
+}
+
+/// Take back the bindings recorded in the settings, as the units have not reported their
+/// serials since startup yet.
+pub(crate) fn restore(serials: &IndexMap<Uuid, String>) {
+    let mut bindings = BINDINGS.lock().unwrap();
+    for (camera_uuid, serial) in serials {
+        bindings.bind(*camera_uuid, serial);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_follow_the_serial_to_a_new_uuid() {
+        let mut bindings = Bindings::default();
+        let (old_uuid, new_uuid) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let key = CameraKey::Serial("SN1".to_string());
+
+        assert_eq!(bindings.key(&old_uuid), CameraKey::Uuid(old_uuid));
+
+        assert!(bindings.bind(old_uuid, "SN1"));
+        assert!(!bindings.bind(old_uuid, "SN1"));
+        assert_eq!(bindings.key(&old_uuid), key);
+
+        assert!(bindings.bind(new_uuid, "SN1"));
+        assert_eq!(bindings.key(&new_uuid), key);
+        assert_eq!(bindings.key(&old_uuid), CameraKey::Uuid(old_uuid));
+        assert_eq!(bindings.uuid(&key), new_uuid);
+    }
+
+    #[test]
+    fn an_unseen_unit_is_addressed_by_a_placeholder_until_it_shows_up() {
+        let mut bindings = Bindings::default();
+        let key = CameraKey::Serial("SN1".to_string());
+
+        let placeholder = bindings.uuid(&key);
+        assert_eq!(bindings.uuid(&key), placeholder);
+        assert_eq!(bindings.key(&placeholder), key);
+
+        let camera_uuid = Uuid::from_u128(1);
+        bindings.bind(camera_uuid, "SN1");
+        assert_eq!(bindings.uuid(&key), camera_uuid);
+        assert_eq!(bindings.key(&placeholder), CameraKey::Uuid(placeholder));
+    }
+
+    #[test]
+    fn keys_read_back_as_serials_or_uuids() {
+        let uuid = Uuid::from_u128(1);
+        let keys: Vec<CameraKey> =
+            serde_json::from_value(serde_json::json!(["SN1", uuid.to_string()])).unwrap();
+
+        assert_eq!(
+            keys,
+            vec![CameraKey::Serial("SN1".to_string()), CameraKey::Uuid(uuid)]
+        );
+    }
+}
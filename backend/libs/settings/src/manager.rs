@@ -9,7 +9,10 @@ use tokio::{fs, io::AsyncWriteExt, sync::RwLock};
 use tracing::*;
 use uuid::Uuid;
 
-use crate::{CameraActuatorsSettings, RawSettingsData, SettingsDataImpl, v1::SettingsDataV1};
+use crate::{
+    CameraActuatorsSettings, CameraKey, CameraStreamSettings, RawSettingsData, SettingsDataImpl,
+    VehicleProfile, v1::SettingsDataV1,
+};
 
 pub static MANAGER: OnceCell<RwLock<Manager>> = OnceCell::new();
 
@@ -17,11 +20,31 @@ pub static MANAGER: OnceCell<RwLock<Manager>> = OnceCell::new();
 /// moment the user changes something, which is long after it starts mattering.
 static LAST_SAVE_ERROR: Mutex<Option<String>> = Mutex::new(None);
 
+/// The backup the settings were restored from at startup, when the live file was unusable.
+static RECOVERED_FROM: Mutex<Option<String>> = Mutex::new(None);
+
+/// Copies of earlier settings kept next to the live file, newest first. Calibrations take
+/// hours to measure, so a handful of older revisions is cheap insurance.
+const BACKUPS_KEPT: usize = 10;
+
 #[derive(Debug)]
 pub struct Manager {
     pub settings: Settings,
 }
 
+/// What identifying the connected autopilot did to the settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VehicleSelection {
+    /// The settings already belong to this vehicle.
+    Unchanged,
+    /// The first vehicle ever identified takes the settings as they are.
+    Claimed,
+    /// The vehicle's own profile replaced the previous vehicle's configuration.
+    Switched,
+    /// No profile exists for this vehicle, and nothing changed.
+    Unknown,
+}
+
 #[derive(Debug)]
 pub struct Settings {
     path: PathBuf,
@@ -31,11 +54,17 @@ pub struct Settings {
 impl Settings {
     pub async fn try_new(
         path: PathBuf,
-        actuators: IndexMap<Uuid, CameraActuatorsSettings>,
+        actuators: IndexMap<CameraKey, CameraActuatorsSettings>,
     ) -> Result<Self> {
         let settings = Self {
             path,
-            inner: Box::new(SettingsDataV1 { actuators }),
+            inner: Box::new(SettingsDataV1 {
+                actuators,
+                vehicle: None,
+                vehicles: IndexMap::default(),
+                streams: IndexMap::default(),
+                serials: IndexMap::default(),
+            }),
         };
 
         settings.save().await?;
@@ -45,8 +74,7 @@ impl Settings {
 
     pub async fn from_path(path: &Path) -> Result<Self> {
         // Reading a backup must still leave the manager writing to the live file, or every
-        // later save lands on the backup — which, with a single fixed backup name, would
-        // copy that file over itself.
+        // later save lands on the backup.
         async fn read_inner(source: &Path, destination: &Path) -> Result<Settings> {
             let contents = fs::read_to_string(source)
                 .await
@@ -55,17 +83,9 @@ impl Settings {
             let raw: RawSettingsData = serde_json::from_str(&contents)
                 .with_context(|| format!("Failed to parse JSON from settings: {source:?}"))?;
 
-            let inner = match raw {
-                RawSettingsData::V1(v1) => Box::new(v1),
-                RawSettingsData::V0(v0) => {
-                    warn!("Migrating settings V0 to V1 from {source:?}");
-                    Box::new(SettingsDataV1::from(v0))
-                }
-            };
-
             let settings = Settings {
                 path: destination.to_owned(),
-                inner,
+                inner: Box::new(migrate(raw)),
             };
 
             settings.save().await?;
@@ -88,12 +108,20 @@ impl Settings {
             }
         }
 
-        let backup_path = backup_path_for(path);
-        if backup_path.exists() {
-            return read_inner(&backup_path, path).await;
+        for backup_path in backup_paths_for(path).await {
+            match read_inner(&backup_path, path).await {
+                Ok(settings) => {
+                    warn!("Recovered settings from backup {backup_path:?}");
+                    if let Ok(mut recovered) = RECOVERED_FROM.lock() {
+                        *recovered = Some(backup_path.to_string_lossy().into_owned());
+                    }
+                    return Ok(settings);
+                }
+                Err(error) => warn!("Skipping unreadable settings backup: {error:#}"),
+            }
         }
 
-        Err(anyhow!("No settings file or backup found"))
+        Err(anyhow!("No readable settings file or backup found"))
     }
 
     pub async fn save(&self) -> Result<()> {
@@ -124,12 +152,14 @@ impl Settings {
                 return Ok(());
             }
 
-            let backup_path = backup_path_for(path);
+            let backup_path = timestamped_backup_path_for(path);
 
             fs::copy(path, &backup_path)
                 .await
                 .with_context(|| format!("Failed to create backup at {backup_path:?}"))?;
             debug!("Created settings backup: {backup_path:?}");
+
+            prune_backups(path).await;
         }
 
         // Rename is atomic within a filesystem, so an unclean power-down can lose the new
@@ -149,22 +179,160 @@ impl Settings {
             .await
             .with_context(|| format!("Failed to replace settings at {settings_file:?}"))?;
 
+        // The rename itself only survives a power cut once the directory entry is flushed.
+        // Not every filesystem can sync a directory, and the file is already safe either way.
+        let directory = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        match fs::File::open(directory).await {
+            Ok(directory) => {
+                if let Err(error) = directory.sync_all().await {
+                    debug!("Failed to flush the settings directory: {error:?}");
+                }
+            }
+            Err(error) => debug!("Failed to open the settings directory: {error:?}"),
+        }
+
         debug!("Wrote new settings to {settings_file:?}:\n{:?}", self.inner);
 
         Ok(())
     }
 
-    pub fn get_actuators(&self) -> &IndexMap<Uuid, CameraActuatorsSettings> {
+    pub fn get_actuators(&self) -> &IndexMap<CameraKey, CameraActuatorsSettings> {
         self.inner.get_actuators()
     }
 
-    pub fn get_actuators_mut(&mut self) -> &mut IndexMap<Uuid, CameraActuatorsSettings> {
+    pub fn get_actuators_mut(&mut self) -> &mut IndexMap<CameraKey, CameraActuatorsSettings> {
         self.inner.get_actuators_mut()
     }
 
+    pub fn get_streams(&self) -> &IndexMap<CameraKey, CameraStreamSettings> {
+        self.inner.get_streams()
+    }
+
+    pub fn get_streams_mut(&mut self) -> &mut IndexMap<CameraKey, CameraStreamSettings> {
+        self.inner.get_streams_mut()
+    }
+
+    /// Replace every stream preference with `streams`. Returns whether anything changed.
+    pub fn set_streams(&mut self, streams: IndexMap<CameraKey, CameraStreamSettings>) -> bool {
+        let current = self.get_streams_mut();
+        if *current == streams {
+            return false;
+        }
+        *current = streams;
+        true
+    }
+
+    /// Drop the stream preferences of `camera_uuids`. Returns whether any were set.
+    pub fn forget_streams(&mut self, camera_uuids: &[Uuid]) -> bool {
+        let streams = self.get_streams_mut();
+        let before = streams.len();
+        for camera_uuid in camera_uuids {
+            streams.shift_remove(&crate::camera_key(camera_uuid));
+        }
+        streams.len() != before
+    }
+
+    /// Forget every camera.
+    pub fn clear(&mut self) {
+        self.get_actuators_mut().clear();
+        self.get_streams_mut().clear();
+    }
+
+    /// Move the stream preferences recorded under `camera_uuid` before its serial was known
+    /// to `serial`, unless the unit already has some. Returns whether anything changed.
+    fn file_streams_under_serial(&mut self, camera_uuid: Uuid, serial: &str) -> bool {
+        let streams = self.get_streams_mut();
+        let Some((index, _, stream)) = streams.shift_remove_full(&CameraKey::Uuid(camera_uuid))
+        else {
+            return false;
+        };
+
+        let key = CameraKey::Serial(serial.to_string());
+        if !streams.contains_key(&key) {
+            streams.shift_insert(index, key, stream);
+        }
+        true
+    }
+
+    pub fn get_serials(&self) -> &IndexMap<Uuid, String> {
+        self.inner.get_serials()
+    }
+
+    /// Record that `camera_uuid` is the unit with `serial`. Returns whether anything changed.
+    fn record_serial(&mut self, camera_uuid: Uuid, serial: &str) -> bool {
+        let serials = self.inner.get_serials_mut();
+        if serials.get(&camera_uuid).map(String::as_str) == Some(serial) {
+            return false;
+        }
+
+        serials.retain(|_, bound| bound != serial);
+        serials.insert(camera_uuid, serial.to_string());
+        true
+    }
+
     pub fn to_raw(&self) -> RawSettingsData {
         self.inner.to_raw()
     }
+
+    pub fn get_vehicle(&self) -> Option<&str> {
+        self.inner.get_vehicle()
+    }
+
+    pub fn get_vehicles(&self) -> &IndexMap<String, VehicleProfile> {
+        self.inner.get_vehicles()
+    }
+
+    /// Make the actuators of the autopilot identified as `identity` the active ones.
+    pub fn select_vehicle(&mut self, identity: &str) -> VehicleSelection {
+        match self.inner.get_vehicle() {
+            Some(vehicle) if vehicle == identity => return VehicleSelection::Unchanged,
+            Some(_) => {}
+            None => {
+                self.inner.set_vehicle(Some(identity.to_owned()));
+                return VehicleSelection::Claimed;
+            }
+        }
+
+        let Some(profile) = self.inner.get_vehicles_mut().shift_remove(identity) else {
+            return VehicleSelection::Unknown;
+        };
+        self.activate_vehicle(identity, profile.actuators);
+
+        VehicleSelection::Switched
+    }
+
+    /// Start a profile for `identity`, from a copy of the active configuration or from
+    /// nothing, and make it the active one.
+    pub fn adopt_vehicle(&mut self, identity: &str, start_empty: bool) {
+        let actuators = if start_empty {
+            IndexMap::new()
+        } else {
+            self.get_actuators().clone()
+        };
+        self.inner.get_vehicles_mut().shift_remove(identity);
+        self.activate_vehicle(identity, actuators);
+    }
+
+    /// Stash the active configuration under its vehicle and activate `actuators`.
+    fn activate_vehicle(
+        &mut self,
+        identity: &str,
+        actuators: IndexMap<CameraKey, CameraActuatorsSettings>,
+    ) {
+        let previous = std::mem::replace(self.get_actuators_mut(), actuators);
+        if let Some(vehicle) = self.inner.get_vehicle().map(str::to_owned) {
+            self.inner.get_vehicles_mut().insert(
+                vehicle,
+                VehicleProfile {
+                    actuators: previous,
+                },
+            );
+        }
+        self.inner.set_vehicle(Some(identity.to_owned()));
+    }
 }
 
 /// Constructs our manager, Should be done inside main
@@ -174,7 +342,7 @@ pub async fn init(settings_file: String, reset: bool) -> Result<()> {
     let settings = match (reset, Settings::from_path(settings_path).await) {
         (false, Ok(settings)) => settings,
         (false, Err(error)) => {
-            if backup_path_for(settings_path).exists() {
+            if !backup_paths_for(settings_path).await.is_empty() {
                 return Err(error).context(
                     "Settings file is unreadable and backup could not be recovered; refusing to overwrite backup with empty settings",
                 );
@@ -193,6 +361,8 @@ pub async fn init(settings_file: String, reset: bool) -> Result<()> {
         }
     };
 
+    crate::identity::restore(settings.get_serials());
+
     if let Some(manager) = MANAGER.get() {
         manager.write().await.settings = settings;
         return Ok(());
@@ -203,17 +373,171 @@ pub async fn init(settings_file: String, reset: bool) -> Result<()> {
     Ok(())
 }
 
+/// Upgrade a settings document of any known version to the current one.
+pub fn migrate(raw: RawSettingsData) -> SettingsDataV1 {
+    match raw {
+        RawSettingsData::V1(v1) => *v1,
+        RawSettingsData::V0(v0) => {
+            warn!("Migrating settings V0 to V1");
+            SettingsDataV1::from(v0)
+        }
+    }
+}
+
+/// Parse and migrate a settings document, e.g. one restored from a backup download.
+pub fn parse_document(document: serde_json::Value) -> Result<SettingsDataV1> {
+    let raw: RawSettingsData =
+        serde_json::from_value(document).context("Not a valid settings document")?;
+
+    Ok(migrate(raw))
+}
+
+/// The persisted settings document, as written to disk.
+#[instrument(level = "debug")]
+pub async fn export() -> Result<RawSettingsData> {
+    let manager = MANAGER.get().context("settings not initialized")?;
+
+    Ok(manager.read().await.settings.to_raw())
+}
+
+/// Select the profile of the autopilot identified as `identity`, persisting any change.
+#[instrument(level = "debug")]
+pub async fn select_vehicle(identity: &str) -> Result<VehicleSelection> {
+    let manager = MANAGER.get().context("settings not initialized")?;
+    let mut guard = manager.write().await;
+
+    let selection = guard.settings.select_vehicle(identity);
+    if matches!(
+        selection,
+        VehicleSelection::Claimed | VehicleSelection::Switched
+    ) {
+        guard.settings.save().await?;
+    }
+
+    Ok(selection)
+}
+
+/// Start and activate a profile for the autopilot identified as `identity`.
+#[instrument(level = "debug")]
+pub async fn adopt_vehicle(identity: &str, start_empty: bool) -> Result<()> {
+    let manager = MANAGER.get().context("settings not initialized")?;
+    let mut guard = manager.write().await;
+
+    guard.settings.adopt_vehicle(identity, start_empty);
+    guard.settings.save().await
+}
+
+/// The active vehicle, and every vehicle with a stored profile.
+#[instrument(level = "debug")]
+pub async fn vehicles() -> Result<(Option<String>, Vec<String>)> {
+    let manager = MANAGER.get().context("settings not initialized")?;
+    let guard = manager.read().await;
+
+    Ok((
+        guard.settings.get_vehicle().map(str::to_owned),
+        guard.settings.get_vehicles().keys().cloned().collect(),
+    ))
+}
+
+/// How MCM should stream the camera, when it has been set.
+#[instrument(level = "debug")]
+pub async fn stream_settings(camera_uuid: &Uuid) -> Result<Option<CameraStreamSettings>> {
+    let manager = MANAGER.get().context("settings not initialized")?;
+
+    Ok(manager
+        .read()
+        .await
+        .settings
+        .get_streams()
+        .get(&crate::camera_key(camera_uuid))
+        .cloned())
+}
+
+/// Persist how MCM should stream the camera; `None` goes back to the defaults.
+#[instrument(level = "debug")]
+pub async fn set_stream_settings(
+    camera_uuid: Uuid,
+    stream: Option<CameraStreamSettings>,
+) -> Result<()> {
+    let manager = MANAGER.get().context("settings not initialized")?;
+    let mut guard = manager.write().await;
+
+    let key = crate::camera_key(&camera_uuid);
+    let streams = guard.settings.get_streams_mut();
+    let unchanged = match stream {
+        Some(stream) => streams.insert(key, stream.clone()) == Some(stream),
+        None => streams.shift_remove(&key).is_none(),
+    };
+    if unchanged {
+        return Ok(());
+    }
+
+    guard.settings.save().await
+}
+
+/// Replace every stream preference with imported ones.
+#[instrument(level = "debug", skip(streams))]
+pub async fn import_stream_settings(
+    streams: IndexMap<CameraKey, CameraStreamSettings>,
+) -> Result<()> {
+    let manager = MANAGER.get().context("settings not initialized")?;
+    let mut guard = manager.write().await;
+
+    if guard.settings.set_streams(streams) {
+        guard.settings.save().await?;
+    }
+
+    Ok(())
+}
+
+/// Drop the stream preferences of cameras that are being forgotten.
+#[instrument(level = "debug")]
+pub async fn forget_stream_settings(camera_uuids: &[Uuid]) -> Result<()> {
+    let manager = MANAGER.get().context("settings not initialized")?;
+    let mut guard = manager.write().await;
+
+    if guard.settings.forget_streams(camera_uuids) {
+        guard.settings.save().await?;
+    }
+
+    Ok(())
+}
+
+/// Record that `camera_uuid` is the unit with `serial`, and file the stream preferences set
+/// before the serial was known under it. Returns whether the binding is new.
+#[instrument(level = "debug")]
+pub async fn adopt_serial(camera_uuid: Uuid, serial: &str) -> Result<bool> {
+    let rebound = crate::identity::bind_serial(camera_uuid, serial);
+
+    let manager = MANAGER.get().context("settings not initialized")?;
+    let mut guard = manager.write().await;
+    let recorded = guard.settings.record_serial(camera_uuid, serial);
+    let filed = guard
+        .settings
+        .file_streams_under_serial(camera_uuid, serial);
+    if recorded || filed {
+        guard.settings.save().await?;
+    }
+
+    Ok(rebound)
+}
+
 /// Text of the last failed settings write, cleared by the next successful one.
 pub fn last_save_error() -> Option<String> {
     LAST_SAVE_ERROR.lock().ok()?.clone()
 }
 
+/// The backup the settings were restored from at startup, if the live file was unusable.
+pub fn recovered_from() -> Option<String> {
+    RECOVERED_FROM.lock().ok()?.clone()
+}
+
 #[instrument(level = "debug")]
 pub async fn clear() -> Result<()> {
     let manager = MANAGER.get().context("settings not initialized")?;
     let mut guard = manager.write().await;
 
-    guard.settings.get_actuators_mut().clear();
+    guard.settings.clear();
     guard.settings.save().await
 }
 
@@ -225,10 +549,58 @@ fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
     path.with_file_name(format!("{name}{suffix}"))
 }
 
-fn backup_path_for(path: &Path) -> PathBuf {
+/// The single backup earlier versions kept, still considered when recovering.
+fn legacy_backup_path_for(path: &Path) -> PathBuf {
     sibling_path(path, ".bak")
 }
 
+/// Timestamps sort like the times they encode, so the newest backup has the greatest name.
+fn timestamped_backup_path_for(path: &Path) -> PathBuf {
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.6fZ");
+    sibling_path(path, &format!(".bak.{timestamp}"))
+}
+
+/// Every backup of the settings at `path`, newest first.
+async fn backup_paths_for(path: &Path) -> Vec<PathBuf> {
+    let prefix = sibling_path(path, ".bak.")
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let directory = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+
+    let mut backups = Vec::new();
+    match fs::read_dir(directory).await {
+        Ok(mut entries) => {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                if entry.file_name().to_string_lossy().starts_with(&prefix) {
+                    backups.push(entry.path());
+                }
+            }
+        }
+        Err(error) => warn!("Failed to list settings backups in {directory:?}: {error:?}"),
+    }
+    backups.sort_unstable_by(|left, right| right.cmp(left));
+
+    let legacy = legacy_backup_path_for(path);
+    if legacy.exists() {
+        backups.push(legacy);
+    }
+
+    backups
+}
+
+async fn prune_backups(path: &Path) {
+    for stale in backup_paths_for(path).await.into_iter().skip(BACKUPS_KEPT) {
+        match fs::remove_file(&stale).await {
+            Ok(()) => debug!("Removed old settings backup {stale:?}"),
+            Err(error) => warn!("Failed to remove old settings backup {stale:?}: {error:?}"),
+        }
+    }
+}
+
 fn temp_path_for(path: &Path) -> PathBuf {
     sibling_path(path, ".tmp")
 }
@@ -301,8 +673,164 @@ fn corrupt_path_for(path: &Path) -> PathBuf {
 mod tests {
     use super::*;
 
+    #[test]
+    fn each_vehicle_keeps_its_own_profile() {
+        let mut settings = Settings {
+            path: PathBuf::from("settings.json"),
+            inner: Box::new(SettingsDataV1 {
+                actuators: IndexMap::default(),
+                vehicle: None,
+                vehicles: IndexMap::default(),
+                streams: IndexMap::default(),
+                serials: IndexMap::default(),
+            }),
+        };
+
+        assert_eq!(settings.select_vehicle("rov-a"), VehicleSelection::Claimed);
+        assert_eq!(
+            settings.select_vehicle("rov-a"),
+            VehicleSelection::Unchanged
+        );
+        assert_eq!(settings.select_vehicle("rov-b"), VehicleSelection::Unknown);
+        assert_eq!(settings.get_vehicle(), Some("rov-a"));
+        assert!(settings.get_vehicles().is_empty());
+
+        settings.adopt_vehicle("rov-b", true);
+        assert_eq!(settings.get_vehicle(), Some("rov-b"));
+        assert_eq!(
+            settings.get_vehicles().keys().collect::<Vec<_>>(),
+            vec!["rov-a"]
+        );
+
+        assert_eq!(settings.select_vehicle("rov-a"), VehicleSelection::Switched);
+        assert_eq!(settings.get_vehicle(), Some("rov-a"));
+        assert_eq!(
+            settings.get_vehicles().keys().collect::<Vec<_>>(),
+            vec!["rov-b"]
+        );
+    }
+
+    fn settings_with_streams(streams: IndexMap<CameraKey, CameraStreamSettings>) -> Settings {
+        Settings {
+            path: PathBuf::from("settings.json"),
+            inner: Box::new(SettingsDataV1 {
+                actuators: IndexMap::default(),
+                vehicle: None,
+                vehicles: IndexMap::default(),
+                streams,
+                serials: IndexMap::default(),
+            }),
+        }
+    }
+
+    fn aux_stream() -> CameraStreamSettings {
+        CameraStreamSettings {
+            channel: crate::StreamChannel::Aux,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn clearing_forgets_the_streams_too() {
+        let camera_uuid = Uuid::from_u128(1);
+        let mut settings = settings_with_streams(IndexMap::from([(
+            CameraKey::Uuid(camera_uuid),
+            aux_stream(),
+        )]));
+
+        settings.clear();
+
+        assert!(settings.get_streams().is_empty());
+    }
+
+    #[test]
+    fn forgotten_cameras_lose_their_streams() {
+        let (forgotten, kept) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let mut settings = settings_with_streams(IndexMap::from([
+            (CameraKey::Uuid(forgotten), aux_stream()),
+            (CameraKey::Uuid(kept), aux_stream()),
+        ]));
+
+        assert!(settings.forget_streams(&[forgotten]));
+        assert!(!settings.forget_streams(&[forgotten]));
+
+        assert_eq!(
+            settings.get_streams().keys().collect::<Vec<_>>(),
+            vec![&CameraKey::Uuid(kept)]
+        );
+    }
+
+    #[test]
+    fn imported_streams_replace_the_current_ones() {
+        let (current, imported) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let mut settings =
+            settings_with_streams(IndexMap::from([(CameraKey::Uuid(current), aux_stream())]));
+
+        let document = parse_document(serde_json::json!({
+            "version": "V1",
+            "actuators": {},
+            "streams": { imported.to_string(): { "channel": "Third" } },
+        }))
+        .unwrap();
+
+        assert!(settings.set_streams(document.streams.clone()));
+        assert!(!settings.set_streams(document.streams));
+        assert_eq!(
+            settings.get_streams()[&CameraKey::Uuid(imported)].channel,
+            crate::StreamChannel::Third
+        );
+        assert!(
+            !settings
+                .get_streams()
+                .contains_key(&CameraKey::Uuid(current))
+        );
+    }
+
+    #[test]
+    fn streams_set_before_the_serial_was_known_follow_it() {
+        let camera_uuid = Uuid::from_u128(1);
+        let mut settings = settings_with_streams(IndexMap::from([(
+            CameraKey::Uuid(camera_uuid),
+            aux_stream(),
+        )]));
+
+        assert!(settings.file_streams_under_serial(camera_uuid, "SN1"));
+        assert!(!settings.file_streams_under_serial(camera_uuid, "SN1"));
+
+        assert_eq!(
+            settings.get_streams().keys().collect::<Vec<_>>(),
+            vec![&CameraKey::Serial("SN1".to_string())]
+        );
+    }
+
+    #[test]
+    fn a_serial_is_recorded_against_one_uuid() {
+        let (old_uuid, new_uuid) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let mut settings = settings_with_streams(IndexMap::default());
+
+        assert!(settings.record_serial(old_uuid, "SN1"));
+        assert!(!settings.record_serial(old_uuid, "SN1"));
+        assert!(settings.record_serial(new_uuid, "SN1"));
+
+        assert_eq!(
+            settings.get_serials(),
+            &IndexMap::from([(new_uuid, "SN1".to_string())])
+        );
+    }
+
+    #[test]
+    fn only_known_documents_parse() {
+        let current =
+            parse_document(serde_json::json!({"version": "V1", "actuators": {}})).unwrap();
+        assert!(current.actuators.is_empty());
+        assert!(current.streams.is_empty());
+
+        assert!(parse_document(serde_json::json!({"version": "V9"})).is_err());
+        assert!(parse_document(serde_json::json!({"version": "V1", "actuators": []})).is_err());
+    }
+
     #[tokio::test]
-    async fn repeated_saves_leave_one_backup_and_no_temporary() -> Result<()> {
+    async fn saves_rotate_backups_and_leave_no_temporary() -> Result<()> {
         let dir = tempfile::tempdir()?;
         let path = dir.path().join("settings.json");
 
@@ -310,21 +838,57 @@ mod tests {
         let expected = fs::read_to_string(&path).await?;
 
         // An unchanged file is skipped, so dirty the live copy to force a real write.
-        for revision in 0..3 {
+        for revision in 0..BACKUPS_KEPT + 3 {
             fs::write(&path, format!("clobbered {revision}")).await?;
             settings.save().await?;
 
             assert_eq!(fs::read_to_string(&path).await?, expected);
         }
 
-        let mut leftovers: Vec<String> = std::fs::read_dir(dir.path())?
+        let leftovers: Vec<String> = std::fs::read_dir(dir.path())?
             .filter_map(|entry| entry.ok())
             .map(|entry| entry.file_name().to_string_lossy().into_owned())
             .filter(|name| name != "settings.json")
             .collect();
-        leftovers.sort();
 
-        assert_eq!(leftovers, vec!["settings.json.bak".to_string()]);
+        assert_eq!(leftovers.len(), BACKUPS_KEPT);
+        assert!(
+            leftovers
+                .iter()
+                .all(|name| name.starts_with("settings.json.bak."))
+        );
+
+        // The newest backups survive the rotation.
+        let newest = backup_paths_for(&path).await;
+        assert_eq!(
+            fs::read_to_string(&newest[0]).await?,
+            format!("clobbered {}", BACKUPS_KEPT + 2)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn corrupted_settings_recover_from_the_newest_valid_backup() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("settings.json");
+
+        let mut settings = Settings::try_new(path.clone(), IndexMap::default()).await?;
+        for vehicle in ["rov-a", "rov-b", "rov-c"] {
+            settings.adopt_vehicle(vehicle, true);
+            settings.save().await?;
+        }
+
+        // A torn live file, and a newest backup that was torn too.
+        fs::write(&path, "{\"version\": \"V1\", \"actu").await?;
+        fs::write(&backup_paths_for(&path).await[0], "").await?;
+
+        let recovered = Settings::from_path(&path).await?;
+
+        assert_eq!(recovered.get_vehicle(), Some("rov-a"));
+        assert!(recovered_from().is_some());
+        assert!(corrupt_path_for(&path).exists());
+        assert_eq!(recovered.path, path);
 
         Ok(())
     }
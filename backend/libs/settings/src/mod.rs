@@ -1,3 +1,4 @@
+mod identity;
 pub mod manager;
 mod v0;
 mod v1;
@@ -10,19 +11,35 @@ use uuid::Uuid;
 pub use v1::*;
 
 use crate::v0::SettingsDataV0;
+pub use identity::{camera_key, camera_uuid};
 
-pub use manager::{MANAGER, Manager, clear, init, last_save_error};
+pub use manager::{
+    MANAGER, Manager, VehicleSelection, adopt_serial, adopt_vehicle, clear, export,
+    forget_stream_settings, import_stream_settings, init, last_save_error, parse_document,
+    recovered_from, select_vehicle, set_stream_settings, stream_settings, vehicles,
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "version")]
 pub enum RawSettingsData {
     V0(SettingsDataV0),
-    V1(SettingsDataV1),
+    V1(Box<SettingsDataV1>),
 }
 
 pub trait SettingsDataImpl: std::fmt::Debug + Send + Sync {
-    fn get_actuators(&self) -> &IndexMap<Uuid, CameraActuatorsSettings>;
-    fn get_actuators_mut(&mut self) -> &mut IndexMap<Uuid, CameraActuatorsSettings>;
+    fn get_actuators(&self) -> &IndexMap<CameraKey, CameraActuatorsSettings>;
+    fn get_actuators_mut(&mut self) -> &mut IndexMap<CameraKey, CameraActuatorsSettings>;
+
+    fn get_vehicle(&self) -> Option<&str>;
+    fn set_vehicle(&mut self, vehicle: Option<String>);
+    fn get_vehicles(&self) -> &IndexMap<String, VehicleProfile>;
+    fn get_vehicles_mut(&mut self) -> &mut IndexMap<String, VehicleProfile>;
+
+    fn get_streams(&self) -> &IndexMap<CameraKey, CameraStreamSettings>;
+    fn get_streams_mut(&mut self) -> &mut IndexMap<CameraKey, CameraStreamSettings>;
+
+    fn get_serials(&self) -> &IndexMap<Uuid, String>;
+    fn get_serials_mut(&mut self) -> &mut IndexMap<Uuid, String>;
 
     fn to_raw(&self) -> RawSettingsData;
 }
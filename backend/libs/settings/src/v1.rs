@@ -6,21 +6,147 @@ use crate::{RawSettingsData, SettingsDataImpl, v0::SettingsDataV0};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SettingsDataV1 {
-    pub actuators: IndexMap<Uuid, CameraActuatorsSettings>,
+    pub actuators: IndexMap<CameraKey, CameraActuatorsSettings>,
+    /// Identity of the autopilot `actuators` belongs to, once one has been identified.
+    #[serde(default)]
+    pub vehicle: Option<String>,
+    /// Configurations of the other vehicles this install has been on, by autopilot identity.
+    #[serde(default)]
+    pub vehicles: IndexMap<String, VehicleProfile>,
+    /// How MCM streams each camera; cameras without an entry use the defaults.
+    #[serde(default)]
+    pub streams: IndexMap<CameraKey, CameraStreamSettings>,
+    /// Hardware serial of each camera `Uuid` that has reported one, so the settings filed
+    /// under a serial are found again after a restart, before the unit reports it anew.
+    #[serde(default)]
+    pub serials: IndexMap<Uuid, String>,
     // todo: pub image: IndexMap<Uuid, CameraImageSettings>,
 }
 
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VehicleProfile {
+    pub actuators: IndexMap<CameraKey, CameraActuatorsSettings>,
+}
+
+/// What the settings of a camera are filed under: the hardware serial of the unit once it
+/// has reported one, else the `Uuid` MCM assigned it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum CameraKey {
+    Serial(String),
+    Uuid(Uuid),
+}
+
+impl From<String> for CameraKey {
+    fn from(value: String) -> Self {
+        match Uuid::parse_str(&value) {
+            Ok(uuid) => Self::Uuid(uuid),
+            Err(_) => Self::Serial(value),
+        }
+    }
+}
+
+impl From<CameraKey> for String {
+    fn from(value: CameraKey) -> Self {
+        value.to_string()
+    }
+}
+
+impl std::fmt::Display for CameraKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Serial(serial) => f.write_str(serial),
+            Self::Uuid(uuid) => uuid.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CameraStreamSettings {
+    #[serde(default)]
+    pub channel: StreamChannel,
+    #[serde(default)]
+    pub encode: Option<StreamEncode>,
+    #[serde(default)]
+    pub size: Option<StreamSize>,
+    #[serde(default)]
+    pub frame_interval: Option<StreamFrameInterval>,
+    /// Endpoint URLs MCM publishes to.
+    #[serde(default)]
+    pub endpoints: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StreamChannel {
+    #[default]
+    Main,
+    Aux,
+    Third,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StreamEncode {
+    H264,
+    H265,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamSize {
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamFrameInterval {
+    pub numerator: u32,
+    pub denominator: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CameraActuatorsSettings {
     pub parameters: ActuatorsParameters,
     pub calibration: Calibration,
     pub state: ActuatorsState,
+    #[serde(default)]
+    pub optics: Option<OpticsMapping>,
+    #[serde(default)]
+    pub motion_profiles: MotionProfiles,
+    #[serde(default)]
+    pub presets: IndexMap<String, ActuatorsPreset>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Calibration {
     pub closest_points: FocusZoomPoints,
     pub furthest_points: FocusZoomPoints,
+    #[serde(default)]
+    pub samples: CalibrationSamples,
+    /// Named curve sets; the active one always mirrors `closest_points`/`furthest_points`.
+    #[serde(default)]
+    pub sets: IndexMap<String, CalibrationSet>,
+    #[serde(default)]
+    pub active_set: Option<String>,
+    #[serde(default)]
+    pub auto_switch: Option<CalibrationAutoSwitch>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CalibrationSet {
+    pub closest_points: FocusZoomPoints,
+    pub furthest_points: FocusZoomPoints,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CalibrationAutoSwitch {
+    pub air_set: String,
+    pub water_set: String,
+    pub depth_threshold: f32,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CalibrationSamples {
+    pub closest: FocusZoomPoints,
+    pub furthest: FocusZoomPoints,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -60,6 +186,8 @@ pub struct ActuatorsParameters {
     pub tilt_mnt_type: MountType,
     pub tilt_mnt_pitch_min: i32,
     pub tilt_mnt_pitch_max: i32,
+    #[serde(default)]
+    pub tilt_backend: TiltBackend,
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -136,6 +264,13 @@ pub enum MountType {
     BrushlessPWM = 7,
 }
 
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TiltBackend {
+    #[default]
+    Servo,
+    GimbalManager,
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
 pub enum TiltChannelFunction {
@@ -154,6 +289,40 @@ pub struct FocusZoomPoint {
     pub zoom: u32,
 }
 
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MotionProfiles {
+    pub focus_max_rate: Option<f32>,
+    pub zoom_max_rate: Option<f32>,
+    pub tilt_max_rate: Option<f32>,
+    pub tilt_max_acceleration: Option<f32>,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ActuatorsPreset {
+    pub focus: Option<f32>,
+    pub zoom: Option<f32>,
+    pub tilt_degrees: Option<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OpticsMapping {
+    pub zoom: Vec<ZoomMappingPoint>,
+    pub focus: Vec<FocusDistancePoint>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ZoomMappingPoint {
+    pub zoom: f32,
+    pub magnification: f32,
+    pub horizontal_fov: f32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct FocusDistancePoint {
+    pub focus: f32,
+    pub distance: f32,
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub struct ActuatorsState {
     pub focus: Option<f32>,
@@ -165,20 +334,56 @@ impl From<SettingsDataV0> for SettingsDataV1 {
     fn from(_v1: SettingsDataV0) -> Self {
         SettingsDataV1 {
             actuators: IndexMap::default(),
+            vehicle: None,
+            vehicles: IndexMap::default(),
+            streams: IndexMap::default(),
+            serials: IndexMap::default(),
         }
     }
 }
 
 impl SettingsDataImpl for SettingsDataV1 {
-    fn get_actuators(&self) -> &IndexMap<Uuid, CameraActuatorsSettings> {
+    fn get_actuators(&self) -> &IndexMap<CameraKey, CameraActuatorsSettings> {
         &self.actuators
     }
 
-    fn get_actuators_mut(&mut self) -> &mut IndexMap<Uuid, CameraActuatorsSettings> {
+    fn get_actuators_mut(&mut self) -> &mut IndexMap<CameraKey, CameraActuatorsSettings> {
         &mut self.actuators
     }
 
+    fn get_vehicle(&self) -> Option<&str> {
+        self.vehicle.as_deref()
+    }
+
+    fn set_vehicle(&mut self, vehicle: Option<String>) {
+        self.vehicle = vehicle;
+    }
+
+    fn get_vehicles(&self) -> &IndexMap<String, VehicleProfile> {
+        &self.vehicles
+    }
+
+    fn get_vehicles_mut(&mut self) -> &mut IndexMap<String, VehicleProfile> {
+        &mut self.vehicles
+    }
+
+    fn get_streams(&self) -> &IndexMap<CameraKey, CameraStreamSettings> {
+        &self.streams
+    }
+
+    fn get_streams_mut(&mut self) -> &mut IndexMap<CameraKey, CameraStreamSettings> {
+        &mut self.streams
+    }
+
+    fn get_serials(&self) -> &IndexMap<Uuid, String> {
+        &self.serials
+    }
+
+    fn get_serials_mut(&mut self) -> &mut IndexMap<Uuid, String> {
+        &mut self.serials
+    }
+
     fn to_raw(&self) -> RawSettingsData {
-        RawSettingsData::V1(self.clone())
+        RawSettingsData::V1(Box::new(self.clone()))
     }
 }
@@ -85,6 +85,39 @@ pub async fn delete<
     send_request(client().delete(url), serde_json::to_string(&body_data)?).await
 }
 
+/// Fetch a binary resource, like an image, instead of JSON.
+#[instrument(level = "debug", skip_all)]
+pub async fn get_bytes<Q: Serialize + std::fmt::Debug>(
+    address: &SocketAddr,
+    path: &str,
+    query_params: Q,
+) -> Result<Vec<u8>> {
+    let url = Url::parse_with_params(
+        &format!("http://{address}/{path}"),
+        to_url_params(query_params),
+    )?;
+
+    let content = client()
+        .get(url)
+        .timeout(std::time::Duration::from_secs(30))
+        .send()
+        .await
+        .inspect_err(|error| {
+            warn!("Error from send(): {error:#?}");
+        })?
+        .error_for_status()
+        .inspect_err(|error| {
+            warn!("Error from error_for_status(): {error:#?}");
+        })?
+        .bytes()
+        .await
+        .inspect_err(|error| {
+            warn!("Error from bytes(): {error:#?}");
+        })?;
+
+    Ok(content.to_vec())
+}
+
 #[instrument(level = "debug", skip_all)]
 pub async fn send_request<D: DeserializeOwned + std::fmt::Debug>(
     request_builder: RequestBuilder,
@@ -106,6 +106,7 @@ function health(overrides: Partial<SystemHealth> = {}): SystemHealth {
     mcm: 'online',
     cameras_discovered: 1,
     expected_missing: [],
+    standalone: false,
     autopilot: 'online',
     lua_scripting_disabled: false,
     lua_script: 'ok',
@@ -167,6 +168,18 @@ function problemCases(): ProblemCase[] {
       }),
       kinds: ['autopilot'],
       degraded: true },
+    { name: 'mavlink_down standalone does not blame BlueOS',
+      input: baseInput({ systemHealth: health({ autopilot: 'mavlink_down', standalone: true }) }),
+      check: (problems) =>
+        problems.length === 1 && problems[0]?.body.includes('BlueOS') === false },
+    { name: 'subsystems left out of the build are not problems',
+      input: baseInput({
+        ...CAMERA,
+        cameraConnectivity: 'online',
+        systemHealth: health({ mcm: 'disabled', autopilot: 'disabled' }),
+      }),
+      kinds: [],
+      degraded: false },
     { name: 'autopilot unknown during boot',
       input: baseInput({ systemHealth: health({ autopilot: 'unknown' }) }),
       kinds: ['autopilot'],
@@ -13,6 +13,7 @@ export type HealthProblemKind =
   | 'lua_scripting_disabled'
   | 'lua_script'
   | 'parameter_drift'
+  | 'unknown_vehicle'
 
 export type HealthProblem = {
   kind: HealthProblemKind
@@ -27,6 +28,7 @@ export type HealthProblem = {
   showForget?: boolean
   showGoToSetup?: boolean
   showUpdateLuaScript?: boolean
+  showAdoptVehicle?: boolean
 }
 
 type KindSpec = {
@@ -139,6 +141,17 @@ const KIND_TABLE = {
       showGoToSetup: true,
     },
   },
+  unknown_vehicle: {
+    selfRecovering: false,
+    recoveryTitle: 'Vehicle recognized',
+    recovered: 'The connected vehicle has its own saved setup',
+    problem: {
+      severity: 'warning',
+      title: 'Unrecognized vehicle',
+      body: 'The connected autopilot has no saved camera setup, so 4K Cam Manager will not change its parameters. Use the current setup on this vehicle, or start an empty one for it.',
+      showAdoptVehicle: true,
+    },
+  },
 } satisfies Record<HealthProblemKind, KindSpec>
 
 const ALL_KINDS = Object.keys(KIND_TABLE) as HealthProblemKind[]
@@ -184,6 +197,15 @@ export function collectHealthProblems(input: HealthProblemsInput): HealthProblem
     const autopilot = autopilotProblem(health)
     if (autopilot) problems.push(autopilot)
 
+    // Shown before setup too: applying it is exactly what this problem blocks.
+    if (health.unknown_vehicle) {
+      problems.push({
+        kind: 'unknown_vehicle',
+        ...KIND_TABLE.unknown_vehicle.problem,
+        detail: health.unknown_vehicle,
+      })
+    }
+
     // SCR_ENABLE / lua script / saved-param drift are fixed by hardware setup.
     // Before any setup, Welcome covers that — don't open System status over it.
     if (input.hardwareConfigured === true) {
@@ -343,9 +365,13 @@ function autopilotProblem(health: SystemHealth): HealthProblem | null {
       kind: 'autopilot',
       severity: 'info',
       title: 'Checking autopilot connection',
-      body: '4K Cam Manager is still determining whether the MAVLink link and flight controller are available. This is normal while BlueOS is starting.',
+      body: health.standalone
+        ? '4K Cam Manager is still determining whether the MAVLink link and flight controller are available.'
+        : '4K Cam Manager is still determining whether the MAVLink link and flight controller are available. This is normal while BlueOS is starting.',
       detail: health.autopilot_detail ?? null,
-      progress: 'Waiting for autopilot status from BlueOS…',
+      progress: health.standalone
+        ? 'Waiting for autopilot status…'
+        : 'Waiting for autopilot status from BlueOS…',
     }
   }
 
@@ -363,6 +389,21 @@ function autopilotProblem(health: SystemHealth): HealthProblem | null {
       }
     case 'mavlink_down': {
       const neverReceived = health.diagnostics.last_frame_age_ms == null
+      // Standalone, a MAVLink router such as mavlink-router forwards the autopilot to us.
+      if (health.standalone) {
+        return {
+          kind: 'autopilot',
+          severity: 'error',
+          title: 'MAVLink connection unavailable',
+          body: neverReceived
+            ? '4K Cam Manager has not received any MAVLink data yet. Focus, zoom and tilt are unavailable. Check that the MAVLink router is running and forwards to the address 4K Cam Manager listens on.'
+            : 'No MAVLink data is reaching 4K Cam Manager. Focus, zoom and tilt are unavailable. Check the MAVLink router and its endpoint for 4K Cam Manager.',
+          detail: health.autopilot_detail ?? null,
+          progress: neverReceived
+            ? 'Waiting for the first MAVLink data…'
+            : 'Waiting for MAVLink data…',
+        }
+      }
       return {
         kind: 'autopilot',
         severity: 'error',